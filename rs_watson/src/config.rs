@@ -1,9 +1,13 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::Frame;
+use crate::hooks::{EventKind, FailurePolicy};
+use crate::strict::Strictness;
+use crate::{ConfigFile, DirectoryContext};
 
 // ---------------------------------------------------------------------------
 // Error
@@ -13,6 +17,8 @@ use crate::Frame;
 pub enum ConfigError {
     #[error("Could not determine config directory")]
     NoConfigDir,
+    #[error("Could not determine data directory")]
+    NoDataDir,
     #[error("Could not read config file {path}: {source}")]
     Read {
         path: PathBuf,
@@ -23,14 +29,50 @@ pub enum ConfigError {
         path: PathBuf,
         source: toml::de::Error,
     },
+    #[error("Invalid project_regex in epic \"{name}\": {source}")]
+    EpicRegex { name: String, source: regex::Error },
+    #[error("Invalid git.ticket_regex: {0}")]
+    TicketRegex(regex::Error),
+    #[error("Invalid config file {path}: {source}")]
+    Syntax {
+        path: PathBuf,
+        source: toml_edit::TomlError,
+    },
+    #[error(
+        "Config file version {found} is newer than this version of rs_watson supports ({supported})"
+    )]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("Unknown config key \"{0}\"")]
+    UnknownKey(String),
+    #[error("\"{0}\" cannot be set directly{1}")]
+    ReadOnlyKey(String, &'static str),
+    #[error("Could not serialize config: {0}")]
+    Serialize(toml::ser::Error),
+    #[error("Could not write config file {path}: {source}")]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("\"{0}\" must be written as [[{0}]] tables to be edited")]
+    NotTableArray(String),
+    #[error("Epic \"{0}\" already exists")]
+    DuplicateEpic(String),
+    #[error("Epic \"{0}\" not found")]
+    UnknownEpic(String),
+    #[error("Epic name must not be empty")]
+    EmptyEpicName,
 }
 
 // ---------------------------------------------------------------------------
 // Top-level Config
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct Config {
+    /// Layout version of the file. Older layouts are migrated when loaded.
+    /// Default: 0 (written before versioning).
+    #[serde(default)]
+    pub version: u32,
     #[serde(default)]
     pub storage: StorageConfig,
     #[serde(default)]
@@ -38,30 +80,152 @@ pub struct Config {
     #[serde(default)]
     pub log: LogConfig,
     #[serde(default)]
+    pub working_hours: WorkingHoursConfig,
+    #[serde(default)]
+    pub aliases: AliasConfig,
+    #[serde(default)]
+    pub git: GitConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
     pub epics: Vec<EpicConfig>,
+    /// The `.watson.toml` layered over this configuration, if any.
+    #[serde(skip)]
+    pub context: Option<DirectoryContext>,
 }
 
 impl Config {
+    /// The layout version written by this release. See [`ConfigFile::migrate`].
+    pub const CURRENT_VERSION: u32 = 1;
+
+    /// Location of `config.toml`: `$RS_WATSON_CONFIG_DIR/config.toml` if set,
+    /// otherwise `rs_watson/config.toml` in the platform config directory.
+    pub fn path() -> Result<PathBuf, ConfigError> {
+        if let Ok(dir) = std::env::var("RS_WATSON_CONFIG_DIR") {
+            return Ok(PathBuf::from(dir).join("config.toml"));
+        }
+        Ok(dirs::config_dir()
+            .ok_or(ConfigError::NoConfigDir)?
+            .join("rs_watson")
+            .join("config.toml"))
+    }
+
+    /// Loads `config.toml`, or the defaults if there is none, and layers the
+    /// nearest `.watson.toml` above the working directory over it. A file in an
+    /// older layout is migrated in memory; it is only rewritten when something
+    /// edits it through [`ConfigFile`].
+    ///
+    /// Precedence, highest first: command-line arguments, environment variables,
+    /// `.watson.toml`, `config.toml`, built-in defaults.
     pub fn load() -> Result<Self, ConfigError> {
-        let path = if let Ok(dir) = std::env::var("RS_WATSON_CONFIG_DIR") {
-            PathBuf::from(dir).join("config.toml")
+        let dir = std::env::current_dir().ok();
+        Self::load_from(&Self::path()?, dir.as_deref())
+    }
+
+    /// [`load`](Self::load) with an explicit `config.toml` and working directory.
+    /// With no `dir`, no `.watson.toml` is looked for.
+    pub fn load_from(path: &Path, dir: Option<&Path>) -> Result<Self, ConfigError> {
+        let file = if path.exists() {
+            let mut file = ConfigFile::open_at(path.to_path_buf())?;
+            file.migrate()?;
+            Some(file)
         } else {
-            dirs::config_dir()
-                .ok_or(ConfigError::NoConfigDir)?
-                .join("rs_watson")
-                .join("config.toml")
+            None
+        };
+        let Some(context_path) = dir.and_then(DirectoryContext::find) else {
+            return file.map_or_else(|| Ok(Config::default()), |f| f.config());
         };
 
-        if !path.exists() {
-            return Ok(Config::default());
+        let mut table = match &file {
+            Some(file) => {
+                // Report problems in config.toml against config.toml first.
+                file.config()?;
+                toml::from_str(&file.to_string()).map_err(|source| ConfigError::Parse {
+                    path: path.to_path_buf(),
+                    source,
+                })?
+            }
+            None => toml::Table::new(),
+        };
+        let context = DirectoryContext::load(&context_path, &mut table)?;
+        let mut config: Config =
+            toml::Value::Table(table)
+                .try_into()
+                .map_err(|source| ConfigError::Parse {
+                    path: context_path.clone(),
+                    source,
+                })?;
+        config.context = Some(context);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Where frames are stored: `$RS_WATSON_DATA_DIR` if set, then
+    /// `storage.data_dir`, then `rs_watson` in the platform data directory.
+    pub fn data_dir(&self) -> Result<PathBuf, ConfigError> {
+        if let Ok(dir) = std::env::var("RS_WATSON_DATA_DIR") {
+            return Ok(PathBuf::from(dir));
+        }
+        if let Some(dir) = &self.storage.data_dir {
+            return Ok(PathBuf::from(dir));
         }
+        Ok(dirs::data_dir()
+            .ok_or(ConfigError::NoDataDir)?
+            .join("rs_watson"))
+    }
 
-        let content = std::fs::read_to_string(&path).map_err(|source| ConfigError::Read {
-            path: path.clone(),
-            source,
-        })?;
+    /// This configuration with `storage.data_dir` resolved as by
+    /// [`data_dir`](Self::data_dir), i.e. with `RS_WATSON_DATA_DIR` applied.
+    pub fn effective(&self) -> Result<Config, ConfigError> {
+        let mut config = self.clone();
+        config.storage.data_dir = Some(self.data_dir()?.display().to_string());
+        Ok(config)
+    }
 
-        toml::from_str(&content).map_err(|source| ConfigError::Parse { path, source })
+    /// The effective value at a dotted key such as `behavior.week_start` or
+    /// `epics.0.name`, or `None` if it is not set.
+    pub fn get(&self, key: &str) -> Result<Option<toml::Value>, ConfigError> {
+        let mut value = toml::Value::try_from(self.effective()?).map_err(ConfigError::Serialize)?;
+        for segment in key.split('.') {
+            let next = match value {
+                toml::Value::Table(mut table) => table.remove(segment),
+                toml::Value::Array(mut items) => segment
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|i| *i < items.len())
+                    .map(|i| items.swap_remove(i)),
+                _ => None,
+            };
+            match next {
+                Some(next) => value = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// Checks settings serde cannot, such as epic regular expressions.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.version > Self::CURRENT_VERSION {
+            return Err(ConfigError::UnsupportedVersion {
+                found: self.version,
+                supported: Self::CURRENT_VERSION,
+            });
+        }
+        if let Some(pattern) = &self.git.ticket_regex {
+            regex::Regex::new(pattern).map_err(ConfigError::TicketRegex)?;
+        }
+        for epic in &self.epics {
+            if let Some(pattern) = &epic.project_regex {
+                crate::epic::compile_project_regex(pattern).map_err(|source| {
+                    ConfigError::EpicRegex {
+                        name: epic.name.clone(),
+                        source,
+                    }
+                })?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -75,7 +239,7 @@ compile_error!(
      (features: storage-json, storage-sqlite)"
 );
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct StorageConfig {
     #[serde(default)]
     pub provider: StorageProvider,
@@ -106,7 +270,7 @@ impl Default for StorageProvider {
 // [behavior]
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct BehaviorConfig {
     /// Allow start, stop and add to accept times in the future. Default: false.
     #[serde(default)]
@@ -114,6 +278,47 @@ pub struct BehaviorConfig {
     /// First day of the week for the "week" date shortcut. Default: monday.
     #[serde(default)]
     pub week_start: WeekStart,
+    /// Active frames running longer than this many hours are flagged as probably
+    /// forgotten and offered to be stopped earlier (0 = disabled). Default: 0.
+    #[serde(default)]
+    pub max_frame_hours: u32,
+    /// Match project and tag filters case-insensitively. Default: false.
+    #[serde(default)]
+    pub ignore_case: bool,
+    /// Reject project names that were never used, registered or allow-listed. Default: false.
+    #[serde(default)]
+    pub strict_projects: bool,
+    /// Reject tag names that were never used or allow-listed. Default: false.
+    #[serde(default)]
+    pub strict_tags: bool,
+    /// Project names always accepted in strict mode. Default: empty.
+    #[serde(default)]
+    pub allowed_projects: Vec<String>,
+    /// Tag names always accepted in strict mode. Default: empty.
+    #[serde(default)]
+    pub allowed_tags: Vec<String>,
+    /// Separator for nested project names such as `acme/website/frontend`. When
+    /// set, reports roll up totals per level and project filters match whole
+    /// subtrees. Default: none (flat project names).
+    #[serde(default)]
+    pub project_separator: Option<String>,
+}
+
+impl BehaviorConfig {
+    /// The idle threshold as a duration, or `None` when idle detection is disabled.
+    pub fn max_frame_duration(&self) -> Option<Duration> {
+        (self.max_frame_hours > 0).then(|| Duration::hours(i64::from(self.max_frame_hours)))
+    }
+
+    /// The strict-mode settings to hand to [`Watson::with_strictness`](crate::Watson::with_strictness).
+    pub fn strictness(&self) -> Strictness {
+        Strictness {
+            projects: self.strict_projects,
+            tags: self.strict_tags,
+            allowed_projects: self.allowed_projects.clone(),
+            allowed_tags: self.allowed_tags.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
@@ -124,11 +329,39 @@ pub enum WeekStart {
     Sunday,
 }
 
+impl WeekStart {
+    /// Position of `day` within the week, starting at 0.
+    pub fn days_into_week(self, day: Weekday) -> u32 {
+        match self {
+            WeekStart::Monday => day.num_days_from_monday(),
+            WeekStart::Sunday => day.num_days_from_sunday(),
+        }
+    }
+
+    /// The days of the week in order, starting with this one.
+    pub fn weekdays(self) -> [Weekday; 7] {
+        let first = match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        };
+        let mut day = first.pred();
+        std::array::from_fn(|_| {
+            day = day.succ();
+            day
+        })
+    }
+
+    /// The first day of the week containing `date`.
+    pub fn start_of_week(self, date: NaiveDate) -> NaiveDate {
+        date - Duration::days(i64::from(self.days_into_week(date.weekday())))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // [log]
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct LogConfig {
     /// Default number of frames shown by `log` (0 = show all). Default: 0.
     #[serde(default)]
@@ -136,29 +369,237 @@ pub struct LogConfig {
 }
 
 // ---------------------------------------------------------------------------
-// [epics]
+// [working_hours]
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct EpicConfig {
-    pub name: String,
-    pub project: String,
+/// The working day used by `gaps` to decide which untracked time matters.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WorkingHoursConfig {
+    /// Start of the working day in local time. Default: 09:00.
+    #[serde(default = "default_day_start")]
+    pub start: NaiveTime,
+    /// End of the working day in local time. Default: 17:00.
+    #[serde(default = "default_day_end")]
+    pub end: NaiveTime,
+    /// Days that count as working days. Default: mon–fri.
+    #[serde(default = "default_work_days")]
+    pub days: Vec<Weekday>,
+    /// Untracked intervals shorter than this are not reported as gaps. Default: 15.
+    #[serde(default = "default_min_gap_minutes")]
+    pub min_gap_minutes: u32,
+}
+
+impl Default for WorkingHoursConfig {
+    fn default() -> Self {
+        Self {
+            start: default_day_start(),
+            end: default_day_end(),
+            days: default_work_days(),
+            min_gap_minutes: default_min_gap_minutes(),
+        }
+    }
+}
+
+impl WorkingHoursConfig {
+    pub fn min_gap(&self) -> Duration {
+        Duration::minutes(i64::from(self.min_gap_minutes))
+    }
+}
+
+fn default_day_start() -> NaiveTime {
+    NaiveTime::from_hms_opt(9, 0, 0).expect("valid time")
+}
+
+fn default_day_end() -> NaiveTime {
+    NaiveTime::from_hms_opt(17, 0, 0).expect("valid time")
+}
+
+fn default_work_days() -> Vec<Weekday> {
+    vec![
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+    ]
+}
+
+fn default_min_gap_minutes() -> u32 {
+    15
+}
+
+// ---------------------------------------------------------------------------
+// [git]
+// ---------------------------------------------------------------------------
+
+/// Integration with the git repository in the working directory. See [`crate::git`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GitConfig {
+    /// Tag frames started inside a git repository with the current branch.
+    /// Default: false.
     #[serde(default)]
-    pub tags: Vec<String>,
+    pub tag_branch: bool,
+    /// Tag with the first match of this regex in the branch name (its first
+    /// capture group, if it has one) instead of the whole name, e.g.
+    /// `[A-Z]+-[0-9]+` for `feature/ABC-123-login`. Branches that do not match
+    /// get no tag. Default: none.
+    #[serde(default)]
+    pub ticket_regex: Option<String>,
+    /// Branches never used as a tag. Default: ["main", "master"].
+    #[serde(default = "default_ignore_branches")]
+    pub ignore_branches: Vec<String>,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            tag_branch: false,
+            ticket_regex: None,
+            ignore_branches: default_ignore_branches(),
+        }
+    }
+}
+
+fn default_ignore_branches() -> Vec<String> {
+    vec!["main".into(), "master".into()]
+}
+
+// ---------------------------------------------------------------------------
+// [hooks]
+// ---------------------------------------------------------------------------
+
+/// Commands run when tracking changes. See [`crate::hooks`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HooksConfig {
+    /// Seconds a hook may run before it is killed and counts as failed.
+    /// Default: 10.
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+    /// What a failing hook does unless it sets its own policy. Default: warn.
+    #[serde(default)]
+    pub on_failure: FailurePolicy,
+    /// Hooks in the order they run. Default: empty.
+    #[serde(default)]
+    pub run: Vec<HookConfig>,
+}
+
+impl HooksConfig {
+    /// Lowers every timeout, the default and per-hook ones, to at most `secs`.
+    pub fn with_max_timeout(mut self, secs: u64) -> Self {
+        self.timeout_secs = self.timeout_secs.min(secs);
+        for hook in &mut self.run {
+            hook.timeout_secs = hook.timeout_secs.map(|t| t.min(secs));
+        }
+        self
+    }
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_hook_timeout_secs(),
+            on_failure: FailurePolicy::default(),
+            run: Vec::new(),
+        }
+    }
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    10
+}
+
+/// One `[[hooks.run]]` entry.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct HookConfig {
+    /// Shell command line (`sh -c`, or `cmd /C` on Windows).
+    pub command: String,
+    /// Events that trigger it. Default: empty (every event).
+    #[serde(default)]
+    pub on: Vec<EventKind>,
+    /// Overrides `hooks.timeout_secs`. Default: none.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Overrides `hooks.on_failure`. Default: none.
+    #[serde(default)]
+    pub on_failure: Option<FailurePolicy>,
+}
+
+// ---------------------------------------------------------------------------
+// [aliases]
+// ---------------------------------------------------------------------------
+
+/// Canonical names with the alternative spellings that should map onto them,
+/// e.g. `backend = ["Backend", "be"]`. Applied whenever a frame is recorded.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct AliasConfig {
+    /// Canonical project name → aliases.
+    #[serde(default)]
+    pub projects: BTreeMap<String, Vec<String>>,
+    /// Canonical tag name → aliases.
+    #[serde(default)]
+    pub tags: BTreeMap<String, Vec<String>>,
+}
+
+impl AliasConfig {
+    /// The canonical name for `name`, or `name` itself when it is not an alias.
+    pub fn canonical_project<'a>(&'a self, name: &'a str) -> &'a str {
+        canonical(&self.projects, name)
+    }
+
+    /// The canonical name for `name`, or `name` itself when it is not an alias.
+    pub fn canonical_tag<'a>(&'a self, name: &'a str) -> &'a str {
+        canonical(&self.tags, name)
+    }
+
+    /// Maps every tag to its canonical name, dropping duplicates this creates.
+    pub fn canonical_tags(&self, tags: Vec<String>) -> Vec<String> {
+        let mut out: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = self.canonical_tag(&tag).to_string();
+            if !out.contains(&tag) {
+                out.push(tag);
+            }
+        }
+        out
+    }
+}
+
+fn canonical<'a>(map: &'a BTreeMap<String, Vec<String>>, name: &'a str) -> &'a str {
+    map.iter()
+        .find(|(_, aliases)| aliases.iter().any(|a| a == name))
+        .map_or(name, |(canonical, _)| canonical.as_str())
 }
 
 // ---------------------------------------------------------------------------
-// Epic resolution
+// [epics]
 // ---------------------------------------------------------------------------
 
-/// Returns the best-matching epic name for a frame.
-/// "Best" means the epic with the most matching tags (most specific rule).
-/// All epic tags must be present in the frame; epic project must match exactly.
-pub fn resolve_epic<'a>(frame: &Frame, epics: &'a [EpicConfig]) -> Option<&'a str> {
-    epics
-        .iter()
-        .filter(|e| e.project == frame.project)
-        .filter(|e| e.tags.iter().all(|t| frame.tags.contains(t)))
-        .max_by_key(|e| e.tags.len())
-        .map(|e| e.name.as_str())
+/// A named grouping of frames. A frame belongs to an epic when its project matches
+/// one of the project rules (or there are none), it has all `tags`, and none of
+/// the `exclude_tags`. See [`crate::epic`] for how ties are broken.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct EpicConfig {
+    pub name: String,
+    /// Exact project name. Default: none.
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Any of these exact project names. Default: empty.
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Glob on the project name: `*` matches any run of characters, `?` a single
+    /// one. Default: none.
+    #[serde(default)]
+    pub project_glob: Option<String>,
+    /// Regular expression that must match the whole project name. Default: none.
+    #[serde(default)]
+    pub project_regex: Option<String>,
+    /// Tags that must all be present on the frame. Default: empty.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Frames carrying any of these tags never match. Default: empty.
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    /// Among matching epics the highest priority wins. Default: 0.
+    #[serde(default)]
+    pub priority: i32,
 }
@@ -0,0 +1,681 @@
+//! Format-preserving edits to `config.toml`.
+//!
+//! [`Config`] is read with serde, which would drop comments and reorder keys on
+//! the way back out. [`ConfigFile`] edits the document in place instead, so
+//! everything that is not touched keeps its comments and formatting.
+//!
+//! Files carry a layout `version`; [`ConfigFile::migrate`] upgrades older ones.
+
+use std::cell::Cell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, TableLike, Value, value};
+
+use crate::config::{Config, ConfigError, EpicConfig};
+
+/// An editable `config.toml`.
+pub struct ConfigFile {
+    path: PathBuf,
+    doc: DocumentMut,
+    /// Set once [`migrate`](Self::migrate) changed the document, until
+    /// [`save`](Self::save) has kept the original as a backup.
+    needs_backup: Cell<bool>,
+}
+
+impl ConfigFile {
+    /// Opens the file at [`Config::path`]; a missing file starts out empty.
+    /// A file in an older layout is migrated, and written in the new layout
+    /// by the next [`save`](Self::save).
+    pub fn open() -> Result<Self, ConfigError> {
+        let mut file = Self::open_at(Config::path()?)?;
+        file.migrate()?;
+        Ok(file)
+    }
+
+    pub fn open_at(path: PathBuf) -> Result<Self, ConfigError> {
+        let content = if path.exists() {
+            std::fs::read_to_string(&path).map_err(|source| ConfigError::Read {
+                path: path.clone(),
+                source,
+            })?
+        } else {
+            String::new()
+        };
+        Self::parse(path, &content)
+    }
+
+    /// Parses `content` as if it had been read from `path`.
+    pub fn parse(path: PathBuf, content: &str) -> Result<Self, ConfigError> {
+        let doc = content.parse().map_err(|source| ConfigError::Syntax {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(Self {
+            path,
+            doc,
+            needs_backup: Cell::new(false),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The configuration as currently edited, validated like [`Config::load`].
+    pub fn config(&self) -> Result<Config, ConfigError> {
+        let config: Config =
+            toml::from_str(&self.doc.to_string()).map_err(|source| ConfigError::Parse {
+                path: self.path.clone(),
+                source,
+            })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Validates the edited configuration and writes it back. The first save
+    /// after a migration keeps the original file as `config.toml.bak`.
+    pub fn save(&self) -> Result<(), ConfigError> {
+        self.config()?;
+        if self.needs_backup.get() && self.path.exists() {
+            self.backup()?;
+        }
+        self.needs_backup.set(false);
+        let write = |source| ConfigError::Write {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(write)?;
+        }
+        std::fs::write(&self.path, self.doc.to_string()).map_err(write)
+    }
+
+    /// Copies the file on disk to `config.toml.bak`, e.g. before a migration.
+    pub fn backup(&self) -> Result<(), ConfigError> {
+        let mut backup = self.path.clone().into_os_string();
+        backup.push(".bak");
+        let backup = PathBuf::from(backup);
+        std::fs::copy(&self.path, &backup)
+            .map(|_| ())
+            .map_err(|source| ConfigError::Write {
+                path: backup,
+                source,
+            })
+    }
+
+    /// Dotted keys in the file that no setting reads, such as a misspelled
+    /// `behavior.week_strat`. Serde would silently ignore them.
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let text = self.doc.to_string();
+        let mut unknown = Vec::new();
+        if let Ok(de) = toml::Deserializer::parse(&text) {
+            let _: Result<Config, _> =
+                serde_ignored::deserialize(de, |path| unknown.push(path.to_string()));
+        }
+        unknown
+    }
+
+    // --- Keys --------------------------------------------------------------
+
+    /// Sets the dotted `key` to `raw`, read as a TOML value (`8`, `true`,
+    /// `["a", "b"]`) or else as a plain string. The file is left unchanged if the
+    /// key is unknown or the value does not fit.
+    pub fn set(&mut self, key: &str, raw: &str) -> Result<(), ConfigError> {
+        let (parents, name) = split_key(key)?;
+        let mut doc = self.doc.clone();
+        let mut table: &mut dyn TableLike = doc.as_table_mut();
+        for segment in parents {
+            let mut implicit = Table::new();
+            implicit.set_implicit(true);
+            table = table
+                .entry(segment)
+                .or_insert(Item::Table(implicit))
+                .as_table_like_mut()
+                .ok_or_else(|| ConfigError::UnknownKey(key.to_string()))?;
+        }
+        let mut new: Value = raw.parse().unwrap_or_else(|_| raw.into());
+        if let Some(old) = table.get(name).and_then(Item::as_value) {
+            // Keep spacing and trailing comments.
+            *new.decor_mut() = old.decor().clone();
+        }
+        table.insert(name, Item::Value(new));
+
+        let edited = ConfigFile {
+            path: self.path.clone(),
+            doc,
+            needs_backup: self.needs_backup.clone(),
+        };
+        if edited.unknown_keys().iter().any(|k| k == key) {
+            return Err(ConfigError::UnknownKey(key.to_string()));
+        }
+        edited.config()?;
+        self.doc = edited.doc;
+        Ok(())
+    }
+
+    /// Removes the dotted `key` so its default applies again. Returns whether
+    /// it was set.
+    pub fn unset(&mut self, key: &str) -> Result<bool, ConfigError> {
+        let (parents, name) = split_key(key)?;
+        let mut table: Option<&mut dyn TableLike> = Some(self.doc.as_table_mut());
+        for segment in parents {
+            table = table
+                .and_then(|t| t.get_mut(segment))
+                .and_then(Item::as_table_like_mut);
+        }
+        Ok(table.and_then(|t| t.remove(name)).is_some())
+    }
+
+    // --- Versions ----------------------------------------------------------
+
+    /// The layout version the file was written in; 0 if it predates versioning.
+    pub fn version(&self) -> u32 {
+        self.doc
+            .get("version")
+            .and_then(Item::as_integer)
+            .and_then(|v| u32::try_from(v).ok())
+            .unwrap_or(0)
+    }
+
+    /// Upgrades the document to [`Config::CURRENT_VERSION`] and returns what
+    /// changed, one line per migration that had something to do. Nothing
+    /// happens for current files. Only the document is changed; see
+    /// [`save`](Self::save).
+    pub fn migrate(&mut self) -> Result<Vec<&'static str>, ConfigError> {
+        let found = self.version();
+        if found > Config::CURRENT_VERSION {
+            return Err(ConfigError::UnsupportedVersion {
+                found,
+                supported: Config::CURRENT_VERSION,
+            });
+        }
+        if found == Config::CURRENT_VERSION {
+            return Ok(Vec::new());
+        }
+        let header = header(&self.doc.to_string()).to_string();
+        let applied: Vec<&'static str> = MIGRATIONS
+            .iter()
+            .filter(|m| m.version > found)
+            .filter(|m| (m.apply)(&mut self.doc))
+            .map(|m| m.description)
+            .collect();
+        self.doc
+            .insert("version", value(i64::from(Config::CURRENT_VERSION)));
+        self.keep_header(&header);
+        if !applied.is_empty() {
+            self.needs_backup.set(true);
+        }
+        Ok(applied)
+    }
+
+    /// Moves `header` back to the top of the document, above `version`.
+    fn keep_header(&mut self, header: &str) {
+        let text = self.doc.to_string();
+        if text.starts_with(header) {
+            return;
+        }
+        let moved = format!("{header}{}", text.replacen(header, "\n", 1));
+        self.doc = moved
+            .parse()
+            .expect("moving comments keeps the document valid");
+    }
+
+    // --- Epics -------------------------------------------------------------
+
+    /// Appends `epic` as a new `[[epics]]` table.
+    pub fn add_epic(&mut self, epic: &EpicConfig) -> Result<(), ConfigError> {
+        check_name(&epic.name)?;
+        if self.config()?.epics.iter().any(|e| e.name == epic.name) {
+            return Err(ConfigError::DuplicateEpic(epic.name.clone()));
+        }
+        let mut table = Table::new();
+        for (key, item) in epic_items(epic) {
+            if let Some(item) = item {
+                table.insert(key, item);
+            }
+        }
+        self.epic_tables()?.push(table);
+        Ok(())
+    }
+
+    /// Replaces the epic called `name` with `epic`. Only keys whose value
+    /// changed are rewritten, so comments inside the table survive.
+    pub fn update_epic(&mut self, name: &str, epic: &EpicConfig) -> Result<(), ConfigError> {
+        check_name(&epic.name)?;
+        let epics = self.config()?.epics;
+        let index = epics
+            .iter()
+            .position(|e| e.name == name)
+            .ok_or_else(|| ConfigError::UnknownEpic(name.to_string()))?;
+        if epic.name != name && epics.iter().any(|e| e.name == epic.name) {
+            return Err(ConfigError::DuplicateEpic(epic.name.clone()));
+        }
+        let old = epic_items(&epics[index]);
+        let table = self
+            .epic_tables()?
+            .get_mut(index)
+            .expect("epic index comes from the same document");
+        for ((key, new), (_, old)) in epic_items(epic).into_iter().zip(old) {
+            if new.as_ref().map(Item::to_string) == old.as_ref().map(Item::to_string) {
+                continue;
+            }
+            match new {
+                Some(item) => table[key] = item,
+                None => {
+                    table.remove(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the epic called `name` and returns it.
+    pub fn remove_epic(&mut self, name: &str) -> Result<EpicConfig, ConfigError> {
+        let mut epics = self.config()?.epics;
+        let index = epics
+            .iter()
+            .position(|e| e.name == name)
+            .ok_or_else(|| ConfigError::UnknownEpic(name.to_string()))?;
+        let tables = self.epic_tables()?;
+        tables.remove(index);
+        if tables.is_empty() {
+            self.doc.remove("epics");
+        }
+        Ok(epics.remove(index))
+    }
+
+    /// The `[[epics]]` array, created if missing.
+    fn epic_tables(&mut self) -> Result<&mut ArrayOfTables, ConfigError> {
+        self.doc
+            .entry("epics")
+            .or_insert(Item::ArrayOfTables(ArrayOfTables::new()))
+            .as_array_of_tables_mut()
+            .ok_or_else(|| ConfigError::NotTableArray("epics".into()))
+    }
+}
+
+impl fmt::Display for ConfigFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.doc)
+    }
+}
+
+/// Splits a dotted key into its parent tables and final name. Epics have their
+/// own commands and the version is managed by [`ConfigFile::migrate`].
+fn split_key(key: &str) -> Result<(Vec<&str>, &str), ConfigError> {
+    let mut segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(ConfigError::UnknownKey(key.to_string()));
+    }
+    match segments[0] {
+        "epics" => Err(ConfigError::ReadOnlyKey(
+            key.to_string(),
+            "; use `watson epics` instead",
+        )),
+        "version" => Err(ConfigError::ReadOnlyKey(key.to_string(), "")),
+        _ => {
+            let name = segments.pop().expect("split yields at least one segment");
+            Ok((segments, name))
+        }
+    }
+}
+
+/// One step of the layout history: upgrades a file to `version`. `apply`
+/// returns whether it changed anything.
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&mut DocumentMut) -> bool,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "epics written as an inline array become [[epics]] tables",
+    apply: epics_as_tables,
+}];
+
+/// `epics = [{ name = "A" }, ...]` → one `[[epics]]` table per entry, which is
+/// the layout `watson epics add/edit/remove` can edit.
+fn epics_as_tables(doc: &mut DocumentMut) -> bool {
+    let Some(Item::Value(Value::Array(array))) = doc.get("epics") else {
+        return false;
+    };
+    let mut tables = ArrayOfTables::new();
+    for entry in array.iter() {
+        match entry.as_inline_table() {
+            Some(inline) => tables.push(inline.clone().into_table()),
+            // Not an epic layout we know; leave it for validation to report.
+            None => return false,
+        }
+    }
+    // Comments above `epics = [...]` move to the first table.
+    let comment = doc
+        .key("epics")
+        .and_then(|key| key.leaf_decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .map(str::to_string);
+    if let (Some(comment), Some(first)) = (comment, tables.get_mut(0)) {
+        first.decor_mut().set_prefix(comment);
+    }
+    doc.insert("epics", Item::ArrayOfTables(tables));
+    true
+}
+
+/// The comment block a file starts with, up to its last blank line. Comments
+/// directly above the first setting belong to that setting instead.
+fn header(text: &str) -> &str {
+    let mut end = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            break;
+        }
+        offset += line.len();
+        if trimmed.is_empty() {
+            end = offset;
+        }
+    }
+    &text[..end]
+}
+
+fn check_name(name: &str) -> Result<(), ConfigError> {
+    if name.trim().is_empty() {
+        return Err(ConfigError::EmptyEpicName);
+    }
+    Ok(())
+}
+
+/// Every key of an epic table in canonical order, `None` where the field has its
+/// default value and is left out.
+fn epic_items(epic: &EpicConfig) -> Vec<(&'static str, Option<Item>)> {
+    let string = |s: &Option<String>| s.as_deref().map(value);
+    let list = |v: &[String]| {
+        (!v.is_empty()).then(|| value(v.iter().map(String::as_str).collect::<Array>()))
+    };
+    vec![
+        ("name", Some(value(&epic.name))),
+        ("project", string(&epic.project)),
+        ("projects", list(&epic.projects)),
+        ("project_glob", string(&epic.project_glob)),
+        ("project_regex", string(&epic.project_regex)),
+        ("tags", list(&epic.tags)),
+        ("exclude_tags", list(&epic.exclude_tags)),
+        (
+            "priority",
+            (epic.priority != 0).then(|| value(i64::from(epic.priority))),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# My settings
+[behavior]
+week_start = "sunday" # US weeks
+
+# Client work
+[[epics]]
+name = "Acme"
+project_glob = "acme-*" # every Acme repo
+tags = ["billable"]
+"#;
+
+    fn file(content: &str) -> ConfigFile {
+        ConfigFile::parse(PathBuf::from("config.toml"), content).unwrap()
+    }
+
+    fn epic(name: &str) -> EpicConfig {
+        EpicConfig {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn add_epic_keeps_existing_comments() {
+        let mut f = file(CONFIG);
+        f.add_epic(&EpicConfig {
+            projects: vec!["docs".into(), "blog".into()],
+            priority: 2,
+            ..epic("Writing")
+        })
+        .unwrap();
+        let out = f.to_string();
+        assert!(out.starts_with("# My settings\n"));
+        assert!(out.contains("week_start = \"sunday\" # US weeks"));
+        assert!(out.contains("project_glob = \"acme-*\" # every Acme repo"));
+        assert!(out.ends_with(
+            "[[epics]]\nname = \"Writing\"\nprojects = [\"docs\", \"blog\"]\npriority = 2\n"
+        ));
+        let epics = f.config().unwrap().epics;
+        assert_eq!(epics.len(), 2);
+        assert_eq!(epics[1].projects, vec!["docs", "blog"]);
+    }
+
+    #[test]
+    fn add_epic_to_empty_file() {
+        let mut f = file("");
+        f.add_epic(&EpicConfig {
+            project: Some("backend".into()),
+            ..epic("Backend")
+        })
+        .unwrap();
+        assert_eq!(
+            f.to_string(),
+            "[[epics]]\nname = \"Backend\"\nproject = \"backend\"\n"
+        );
+    }
+
+    #[test]
+    fn add_epic_rejects_duplicates_and_empty_names() {
+        let mut f = file(CONFIG);
+        assert!(matches!(
+            f.add_epic(&epic("Acme")),
+            Err(ConfigError::DuplicateEpic(_))
+        ));
+        assert!(matches!(
+            f.add_epic(&epic(" ")),
+            Err(ConfigError::EmptyEpicName)
+        ));
+    }
+
+    #[test]
+    fn update_epic_rewrites_only_changed_keys() {
+        let mut f = file(CONFIG);
+        let mut acme = f.config().unwrap().epics.remove(0);
+        acme.tags.clear();
+        acme.priority = 5;
+        f.update_epic("Acme", &acme).unwrap();
+        let out = f.to_string();
+        assert!(out.contains("project_glob = \"acme-*\" # every Acme repo"));
+        assert!(!out.contains("billable"));
+        assert!(out.contains("priority = 5"));
+        assert_eq!(f.config().unwrap().epics[0], acme);
+    }
+
+    #[test]
+    fn update_epic_renames_unless_taken() {
+        let mut f = file(CONFIG);
+        f.add_epic(&epic("Other")).unwrap();
+        assert!(matches!(
+            f.update_epic("Acme", &epic("Other")),
+            Err(ConfigError::DuplicateEpic(_))
+        ));
+        assert!(matches!(
+            f.update_epic("Globex", &epic("Globex")),
+            Err(ConfigError::UnknownEpic(_))
+        ));
+        let renamed = EpicConfig {
+            name: "Acme Corp".into(),
+            ..f.config().unwrap().epics[0].clone()
+        };
+        f.update_epic("Acme", &renamed).unwrap();
+        assert_eq!(f.config().unwrap().epics[0].name, "Acme Corp");
+    }
+
+    #[test]
+    fn remove_epic_drops_the_table() {
+        let mut f = file(CONFIG);
+        let removed = f.remove_epic("Acme").unwrap();
+        assert_eq!(removed.project_glob.as_deref(), Some("acme-*"));
+        let out = f.to_string();
+        assert!(!out.contains("[[epics]]"));
+        assert!(out.contains("week_start = \"sunday\" # US weeks"));
+        assert!(matches!(
+            f.remove_epic("Acme"),
+            Err(ConfigError::UnknownEpic(_))
+        ));
+    }
+
+    #[test]
+    fn inline_epic_arrays_are_not_edited() {
+        let mut f = file("epics = [{ name = \"Acme\" }]\n");
+        assert!(matches!(
+            f.add_epic(&epic("Globex")),
+            Err(ConfigError::NotTableArray(_))
+        ));
+    }
+
+    #[test]
+    fn set_parses_values_and_keeps_comments() {
+        let mut f = file(CONFIG);
+        f.set("behavior.week_start", "monday").unwrap();
+        f.set("behavior.max_frame_hours", "8").unwrap();
+        f.set("log.default_limit", "20").unwrap();
+        let out = f.to_string();
+        assert!(out.contains("week_start = \"monday\" # US weeks"));
+        assert!(out.contains("max_frame_hours = 8"));
+        assert!(out.contains("[log]\ndefault_limit = 20"));
+        let config = f.config().unwrap();
+        assert_eq!(config.behavior.max_frame_hours, 8);
+        assert_eq!(config.log.default_limit, 20);
+    }
+
+    #[test]
+    fn set_rejects_unknown_keys_and_bad_values() {
+        let mut f = file(CONFIG);
+        assert!(matches!(
+            f.set("behavior.week_strat", "monday"),
+            Err(ConfigError::UnknownKey(_))
+        ));
+        assert!(matches!(
+            f.set("behavior.max_frame_hours", "lots"),
+            Err(ConfigError::Parse { .. })
+        ));
+        assert!(matches!(
+            f.set("epics.0.name", "x"),
+            Err(ConfigError::ReadOnlyKey(..))
+        ));
+        assert_eq!(f.to_string(), CONFIG);
+    }
+
+    #[test]
+    fn unset_removes_a_key() {
+        let mut f = file(CONFIG);
+        assert!(f.unset("behavior.week_start").unwrap());
+        assert!(!f.unset("behavior.week_start").unwrap());
+        assert!(!f.unset("log.default_limit").unwrap());
+        assert_eq!(
+            f.config().unwrap().behavior.week_start,
+            crate::config::WeekStart::Monday
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_listed_with_their_path() {
+        let f = file(
+            "[behavior]\nweek_strat = \"sunday\"\n\n[[epics]]\nname = \"A\"\nprojekt = \"x\"\n",
+        );
+        assert_eq!(
+            f.unknown_keys(),
+            vec!["behavior.week_strat", "epics.0.projekt"]
+        );
+        assert!(file(CONFIG).unknown_keys().is_empty());
+    }
+
+    #[test]
+    fn migrate_stamps_version_and_converts_inline_epics() {
+        let mut f = file("# old layout\nepics = [{ name = \"A\", project = \"x\" }]\n");
+        assert_eq!(f.version(), 0);
+        assert_eq!(f.migrate().unwrap().len(), 1);
+        assert_eq!(f.version(), Config::CURRENT_VERSION);
+        let out = f.to_string();
+        assert!(out.contains("[[epics]]\nname = \"A\"\nproject = \"x\"\n"));
+        assert!(out.contains("version = 1"));
+        assert_eq!(f.config().unwrap().epics[0].name, "A");
+        // Already current: nothing to do.
+        assert!(f.migrate().unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_reports_only_migrations_that_changed_something() {
+        let mut f = file("# My settings\n\n[behavior]\nweek_start = \"sunday\"\n");
+        assert!(f.migrate().unwrap().is_empty());
+        assert_eq!(
+            f.to_string(),
+            "# My settings\n\nversion = 1\n\n[behavior]\nweek_start = \"sunday\"\n"
+        );
+    }
+
+    #[test]
+    fn migrate_keeps_the_header_and_epic_comments_on_top() {
+        let mut f = file(
+            "# My settings\n\n# Client work\nepics = [{ name = \"A\" }]\n\n[behavior]\nweek_start = \"sunday\"\n",
+        );
+        assert_eq!(f.migrate().unwrap().len(), 1);
+        let out = f.to_string();
+        assert!(out.starts_with("# My settings\n\nversion = 1\n"), "{out}");
+        assert!(
+            out.contains("# Client work\n[[epics]]\nname = \"A\"\n"),
+            "{out}"
+        );
+        assert_eq!(
+            f.config().unwrap().behavior.week_start,
+            crate::config::WeekStart::Sunday
+        );
+    }
+
+    #[test]
+    fn migrate_refuses_newer_versions() {
+        let mut f = file("version = 99\n");
+        assert!(matches!(
+            f.migrate(),
+            Err(ConfigError::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn get_reads_effective_values_with_defaults() {
+        let config = file(CONFIG).config().unwrap();
+        assert_eq!(
+            config.get("behavior.week_start").unwrap(),
+            Some(toml::Value::String("sunday".into()))
+        );
+        assert_eq!(
+            config.get("log.default_limit").unwrap(),
+            Some(toml::Value::Integer(0))
+        );
+        assert_eq!(
+            config.get("epics.0.name").unwrap(),
+            Some(toml::Value::String("Acme".into()))
+        );
+        assert_eq!(config.get("behavior.project_separator").unwrap(), None);
+        assert_eq!(config.get("epics.5.name").unwrap(), None);
+    }
+
+    #[test]
+    fn save_round_trips_through_disk() {
+        let dir = std::env::temp_dir().join(format!("rs_watson_cfg_{}", uuid::Uuid::new_v4()));
+        let path = dir.join("config.toml");
+        let mut f = ConfigFile::open_at(path.clone()).unwrap();
+        f.add_epic(&epic("Backend")).unwrap();
+        f.save().unwrap();
+        let reopened = ConfigFile::open_at(path).unwrap();
+        assert_eq!(reopened.config().unwrap().epics[0].name, "Backend");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+//! Per-directory defaults from `.watson.toml`.
+//!
+//! The nearest `.watson.toml` at or above the working directory names the
+//! project, tags and epic `watson start` uses when no project is given. Any
+//! other keys are config settings layered over `config.toml`: tables are merged
+//! key by key, `[[epics]]` are appended, and other values replace the global
+//! ones. Environment variables and command-line arguments still win over both.
+//! `[hooks]` and `[storage]` are ignored: a cloned repository must not be able
+//! to run commands or redirect the data just by being the working directory.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{Config, ConfigError, EpicConfig};
+
+/// File name searched for in the working directory and its ancestors.
+pub const CONTEXT_FILE: &str = ".watson.toml";
+
+/// Keys of `.watson.toml` that describe the directory rather than override
+/// config settings.
+const CONTEXT_KEYS: [&str; 3] = ["project", "tags", "epic"];
+
+/// Settings only `config.toml` may set; dropped from `.watson.toml`.
+const GLOBAL_ONLY_KEYS: [&str; 2] = ["hooks", "storage"];
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct DirectoryContext {
+    /// The `.watson.toml` this was read from.
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// Project used by `watson start` without a project. Default: none.
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Tags added when starting without a project. Default: empty.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Epic whose required tags are added when starting without a project. If
+    /// `project` is unset, the epic's project is used when it names exactly one.
+    /// Default: none.
+    #[serde(default)]
+    pub epic: Option<String>,
+}
+
+impl DirectoryContext {
+    /// The nearest `.watson.toml` in `dir` or one of its ancestors.
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|d| d.join(CONTEXT_FILE))
+            .find(|p| p.is_file())
+    }
+
+    /// Reads the context at `path` and merges its config overrides into
+    /// `config`, the parsed global `config.toml`.
+    pub(crate) fn load(path: &Path, config: &mut toml::Table) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let parse = |source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+        // Type-check the overrides on their own so errors point into this file.
+        toml::from_str::<Config>(&content).map_err(parse)?;
+        let mut context: DirectoryContext = toml::from_str(&content).map_err(parse)?;
+        context.path = path.to_path_buf();
+
+        let mut overrides: toml::Table = toml::from_str(&content).map_err(parse)?;
+        for key in CONTEXT_KEYS
+            .iter()
+            .chain(&GLOBAL_ONLY_KEYS)
+            .chain(&["version"])
+        {
+            overrides.remove(*key);
+        }
+        if let Some(toml::Value::Array(epics)) = overrides.remove("epics") {
+            match config.get_mut("epics") {
+                Some(toml::Value::Array(global)) => global.extend(epics),
+                _ => {
+                    config.insert("epics".into(), toml::Value::Array(epics));
+                }
+            }
+        }
+        merge(config, overrides);
+        Ok(context)
+    }
+
+    /// Whether `key`, a top-level key of `.watson.toml`, belongs to the context
+    /// rather than the config.
+    pub fn is_context_key(key: &str) -> bool {
+        CONTEXT_KEYS.contains(&key)
+    }
+
+    /// Project and tags for `watson start` without a project: this context's,
+    /// plus the tags its epic requires. The project is `None` if neither the
+    /// context nor its epic names one.
+    pub fn start_defaults(
+        &self,
+        epics: &[EpicConfig],
+    ) -> Result<(Option<String>, Vec<String>), ConfigError> {
+        let mut project = self.project.clone();
+        let mut tags = self.tags.clone();
+        if let Some(name) = &self.epic {
+            let epic = epics
+                .iter()
+                .find(|e| &e.name == name)
+                .ok_or_else(|| ConfigError::UnknownEpic(name.clone()))?;
+            if project.is_none() {
+                project = match (&epic.project, epic.projects.as_slice()) {
+                    (Some(p), []) | (None, [p]) => Some(p.clone()),
+                    _ => None,
+                };
+            }
+            for tag in &epic.tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+        Ok((project, tags))
+    }
+}
+
+/// Merges `overrides` into `base`: tables recursively, everything else replaced.
+fn merge(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(table)) => merge(base, table),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, content: &str) -> PathBuf {
+        let path = dir.join(CONTEXT_FILE);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn find_walks_up_to_the_nearest_file() {
+        let root = tempfile::tempdir().unwrap();
+        let nested = root.path().join("repo/src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(DirectoryContext::find(&nested), None);
+
+        let outer = write(root.path(), "project = \"outer\"");
+        assert_eq!(DirectoryContext::find(&nested), Some(outer));
+        let inner = write(&root.path().join("repo"), "project = \"inner\"");
+        assert_eq!(DirectoryContext::find(&nested), Some(inner));
+    }
+
+    #[test]
+    fn load_layers_overrides_over_global_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            r#"
+project = "website"
+tags = ["client"]
+
+[behavior]
+week_start = "sunday"
+
+[aliases.projects]
+website = ["web"]
+
+[[epics]]
+name = "Local"
+project = "website"
+"#,
+        );
+        let mut global: toml::Table = toml::from_str(
+            r#"
+version = 1
+[behavior]
+strict_projects = true
+week_start = "monday"
+[aliases.projects]
+backend = ["be"]
+[[epics]]
+name = "Global"
+"#,
+        )
+        .unwrap();
+
+        let context = DirectoryContext::load(&path, &mut global).unwrap();
+        assert_eq!(context.project.as_deref(), Some("website"));
+        assert_eq!(context.tags, vec!["client"]);
+        assert_eq!(context.path, path);
+
+        let config: Config = toml::Value::Table(global).try_into().unwrap();
+        assert!(config.behavior.strict_projects);
+        assert_eq!(config.behavior.week_start, crate::config::WeekStart::Sunday);
+        assert_eq!(config.aliases.projects.len(), 2);
+        let names: Vec<&str> = config.epics.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["Global", "Local"]);
+    }
+
+    #[test]
+    fn load_ignores_hooks_and_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            r#"
+[storage]
+data_dir = "/tmp/elsewhere"
+
+[[hooks.run]]
+command = "touch PWNED"
+"#,
+        );
+        let mut global: toml::Table = toml::from_str(
+            "[hooks]
+timeout_secs = 3
+",
+        )
+        .unwrap();
+        DirectoryContext::load(&path, &mut global).unwrap();
+
+        let config: Config = toml::Value::Table(global).try_into().unwrap();
+        assert!(config.hooks.run.is_empty());
+        assert_eq!(config.hooks.timeout_secs, 3);
+        assert_eq!(config.storage.data_dir, None);
+    }
+
+    #[test]
+    fn load_reports_type_errors_against_the_context_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(dir.path(), "[behavior]\nstrict_projects = \"yes\"\n");
+        let err = DirectoryContext::load(&path, &mut toml::Table::new()).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { path: p, .. } if p == path));
+    }
+
+    #[test]
+    fn start_defaults_take_project_and_tags_from_the_epic() {
+        let epics = vec![EpicConfig {
+            name: "Acme".into(),
+            project: Some("acme-web".into()),
+            tags: vec!["billable".into()],
+            ..Default::default()
+        }];
+        let context = DirectoryContext {
+            tags: vec!["client".into()],
+            epic: Some("Acme".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            context.start_defaults(&epics).unwrap(),
+            (
+                Some("acme-web".into()),
+                vec!["client".into(), "billable".into()]
+            )
+        );
+
+        let unknown = DirectoryContext {
+            epic: Some("Globex".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            unknown.start_defaults(&epics),
+            Err(ConfigError::UnknownEpic(_))
+        ));
+    }
+}
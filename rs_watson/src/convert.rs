@@ -1,6 +1,6 @@
-use rs_watson_storage::{ActiveFrameRecord, FrameRecord};
+use rs_watson_storage::{ActiveFrameRecord, FrameRecord, ProjectRecord};
 
-use crate::{ActiveFrame, Frame};
+use crate::{ActiveFrame, Frame, Project};
 
 impl From<&ActiveFrame> for ActiveFrameRecord {
     fn from(f: &ActiveFrame) -> Self {
@@ -8,6 +8,7 @@ impl From<&ActiveFrame> for ActiveFrameRecord {
             project: f.project.clone(),
             tags: f.tags.clone(),
             start: f.start,
+            last_activity: f.last_activity,
         }
     }
 }
@@ -18,6 +19,7 @@ impl From<ActiveFrameRecord> for ActiveFrame {
             project: r.project,
             tags: r.tags,
             start: r.start,
+            last_activity: r.last_activity,
         }
     }
 }
@@ -45,3 +47,27 @@ impl From<FrameRecord> for Frame {
         }
     }
 }
+
+impl From<&Project> for ProjectRecord {
+    fn from(p: &Project) -> Self {
+        ProjectRecord {
+            name: p.name.clone(),
+            description: p.description.clone(),
+            color: p.color.clone(),
+            client: p.client.clone(),
+            archived: p.archived,
+        }
+    }
+}
+
+impl From<ProjectRecord> for Project {
+    fn from(r: ProjectRecord) -> Self {
+        Project {
+            name: r.name,
+            description: r.description,
+            color: r.color,
+            client: r.client,
+            archived: r.archived,
+        }
+    }
+}
@@ -0,0 +1,395 @@
+//! Epic resolution: which configured epic a frame belongs to.
+//!
+//! When several epics match a frame, the one with the highest `priority` wins;
+//! ties go to the epic requiring the most tags, then to the last in config order.
+
+use std::fmt;
+
+use regex::Regex;
+
+use crate::Frame;
+use crate::config::EpicConfig;
+
+/// Compiles an epic's `project_regex`, anchored so it must match the whole name.
+pub(crate) fn compile_project_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{pattern})$"))
+}
+
+/// Matches `text` against a glob where `*` is any run of characters and `?` is
+/// exactly one character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // Position of the last `*` and the text index it is currently matched up to.
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// The project rule that let a frame through, for explanations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectRule<'a> {
+    /// The epic has no project rules and matches every project.
+    Any,
+    Exact(&'a str),
+    Glob(&'a str),
+    Regex(&'a str),
+}
+
+impl fmt::Display for ProjectRule<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectRule::Any => f.write_str("any project"),
+            ProjectRule::Exact(p) => write!(f, "project \"{p}\""),
+            ProjectRule::Glob(g) => write!(f, "glob \"{g}\""),
+            ProjectRule::Regex(r) => write!(f, "regex \"{r}\""),
+        }
+    }
+}
+
+/// How one epic relates to a frame, as reported by [`EpicResolver::explain`].
+#[derive(Debug)]
+pub struct EpicExplanation<'a> {
+    pub epic: &'a EpicConfig,
+    pub matched: bool,
+    /// One line per rule, saying whether and why it passed.
+    pub reasons: Vec<String>,
+}
+
+struct Rule<'a> {
+    epic: &'a EpicConfig,
+    /// `None` when the epic has no regex, or an invalid one (which never matches).
+    regex: Option<Regex>,
+}
+
+impl<'a> Rule<'a> {
+    fn has_project_rules(&self) -> bool {
+        let e = self.epic;
+        e.project.is_some()
+            || !e.projects.is_empty()
+            || e.project_glob.is_some()
+            || e.project_regex.is_some()
+    }
+
+    fn project_rule(&self, project: &str) -> Option<ProjectRule<'a>> {
+        let e = self.epic;
+        if !self.has_project_rules() {
+            return Some(ProjectRule::Any);
+        }
+        if let Some(p) = e.project.iter().chain(&e.projects).find(|p| *p == project) {
+            return Some(ProjectRule::Exact(p));
+        }
+        if let Some(glob) = e.project_glob.as_deref().filter(|g| glob_match(g, project)) {
+            return Some(ProjectRule::Glob(glob));
+        }
+        if let Some(pattern) = e.project_regex.as_deref()
+            && self.regex.as_ref().is_some_and(|re| re.is_match(project))
+        {
+            return Some(ProjectRule::Regex(pattern));
+        }
+        None
+    }
+
+    fn matches(&self, frame: &Frame) -> bool {
+        self.project_rule(&frame.project).is_some()
+            && self.epic.tags.iter().all(|t| frame.tags.contains(t))
+            && !self
+                .epic
+                .exclude_tags
+                .iter()
+                .any(|t| frame.tags.contains(t))
+    }
+
+    /// Priority first, then specificity. Higher ranks win.
+    fn rank(&self) -> (i32, usize) {
+        (self.epic.priority, self.epic.tags.len())
+    }
+}
+
+/// Resolves frames against a list of epics, compiling their regexes once.
+/// Build one per report rather than per frame.
+pub struct EpicResolver<'a> {
+    rules: Vec<Rule<'a>>,
+}
+
+impl<'a> EpicResolver<'a> {
+    pub fn new(epics: &'a [EpicConfig]) -> Self {
+        let rules = epics
+            .iter()
+            .map(|epic| Rule {
+                epic,
+                regex: epic
+                    .project_regex
+                    .as_deref()
+                    .and_then(|p| compile_project_regex(p).ok()),
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// The epic `frame` belongs to, if any.
+    pub fn resolve(&self, frame: &Frame) -> Option<&'a EpicConfig> {
+        let mut best: Option<&Rule<'a>> = None;
+        for rule in self.rules.iter().filter(|r| r.matches(frame)) {
+            // On equal priority and tag count, the later epic in the config wins.
+            if best.is_none_or(|b| rule.rank() >= b.rank()) {
+                best = Some(rule);
+            }
+        }
+        best.map(|r| r.epic)
+    }
+
+    /// Checks every epic against `frame` and says why each one matches or not.
+    pub fn explain(&self, frame: &Frame) -> Vec<EpicExplanation<'a>> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let e = rule.epic;
+                let mut reasons = Vec::new();
+                match rule.project_rule(&frame.project) {
+                    Some(ProjectRule::Any) => {
+                        reasons.push("✓ any project (no project rule)".into())
+                    }
+                    Some(r) => reasons.push(format!("✓ \"{}\" matches {r}", frame.project)),
+                    None => reasons.push(format!(
+                        "✗ \"{}\" matches none of the project rules",
+                        frame.project
+                    )),
+                }
+                let missing: Vec<&str> = e
+                    .tags
+                    .iter()
+                    .filter(|t| !frame.tags.contains(t))
+                    .map(String::as_str)
+                    .collect();
+                if !e.tags.is_empty() {
+                    if missing.is_empty() {
+                        reasons.push(format!("✓ has all tags: {}", e.tags.join(", ")));
+                    } else {
+                        reasons.push(format!("✗ missing tags: {}", missing.join(", ")));
+                    }
+                }
+                let excluded: Vec<&str> = e
+                    .exclude_tags
+                    .iter()
+                    .filter(|t| frame.tags.contains(t))
+                    .map(String::as_str)
+                    .collect();
+                if !e.exclude_tags.is_empty() {
+                    if excluded.is_empty() {
+                        reasons.push(format!(
+                            "✓ none of the excluded tags: {}",
+                            e.exclude_tags.join(", ")
+                        ));
+                    } else {
+                        reasons.push(format!("✗ has excluded tags: {}", excluded.join(", ")));
+                    }
+                }
+                EpicExplanation {
+                    epic: e,
+                    matched: rule.matches(frame),
+                    reasons,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns the best-matching epic name for a frame. For many frames, build an
+/// [`EpicResolver`] once instead.
+pub fn resolve_epic<'a>(frame: &Frame, epics: &'a [EpicConfig]) -> Option<&'a str> {
+    EpicResolver::new(epics)
+        .resolve(frame)
+        .map(|e| e.name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn frame(project: &str, tags: &[&str]) -> Frame {
+        Frame::new(
+            project,
+            tags.iter().map(|s| s.to_string()).collect(),
+            Utc.with_ymd_and_hms(2026, 1, 15, 9, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2026, 1, 15, 10, 0, 0).unwrap(),
+        )
+    }
+
+    fn epic(name: &str) -> EpicConfig {
+        EpicConfig {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(glob_match("acme-*", "acme-web"));
+        assert!(glob_match("*-web", "acme-web"));
+        assert!(glob_match("a?me*", "acme"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("acme-*", "globex-web"));
+        assert!(!glob_match("a?me", "ame"));
+    }
+
+    #[test]
+    fn epic_spans_several_projects() {
+        let epics = vec![EpicConfig {
+            projects: vec!["backend".into(), "frontend".into()],
+            ..epic("Website")
+        }];
+        assert_eq!(
+            resolve_epic(&frame("frontend", &[]), &epics),
+            Some("Website")
+        );
+        assert_eq!(resolve_epic(&frame("docs", &[]), &epics), None);
+    }
+
+    #[test]
+    fn epic_matches_project_glob_and_regex() {
+        let epics = vec![
+            EpicConfig {
+                project_glob: Some("acme-*".into()),
+                ..epic("Acme")
+            },
+            EpicConfig {
+                project_regex: Some("client-[0-9]+".into()),
+                ..epic("Clients")
+            },
+        ];
+        assert_eq!(resolve_epic(&frame("acme-web", &[]), &epics), Some("Acme"));
+        assert_eq!(
+            resolve_epic(&frame("client-42", &[]), &epics),
+            Some("Clients")
+        );
+        // The regex must match the whole name.
+        assert_eq!(resolve_epic(&frame("client-42-old", &[]), &epics), None);
+    }
+
+    #[test]
+    fn excluded_tags_prevent_a_match() {
+        let epics = vec![EpicConfig {
+            project: Some("backend".into()),
+            exclude_tags: vec!["wip".into()],
+            ..epic("Backend")
+        }];
+        assert_eq!(
+            resolve_epic(&frame("backend", &["api"]), &epics),
+            Some("Backend")
+        );
+        assert_eq!(
+            resolve_epic(&frame("backend", &["api", "wip"]), &epics),
+            None
+        );
+    }
+
+    #[test]
+    fn priority_beats_specificity_and_ties_go_to_the_later_epic() {
+        let epics = vec![
+            EpicConfig {
+                project: Some("backend".into()),
+                tags: vec!["api".into()],
+                ..epic("Specific")
+            },
+            EpicConfig {
+                project_glob: Some("*".into()),
+                priority: 10,
+                ..epic("Urgent")
+            },
+            EpicConfig {
+                project_glob: Some("back*".into()),
+                priority: 10,
+                ..epic("Also urgent")
+            },
+        ];
+        assert_eq!(
+            resolve_epic(&frame("backend", &["api"]), &epics),
+            Some("Also urgent")
+        );
+    }
+
+    #[test]
+    fn equally_specific_epics_resolve_to_the_last_one() {
+        let epics = vec![
+            EpicConfig {
+                project: Some("backend".into()),
+                tags: vec!["api".into()],
+                ..epic("First")
+            },
+            EpicConfig {
+                project: Some("backend".into()),
+                tags: vec!["db".into()],
+                ..epic("Second")
+            },
+            EpicConfig {
+                project: Some("backend".into()),
+                ..epic("Untagged")
+            },
+        ];
+        assert_eq!(
+            resolve_epic(&frame("backend", &["api", "db"]), &epics),
+            Some("Second")
+        );
+        assert_eq!(
+            resolve_epic(&frame("backend", &["api"]), &epics),
+            Some("First")
+        );
+    }
+
+    #[test]
+    fn epic_without_project_rules_matches_by_tags() {
+        let epics = vec![EpicConfig {
+            tags: vec!["sprint-12".into()],
+            ..epic("Sprint 12")
+        }];
+        assert_eq!(
+            resolve_epic(&frame("anything", &["sprint-12"]), &epics),
+            Some("Sprint 12")
+        );
+    }
+
+    #[test]
+    fn explain_lists_reasons_per_epic() {
+        let epics = vec![
+            EpicConfig {
+                project_glob: Some("back*".into()),
+                tags: vec!["api".into()],
+                exclude_tags: vec!["wip".into()],
+                ..epic("Backend")
+            },
+            EpicConfig {
+                project: Some("frontend".into()),
+                ..epic("Frontend")
+            },
+        ];
+        let explained = EpicResolver::new(&epics).explain(&frame("backend", &["api"]));
+        assert!(explained[0].matched);
+        assert_eq!(
+            explained[0].reasons,
+            vec![
+                "✓ \"backend\" matches glob \"back*\"",
+                "✓ has all tags: api",
+                "✓ none of the excluded tags: wip",
+            ]
+        );
+        assert!(!explained[1].matched);
+        assert!(explained[1].reasons[0].starts_with('✗'));
+    }
+}
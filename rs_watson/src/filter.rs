@@ -0,0 +1,211 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::project::in_subtree;
+use crate::{Frame, Query};
+
+/// Criteria for selecting frames — the same ones `log` and `report` offer.
+/// Empty criteria match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameFilter {
+    /// Project name; with `separator` set, nested projects match too.
+    pub project: Option<String>,
+    /// Tags that must all be present on the frame.
+    pub tags: Vec<String>,
+    /// Projects to leave out; with `separator` set, their subprojects too.
+    pub exclude_projects: Vec<String>,
+    /// Frames carrying any of these tags are left out.
+    pub exclude_tags: Vec<String>,
+    /// Expression over project and tags, e.g. `(tag:api or tag:db) and not tag:meeting`.
+    pub query: Option<Query>,
+    /// First day to include (local date of the frame start).
+    pub from: Option<NaiveDate>,
+    /// Last day to include (local date of the frame start).
+    pub to: Option<NaiveDate>,
+    /// Compare project and tag names case-insensitively.
+    pub ignore_case: bool,
+    /// Project path separator (e.g. `/`), so `acme` also matches `acme/website`.
+    pub separator: Option<String>,
+}
+
+impl FrameFilter {
+    pub fn matches(&self, frame: &Frame) -> bool {
+        self.matches_parts(&frame.project, &frame.tags, frame.start)
+    }
+
+    /// Shared by [`matches`](Self::matches) and the storage-level bulk operations in `Watson`.
+    pub(crate) fn matches_parts(
+        &self,
+        project: &str,
+        tags: &[String],
+        start: DateTime<Utc>,
+    ) -> bool {
+        let day = start.with_timezone(&Local).date_naive();
+        self.project
+            .as_deref()
+            .is_none_or(|p| self.same_project(project, p))
+            && self
+                .tags
+                .iter()
+                .all(|t| tags.iter().any(|ft| self.same(ft, t)))
+            && !self
+                .exclude_projects
+                .iter()
+                .any(|p| self.same_project(project, p))
+            && !self
+                .exclude_tags
+                .iter()
+                .any(|t| tags.iter().any(|ft| self.same(ft, t)))
+            && self
+                .query
+                .as_ref()
+                .is_none_or(|q| q.matches_parts(project, tags, self))
+            && self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+    }
+
+    pub(crate) fn same_project(&self, project: &str, root: &str) -> bool {
+        let separator = self.separator.as_deref();
+        if self.ignore_case {
+            in_subtree(&project.to_lowercase(), &root.to_lowercase(), separator)
+        } else {
+            in_subtree(project, root, separator)
+        }
+    }
+
+    pub(crate) fn same(&self, a: &str, b: &str) -> bool {
+        if self.ignore_case {
+            a.to_lowercase() == b.to_lowercase()
+        } else {
+            a == b
+        }
+    }
+
+    /// True when no criteria are set, i.e. every frame matches.
+    pub fn is_empty(&self) -> bool {
+        self.project.is_none()
+            && self.tags.is_empty()
+            && self.exclude_projects.is_empty()
+            && self.exclude_tags.is_empty()
+            && self.query.is_none()
+            && self.from.is_none()
+            && self.to.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn frame(project: &str, tags: &[&str], day: u32) -> Frame {
+        let start = Local
+            .with_ymd_and_hms(2026, 1, day, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc);
+        Frame::new(
+            project,
+            tags.iter().map(|t| t.to_string()).collect(),
+            start,
+            start + chrono::Duration::hours(1),
+        )
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, day).unwrap()
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let filter = FrameFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches(&frame("backend", &[], 15)));
+    }
+
+    #[test]
+    fn project_must_match_exactly() {
+        let filter = FrameFilter {
+            project: Some("backend".into()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&frame("backend", &[], 15)));
+        assert!(!filter.matches(&frame("backend-v2", &[], 15)));
+    }
+
+    #[test]
+    fn all_tags_must_be_present() {
+        let filter = FrameFilter {
+            tags: vec!["api".into(), "auth".into()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&frame("backend", &["auth", "api", "db"], 15)));
+        assert!(!filter.matches(&frame("backend", &["api"], 15)));
+    }
+
+    #[test]
+    fn ignore_case_applies_to_project_and_tags() {
+        let mut filter = FrameFilter {
+            project: Some("backend".into()),
+            tags: vec!["API".into()],
+            ..Default::default()
+        };
+        let f = frame("Backend", &["api"], 15);
+        assert!(!filter.matches(&f));
+        filter.ignore_case = true;
+        assert!(filter.matches(&f));
+    }
+
+    #[test]
+    fn separator_matches_project_subtree() {
+        let mut filter = FrameFilter {
+            project: Some("acme".into()),
+            ..Default::default()
+        };
+        let nested = frame("acme/website", &[], 15);
+        assert!(!filter.matches(&nested));
+        filter.separator = Some("/".into());
+        assert!(filter.matches(&nested));
+        assert!(filter.matches(&frame("acme", &[], 15)));
+        assert!(!filter.matches(&frame("acmecorp", &[], 15)));
+    }
+
+    #[test]
+    fn excluded_projects_and_tags_are_left_out() {
+        let filter = FrameFilter {
+            exclude_projects: vec!["acme".into()],
+            exclude_tags: vec!["meeting".into(), "break".into()],
+            separator: Some("/".into()),
+            ..Default::default()
+        };
+        assert!(!filter.is_empty());
+        assert!(filter.matches(&frame("backend", &["api"], 15)));
+        assert!(!filter.matches(&frame("acme", &[], 15)));
+        assert!(!filter.matches(&frame("acme/website", &[], 15)));
+        assert!(!filter.matches(&frame("backend", &["api", "meeting"], 15)));
+    }
+
+    #[test]
+    fn query_uses_the_filter_name_rules() {
+        let filter = FrameFilter {
+            query: Some(Query::parse("project:acme and not tag:MEETING").unwrap()),
+            ignore_case: true,
+            separator: Some("/".into()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&frame("Acme/website", &["api"], 15)));
+        assert!(!filter.matches(&frame("acme", &["meeting"], 15)));
+        assert!(!filter.matches(&frame("other", &[], 15)));
+    }
+
+    #[test]
+    fn date_range_is_inclusive() {
+        let filter = FrameFilter {
+            from: Some(date(14)),
+            to: Some(date(15)),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&frame("backend", &[], 13)));
+        assert!(filter.matches(&frame("backend", &[], 14)));
+        assert!(filter.matches(&frame("backend", &[], 15)));
+        assert!(!filter.matches(&frame("backend", &[], 16)));
+    }
+}
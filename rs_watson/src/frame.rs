@@ -26,6 +26,46 @@ impl Frame {
             end,
         }
     }
+
+    /// Appends each of `tags` not already present. Returns whether anything changed.
+    pub fn add_tags(&mut self, tags: &[String]) -> bool {
+        add_missing_tags(&mut self.tags, tags)
+    }
+
+    /// Drops each of `tags` from the frame. Returns whether anything changed.
+    pub fn remove_tags(&mut self, tags: &[String]) -> bool {
+        remove_listed_tags(&mut self.tags, tags)
+    }
+}
+
+pub(crate) fn add_missing_tags(list: &mut Vec<String>, tags: &[String]) -> bool {
+    let before = list.len();
+    for tag in tags {
+        if !list.contains(tag) {
+            list.push(tag.clone());
+        }
+    }
+    list.len() != before
+}
+
+pub(crate) fn remove_listed_tags(list: &mut Vec<String>, tags: &[String]) -> bool {
+    let before = list.len();
+    list.retain(|t| !tags.contains(t));
+    list.len() != before
+}
+
+/// Replaces `from` with `to` in place, or just drops `from` when `to` is
+/// already present. Returns whether anything changed.
+pub(crate) fn replace_tag(list: &mut Vec<String>, from: &str, to: &str) -> bool {
+    let Some(pos) = list.iter().position(|t| t == from) else {
+        return false;
+    };
+    if list.iter().any(|t| t == to) {
+        list.remove(pos);
+    } else {
+        list[pos] = to.to_string();
+    }
+    true
 }
 
 /// A currently running time tracking entry — no end time yet.
@@ -34,6 +74,8 @@ pub struct ActiveFrame {
     pub project: String,
     pub tags: Vec<String>,
     pub start: DateTime<Utc>,
+    /// Last recorded user activity while tracking — see `Watson::record_activity`.
+    pub last_activity: Option<DateTime<Utc>>,
 }
 
 impl ActiveFrame {
@@ -42,6 +84,7 @@ impl ActiveFrame {
             project: project.into(),
             tags,
             start,
+            last_activity: None,
         }
     }
 
@@ -49,6 +92,13 @@ impl ActiveFrame {
     pub fn stop(self, end: DateTime<Utc>) -> Frame {
         Frame::new(self.project, self.tags, self.start, end)
     }
+
+    /// Materializes the running frame as a provisional [`Frame`] ending at `now`,
+    /// without touching storage. Each call produces a fresh ID, so callers that
+    /// need to recognise the frame later should hold on to the returned value.
+    pub fn provisional(&self, now: DateTime<Utc>) -> Frame {
+        self.clone().stop(now.max(self.start))
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +110,36 @@ mod tests {
         Utc.with_ymd_and_hms(2026, 1, 15, h, m, 0).unwrap()
     }
 
+    fn tags(list: &[&str]) -> Vec<String> {
+        list.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn add_tags_skips_existing() {
+        let mut f = Frame::new("backend", tags(&["api"]), t(9, 0), t(10, 0));
+        assert!(f.add_tags(&tags(&["api", "db"])));
+        assert_eq!(f.tags, tags(&["api", "db"]));
+        assert!(!f.add_tags(&tags(&["db"])));
+    }
+
+    #[test]
+    fn remove_tags_reports_change() {
+        let mut f = Frame::new("backend", tags(&["api", "db"]), t(9, 0), t(10, 0));
+        assert!(f.remove_tags(&tags(&["api", "ghost"])));
+        assert_eq!(f.tags, tags(&["db"]));
+        assert!(!f.remove_tags(&tags(&["api"])));
+    }
+
+    #[test]
+    fn replace_tag_keeps_position_and_avoids_duplicates() {
+        let mut list = tags(&["api", "db"]);
+        assert!(replace_tag(&mut list, "api", "rest"));
+        assert_eq!(list, tags(&["rest", "db"]));
+        assert!(replace_tag(&mut list, "rest", "db"));
+        assert_eq!(list, tags(&["db"]));
+        assert!(!replace_tag(&mut list, "ghost", "db"));
+    }
+
     #[test]
     fn frame_new_stores_correct_fields() {
         let f = Frame::new("backend", vec!["api".into()], t(9, 0), t(10, 0));
@@ -86,4 +166,21 @@ mod tests {
         assert_eq!(frame.end, t(10, 30));
         assert_eq!(frame.end - frame.start, Duration::minutes(90));
     }
+
+    #[test]
+    fn active_frame_provisional_ends_at_now_and_keeps_active() {
+        let active = ActiveFrame::new("backend", vec!["api".into()], t(9, 0));
+        let frame = active.provisional(t(9, 45));
+        assert_eq!(frame.project, "backend");
+        assert_eq!(frame.start, t(9, 0));
+        assert_eq!(frame.end, t(9, 45));
+        assert_eq!(active.start, t(9, 0));
+    }
+
+    #[test]
+    fn active_frame_provisional_never_ends_before_start() {
+        let active = ActiveFrame::new("backend", vec![], t(10, 0));
+        let frame = active.provisional(t(9, 0));
+        assert_eq!(frame.end, frame.start);
+    }
 }
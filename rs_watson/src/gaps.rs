@@ -0,0 +1,287 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+
+use crate::Frame;
+use crate::config::WorkingHoursConfig;
+
+/// An untracked interval inside working hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Gap {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+
+    /// The frame bordering this gap — the one ending where the gap starts,
+    /// otherwise the one starting where it ends. Used to pre-fill a new frame.
+    pub fn neighbor<'a>(&self, frames: &'a [Frame]) -> Option<&'a Frame> {
+        frames
+            .iter()
+            .find(|f| f.end == self.start)
+            .or_else(|| frames.iter().find(|f| f.start == self.end))
+    }
+}
+
+/// Finds untracked intervals within working hours on every working day from
+/// `from` to `to` (inclusive, dates in `tz`). Working time after `now` is
+/// ignored, and gaps shorter than `hours.min_gap_minutes` are dropped.
+pub fn find_gaps<Tz: TimeZone>(
+    frames: &[Frame],
+    from: NaiveDate,
+    to: NaiveDate,
+    hours: &WorkingHoursConfig,
+    tz: &Tz,
+    now: DateTime<Utc>,
+) -> Vec<Gap> {
+    let mut sorted: Vec<&Frame> = frames.iter().collect();
+    sorted.sort_by_key(|f| f.start);
+
+    let mut gaps = Vec::new();
+    for date in from.iter_days().take_while(|d| *d <= to) {
+        if !hours.days.contains(&date.weekday()) {
+            continue;
+        }
+        // Days where a DST switch swallows the boundary are skipped.
+        let (Some(day_start), Some(day_end)) = (
+            tz.from_local_datetime(&date.and_time(hours.start))
+                .earliest(),
+            tz.from_local_datetime(&date.and_time(hours.end)).earliest(),
+        ) else {
+            continue;
+        };
+        let day_start = day_start.with_timezone(&Utc);
+        let day_end = day_end.with_timezone(&Utc).min(now);
+
+        let mut cursor = day_start;
+        for f in sorted
+            .iter()
+            .filter(|f| f.end > day_start && f.start < day_end)
+        {
+            if f.start > cursor {
+                gaps.push(Gap {
+                    start: cursor,
+                    end: f.start,
+                });
+            }
+            cursor = cursor.max(f.end);
+        }
+        if cursor < day_end {
+            gaps.push(Gap {
+                start: cursor,
+                end: day_end,
+            });
+        }
+    }
+
+    gaps.retain(|g| g.duration() >= hours.min_gap());
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    // 2026-01-15 is a Thursday.
+    fn t(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, h, m, 0).unwrap()
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+    }
+
+    fn frame(project: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Frame {
+        Frame {
+            id: uuid::Uuid::new_v4(),
+            project: project.into(),
+            tags: vec![],
+            start,
+            end,
+        }
+    }
+
+    fn later() -> DateTime<Utc> {
+        t(23, 0)
+    }
+
+    #[test]
+    fn whole_working_day_is_a_gap_without_frames() {
+        let gaps = find_gaps(
+            &[],
+            day(15),
+            day(15),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            later(),
+        );
+        assert_eq!(
+            gaps,
+            vec![Gap {
+                start: t(9, 0),
+                end: t(17, 0)
+            }]
+        );
+    }
+
+    #[test]
+    fn finds_gaps_around_and_between_frames() {
+        let frames = vec![
+            frame("backend", t(10, 0), t(12, 0)),
+            frame("frontend", t(13, 0), t(16, 0)),
+        ];
+        let gaps = find_gaps(
+            &frames,
+            day(15),
+            day(15),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            later(),
+        );
+        assert_eq!(
+            gaps.iter().map(|g| (g.start, g.end)).collect::<Vec<_>>(),
+            vec![
+                (t(9, 0), t(10, 0)),
+                (t(12, 0), t(13, 0)),
+                (t(16, 0), t(17, 0))
+            ]
+        );
+    }
+
+    #[test]
+    fn frames_outside_working_hours_cover_their_part() {
+        let frames = vec![
+            frame("backend", t(7, 0), t(11, 0)),
+            frame("backend", t(15, 0), t(20, 0)),
+        ];
+        let gaps = find_gaps(
+            &frames,
+            day(15),
+            day(15),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            later(),
+        );
+        assert_eq!(
+            gaps,
+            vec![Gap {
+                start: t(11, 0),
+                end: t(15, 0)
+            }]
+        );
+    }
+
+    #[test]
+    fn overlapping_frames_do_not_produce_gaps() {
+        let frames = vec![
+            frame("backend", t(9, 0), t(14, 0)),
+            frame("backend", t(10, 0), t(12, 0)),
+            frame("backend", t(14, 0), t(17, 0)),
+        ];
+        let gaps = find_gaps(
+            &frames,
+            day(15),
+            day(15),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            later(),
+        );
+        assert!(gaps.is_empty());
+    }
+
+    #[test]
+    fn short_gaps_are_dropped() {
+        let frames = vec![
+            frame("backend", t(9, 0), t(12, 0)),
+            frame("backend", t(12, 10), t(17, 0)),
+        ];
+        let gaps = find_gaps(
+            &frames,
+            day(15),
+            day(15),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            later(),
+        );
+        assert!(gaps.is_empty());
+    }
+
+    #[test]
+    fn skips_non_working_days() {
+        // 2026-01-17/18 is a weekend.
+        let gaps = find_gaps(
+            &[],
+            day(16),
+            day(19),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            Utc.with_ymd_and_hms(2026, 1, 20, 0, 0, 0).unwrap(),
+        );
+        let days: Vec<_> = gaps.iter().map(|g| g.start.date_naive()).collect();
+        assert_eq!(days, vec![day(16), day(19)]);
+    }
+
+    #[test]
+    fn ignores_time_after_now() {
+        let frames = vec![frame("backend", t(9, 0), t(11, 0))];
+        let gaps = find_gaps(
+            &frames,
+            day(15),
+            day(15),
+            &WorkingHoursConfig::default(),
+            &Utc,
+            t(12, 30),
+        );
+        assert_eq!(
+            gaps,
+            vec![Gap {
+                start: t(11, 0),
+                end: t(12, 30)
+            }]
+        );
+    }
+
+    #[test]
+    fn respects_custom_working_hours() {
+        let hours = WorkingHoursConfig {
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            ..Default::default()
+        };
+        let frames = vec![frame("backend", t(9, 0), t(12, 0))];
+        let gaps = find_gaps(&frames, day(15), day(15), &hours, &Utc, later());
+        assert_eq!(
+            gaps,
+            vec![Gap {
+                start: t(8, 0),
+                end: t(9, 0)
+            }]
+        );
+    }
+
+    #[test]
+    fn neighbor_prefers_preceding_frame() {
+        let frames = vec![
+            frame("backend", t(9, 0), t(10, 0)),
+            frame("frontend", t(11, 0), t(12, 0)),
+        ];
+        let gap = Gap {
+            start: t(10, 0),
+            end: t(11, 0),
+        };
+        assert_eq!(gap.neighbor(&frames).unwrap().project, "backend");
+        let first = Gap {
+            start: t(8, 0),
+            end: t(9, 0),
+        };
+        assert_eq!(first.neighbor(&frames).unwrap().project, "backend");
+        let none = Gap {
+            start: t(13, 0),
+            end: t(14, 0),
+        };
+        assert!(none.neighbor(&frames).is_none());
+    }
+}
@@ -0,0 +1,239 @@
+//! The local git repository a command runs in: its current branch, used to tag
+//! frames, and the commits made while a frame was running.
+//!
+//! Everything goes through the `git` executable against the local repository;
+//! no remote is ever contacted.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::Frame;
+use crate::config::GitConfig;
+
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("Could not run git: {0}")]
+    Spawn(std::io::Error),
+    #[error("{} is not inside a git repository", .0.display())]
+    NotARepository(PathBuf),
+    #[error("git {command} failed: {stderr}")]
+    Failed { command: String, stderr: String },
+    #[error("Unexpected output from git log: {0:?}")]
+    Output(String),
+}
+
+/// A commit as listed by [`commits`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// Abbreviated hash.
+    pub id: String,
+    /// Author date, which unlike the commit date survives rebases.
+    pub time: DateTime<Utc>,
+    pub author: String,
+    /// First line of the message.
+    pub summary: String,
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(GitError::Spawn)?;
+    if !output.status.success() {
+        return Err(GitError::Failed {
+            command: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The branch checked out in the repository containing `dir`. `None` outside a
+/// repository, on a detached HEAD, or when git is not installed.
+pub fn current_branch(dir: &Path) -> Option<String> {
+    let out = git(dir, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok()?;
+    let branch = out.trim();
+    (!branch.is_empty()).then(|| branch.to_string())
+}
+
+/// The tag for `branch`: the branch name itself, or the ticket ID that
+/// `ticket_regex` finds in it. `None` for ignored or non-matching branches.
+pub fn branch_tag(branch: &str, config: &GitConfig) -> Option<String> {
+    if config.ignore_branches.iter().any(|b| b == branch) {
+        return None;
+    }
+    let Some(pattern) = &config.ticket_regex else {
+        return Some(branch.to_string());
+    };
+    // Checked by `Config::validate`, so an invalid pattern only means no tag.
+    let caps = Regex::new(pattern).ok()?.captures(branch)?;
+    caps.get(1)
+        .or_else(|| caps.get(0))
+        .map(|m| m.as_str().to_string())
+}
+
+/// The tag for a frame started in `dir`, if `tag_branch` is on and `dir` is
+/// inside a repository.
+pub fn start_tag(dir: &Path, config: &GitConfig) -> Option<String> {
+    if !config.tag_branch {
+        return None;
+    }
+    branch_tag(&current_branch(dir)?, config)
+}
+
+/// Commits on the local branches of the repository containing `dir` that were
+/// authored from `from` up to `to`, oldest first. When `user.email` is set,
+/// only that author's commits are listed.
+pub fn commits(
+    dir: &Path,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Commit>, GitError> {
+    if git(dir, &["rev-parse", "--git-dir"]).is_err() {
+        return Err(GitError::NotARepository(dir.to_path_buf()));
+    }
+    // The commit date is never before the author date, so `--since` is a safe
+    // pre-filter; `--until` is not, as rebased commits have later commit dates.
+    let since = format!("--since={}", from.to_rfc3339());
+    let mut args = vec![
+        "log",
+        "--branches",
+        "--fixed-strings",
+        "--format=%h%x1f%at%x1f%an%x1f%s",
+        &since,
+    ];
+    let email = git(dir, &["config", "user.email"]).unwrap_or_default();
+    let author = format!("--author={}", email.trim());
+    if !email.trim().is_empty() {
+        args.push(&author);
+    }
+    let mut commits: Vec<Commit> = parse_log(&git(dir, &args)?)?
+        .into_iter()
+        .filter(|c| c.time >= from && c.time < to)
+        .collect();
+    commits.sort_by_key(|c| c.time);
+    Ok(commits)
+}
+
+/// Parses `git log --format=%h%x1f%at%x1f%an%x1f%s` output.
+fn parse_log(output: &str) -> Result<Vec<Commit>, GitError> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let bad = || GitError::Output(line.to_string());
+            let mut fields = line.splitn(4, '\x1f');
+            let (Some(id), Some(time), Some(author), Some(summary)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(bad());
+            };
+            let time = time
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .ok_or_else(bad)?;
+            Ok(Commit {
+                id: id.to_string(),
+                time,
+                author: author.to_string(),
+                summary: summary.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Assigns each commit to the frames that were running when it was authored,
+/// keyed by frame id. Frames without commits are left out.
+pub fn commits_by_frame(frames: &[Frame], commits: &[Commit]) -> HashMap<Uuid, Vec<Commit>> {
+    let mut out: HashMap<Uuid, Vec<Commit>> = HashMap::new();
+    for frame in frames {
+        let during: Vec<Commit> = commits
+            .iter()
+            .filter(|c| c.time >= frame.start && c.time < frame.end)
+            .cloned()
+            .collect();
+        if !during.is_empty() {
+            out.insert(frame.id, during);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 5, 14, h, m, 0).unwrap()
+    }
+
+    fn commit(id: &str, time: DateTime<Utc>) -> Commit {
+        Commit {
+            id: id.into(),
+            time,
+            author: "Ada".into(),
+            summary: "Change".into(),
+        }
+    }
+
+    #[test]
+    fn branch_tag_uses_the_branch_or_its_ticket_id() {
+        let mut config = GitConfig::default();
+        assert_eq!(
+            branch_tag("feature/login", &config).as_deref(),
+            Some("feature/login")
+        );
+        assert_eq!(branch_tag("main", &config), None);
+
+        config.ticket_regex = Some("[A-Z]+-[0-9]+".into());
+        assert_eq!(
+            branch_tag("feature/ABC-123-login", &config).as_deref(),
+            Some("ABC-123")
+        );
+        assert_eq!(branch_tag("feature/login", &config), None);
+
+        config.ticket_regex = Some("^issue-([0-9]+)".into());
+        assert_eq!(branch_tag("issue-42-crash", &config).as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn parse_log_reads_fields_and_rejects_garbage() {
+        let commits =
+            parse_log("a1b2c3d\x1f1778749200\x1fAda Lovelace\x1fFix: a\x1fb\n\n").unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].id, "a1b2c3d");
+        assert_eq!(commits[0].time, at(9, 0));
+        assert_eq!(commits[0].author, "Ada Lovelace");
+        assert_eq!(commits[0].summary, "Fix: a\x1fb");
+
+        assert!(matches!(
+            parse_log("fatal: something"),
+            Err(GitError::Output(_))
+        ));
+    }
+
+    #[test]
+    fn commits_are_assigned_to_the_frames_they_fall_in() {
+        let morning = Frame::new("backend", vec![], at(9, 0), at(10, 0));
+        let afternoon = Frame::new("frontend", vec![], at(13, 0), at(14, 0));
+        let commits = vec![
+            commit("a", at(9, 30)),
+            commit("b", at(10, 0)),
+            commit("c", at(13, 0)),
+        ];
+        let by_frame = commits_by_frame(&[morning.clone(), afternoon.clone()], &commits);
+        let ids = |id: Uuid| -> Vec<&str> { by_frame[&id].iter().map(|c| c.id.as_str()).collect() };
+        assert_eq!(ids(morning.id), vec!["a"]);
+        assert_eq!(ids(afternoon.id), vec!["c"]);
+    }
+}
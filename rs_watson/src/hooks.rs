@@ -0,0 +1,463 @@
+//! External commands run when tracking changes, configured in `[hooks]`.
+//!
+//! [`Watson`](crate::Watson) emits an [`Event`] once a change has been checked
+//! and just before it is saved. Each hook subscribed to the event runs through
+//! the shell with the event as JSON on stdin and as `WATSON_*` environment
+//! variables. A hook that fails or outlives its timeout either produces a
+//! warning ([`FailurePolicy::Warn`]) or cancels the change
+//! ([`FailurePolicy::Abort`]). `abort` hooks run before `warn` hooks, for every
+//! event of a change, so a cancelled change has run no `warn` hook.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::config::{HookConfig, HooksConfig};
+use crate::{ActiveFrame, Frame};
+
+/// The kinds of [`Event`], as named in `hooks.run.on` and `WATSON_EVENT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Started,
+    Stopped,
+    Cancelled,
+    Added,
+    Edited,
+    Removed,
+    Renamed,
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EventKind::Started => "started",
+            EventKind::Stopped => "stopped",
+            EventKind::Cancelled => "cancelled",
+            EventKind::Added => "added",
+            EventKind::Edited => "edited",
+            EventKind::Removed => "removed",
+            EventKind::Renamed => "renamed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A change about to be saved. Splitting a frame is reported as an edit plus an
+/// addition, merging as an edit plus removals.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Started(ActiveFrame),
+    Stopped(Frame),
+    Cancelled(ActiveFrame),
+    Added(Frame),
+    Edited {
+        previous: Frame,
+        frame: Frame,
+    },
+    Removed(Frame),
+    /// A project renamed, with its frames and any nested projects.
+    Renamed {
+        from: String,
+        to: String,
+    },
+}
+
+/// What a failing hook does.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailurePolicy {
+    /// Let the change through and report the failure.
+    #[default]
+    Warn,
+    /// Cancel the change.
+    Abort,
+}
+
+/// A hook that exited unsuccessfully, timed out, or could not be started.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Hook \"{command}\" failed on {event}: {reason}")]
+pub struct HookFailure {
+    pub command: String,
+    pub event: EventKind,
+    pub reason: String,
+}
+
+/// A frame as passed to hooks. Active frames have neither `id` nor `stop`.
+#[derive(Serialize)]
+struct FramePayload<'a> {
+    id: Option<Uuid>,
+    project: &'a str,
+    tags: &'a [String],
+    start: DateTime<Utc>,
+    stop: Option<DateTime<Utc>>,
+}
+
+impl<'a> From<&'a Frame> for FramePayload<'a> {
+    fn from(f: &'a Frame) -> Self {
+        Self {
+            id: Some(f.id),
+            project: &f.project,
+            tags: &f.tags,
+            start: f.start,
+            stop: Some(f.end),
+        }
+    }
+}
+
+impl<'a> From<&'a ActiveFrame> for FramePayload<'a> {
+    fn from(f: &'a ActiveFrame) -> Self {
+        Self {
+            id: None,
+            project: &f.project,
+            tags: &f.tags,
+            start: f.start,
+            stop: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    event: EventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frame: Option<FramePayload<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<FramePayload<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<&'a str>,
+}
+
+impl Event {
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Event::Started(_) => EventKind::Started,
+            Event::Stopped(_) => EventKind::Stopped,
+            Event::Cancelled(_) => EventKind::Cancelled,
+            Event::Added(_) => EventKind::Added,
+            Event::Edited { .. } => EventKind::Edited,
+            Event::Removed(_) => EventKind::Removed,
+            Event::Renamed { .. } => EventKind::Renamed,
+        }
+    }
+
+    fn payload(&self) -> Payload<'_> {
+        let mut payload = Payload {
+            event: self.kind(),
+            frame: None,
+            previous: None,
+            from: None,
+            to: None,
+        };
+        match self {
+            Event::Started(f) | Event::Cancelled(f) => payload.frame = Some(f.into()),
+            Event::Stopped(f) | Event::Added(f) | Event::Removed(f) => {
+                payload.frame = Some(f.into())
+            }
+            Event::Edited { previous, frame } => {
+                payload.frame = Some(frame.into());
+                payload.previous = Some(previous.into());
+            }
+            Event::Renamed { from, to } => {
+                payload.from = Some(from);
+                payload.to = Some(to);
+            }
+        }
+        payload
+    }
+
+    /// The event as the JSON document written to a hook's stdin.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.payload()).expect("hook payload serializes")
+    }
+
+    /// The `WATSON_*` environment variables passed to hooks: `WATSON_EVENT`,
+    /// then the frame as `WATSON_ID`, `WATSON_PROJECT`, `WATSON_TAGS`
+    /// (comma-separated), `WATSON_START` and `WATSON_STOP` (RFC 3339), or
+    /// `WATSON_FROM` and `WATSON_TO` for a rename.
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let payload = self.payload();
+        let mut env = vec![("WATSON_EVENT", payload.event.to_string())];
+        if let Some(frame) = &payload.frame {
+            if let Some(id) = frame.id {
+                env.push(("WATSON_ID", id.to_string()));
+            }
+            env.push(("WATSON_PROJECT", frame.project.to_string()));
+            env.push(("WATSON_TAGS", frame.tags.join(",")));
+            env.push(("WATSON_START", frame.start.to_rfc3339()));
+            if let Some(stop) = frame.stop {
+                env.push(("WATSON_STOP", stop.to_rfc3339()));
+            }
+        }
+        if let (Some(from), Some(to)) = (payload.from, payload.to) {
+            env.push(("WATSON_FROM", from.to_string()));
+            env.push(("WATSON_TO", to.to_string()));
+        }
+        env
+    }
+}
+
+impl HookConfig {
+    /// Whether this hook runs for `kind`. No `on` list means every event.
+    pub fn runs_on(&self, kind: EventKind) -> bool {
+        self.on.is_empty() || self.on.contains(&kind)
+    }
+}
+
+/// Runs the hooks subscribed to `events`, the parts of one change: every
+/// `abort` hook first, then the `warn` hooks, each in config order. Returns the
+/// failures of `warn` hooks, or the first failure of an `abort` hook, after
+/// which no further hooks run, so a change cancelled on any of its events runs
+/// no `warn` hook at all.
+pub fn run(config: &HooksConfig, events: &[Event]) -> Result<Vec<HookFailure>, HookFailure> {
+    let mut warnings = Vec::new();
+    let inputs: Vec<_> = events.iter().map(|e| (e.to_json(), e.env())).collect();
+    for policy in [FailurePolicy::Abort, FailurePolicy::Warn] {
+        for (event, input) in events.iter().zip(&inputs) {
+            let kind = event.kind();
+            let hooks = config
+                .run
+                .iter()
+                .filter(|h| h.runs_on(kind) && h.on_failure.unwrap_or(config.on_failure) == policy);
+            for hook in hooks {
+                let timeout = Duration::from_secs(hook.timeout_secs.unwrap_or(config.timeout_secs));
+                let Err(reason) = run_command(&hook.command, &input.0, &input.1, timeout) else {
+                    continue;
+                };
+                let failure = HookFailure {
+                    command: hook.command.clone(),
+                    event: kind,
+                    reason,
+                };
+                match policy {
+                    FailurePolicy::Warn => warnings.push(failure),
+                    FailurePolicy::Abort => return Err(failure),
+                }
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+/// Runs `command` with `input` on stdin, killing it after `timeout`. The error
+/// is a short reason, ending with the hook's stderr if it wrote any.
+fn run_command(
+    command: &str,
+    input: &str,
+    env: &[(&'static str, String)],
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = shell(command)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks are free to ignore stdin and exit before reading it.
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    // Read stderr on a thread so a chatty hook cannot block on a full pipe, and
+    // a background process keeping the pipe open cannot block us.
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        std::thread::spawn(move || {
+            let mut out = String::new();
+            let _ = stderr.read_to_string(&mut out);
+            let _ = tx.send(out);
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+    let wait = deadline
+        .saturating_duration_since(Instant::now())
+        .min(Duration::from_millis(100));
+    let stderr = rx.recv_timeout(wait).unwrap_or_default();
+    let reason = match status.code() {
+        Some(code) => format!("exited with status {code}"),
+        None => "killed by a signal".to_string(),
+    };
+    match stderr.trim() {
+        "" => Err(reason),
+        message => Err(format!("{reason}: {message}")),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn hook(command: &str) -> HookConfig {
+        HookConfig {
+            command: command.into(),
+            ..Default::default()
+        }
+    }
+
+    fn started() -> Event {
+        let start = Utc.with_ymd_and_hms(2026, 5, 14, 9, 0, 0).unwrap();
+        Event::Started(ActiveFrame::new("backend", vec!["api".into()], start))
+    }
+
+    #[test]
+    fn payload_and_env_describe_the_frame() {
+        let event = started();
+        assert_eq!(
+            event.to_json(),
+            r#"{"event":"started","frame":{"id":null,"project":"backend","tags":["api"],"start":"2026-05-14T09:00:00Z","stop":null}}"#
+        );
+        let env = event.env();
+        assert!(env.contains(&("WATSON_EVENT", "started".into())));
+        assert!(env.contains(&("WATSON_PROJECT", "backend".into())));
+        assert!(!env.iter().any(|(k, _)| *k == "WATSON_STOP"));
+
+        let renamed = Event::Renamed {
+            from: "a".into(),
+            to: "b".into(),
+        };
+        assert_eq!(
+            renamed.to_json(),
+            r#"{"event":"renamed","from":"a","to":"b"}"#
+        );
+    }
+
+    #[test]
+    fn hooks_receive_stdin_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let config = HooksConfig {
+            run: vec![hook(&format!(
+                "cat > {0}; echo \" $WATSON_EVENT $WATSON_TAGS\" >> {0}",
+                out.display()
+            ))],
+            ..Default::default()
+        };
+        assert_eq!(run(&config, &[started()]), Ok(vec![]));
+        let written = std::fs::read_to_string(out).unwrap();
+        assert!(written.starts_with("{\"event\":\"started\""));
+        assert!(written.ends_with(" started api\n"));
+    }
+
+    #[test]
+    fn only_subscribed_hooks_run() {
+        let config = HooksConfig {
+            run: vec![HookConfig {
+                on: vec![EventKind::Stopped],
+                ..hook("exit 1")
+            }],
+            ..Default::default()
+        };
+        assert_eq!(run(&config, &[started()]), Ok(vec![]));
+    }
+
+    #[test]
+    fn failures_warn_or_abort_by_policy() {
+        let config = HooksConfig {
+            run: vec![
+                hook("echo oops >&2; exit 3"),
+                HookConfig {
+                    on_failure: Some(FailurePolicy::Abort),
+                    timeout_secs: Some(0),
+                    ..hook("sleep 5")
+                },
+                hook("exit 1"),
+            ],
+            ..Default::default()
+        };
+        let mut warn_only = config.clone();
+        warn_only.run.remove(1);
+        let warnings = run(&warn_only, &[started()]).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].reason, "exited with status 3: oops");
+
+        let abort = run(&config, &[started()]).unwrap_err();
+        assert_eq!(abort.command, "sleep 5");
+        assert_eq!(abort.reason, "timed out after 0s");
+    }
+
+    #[test]
+    fn abort_hooks_check_every_event_before_warn_hooks_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let config = HooksConfig {
+            run: vec![
+                hook(&format!("echo $WATSON_EVENT >> {}", out.display())),
+                HookConfig {
+                    on_failure: Some(FailurePolicy::Abort),
+                    ..hook("[ \"$WATSON_EVENT\" != cancelled ]")
+                },
+            ],
+            ..Default::default()
+        };
+        let Event::Started(active) = started() else {
+            unreachable!()
+        };
+        let abort = run(&config, &[started(), Event::Cancelled(active)]).unwrap_err();
+        assert_eq!(abort.event, EventKind::Cancelled);
+        assert!(!out.exists());
+
+        assert_eq!(run(&config, &[started()]), Ok(vec![]));
+        assert_eq!(std::fs::read_to_string(out).unwrap(), "started\n");
+    }
+
+    #[test]
+    fn max_timeout_caps_default_and_per_hook_timeouts() {
+        let config = HooksConfig {
+            timeout_secs: 10,
+            run: vec![
+                HookConfig {
+                    timeout_secs: Some(30),
+                    ..hook("a")
+                },
+                HookConfig {
+                    timeout_secs: Some(1),
+                    ..hook("b")
+                },
+                hook("c"),
+            ],
+            ..Default::default()
+        }
+        .with_max_timeout(2);
+        assert_eq!(config.timeout_secs, 2);
+        let per_hook: Vec<Option<u64>> = config.run.iter().map(|h| h.timeout_secs).collect();
+        assert_eq!(per_hook, vec![Some(2), Some(1), None]);
+    }
+}
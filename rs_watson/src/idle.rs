@@ -0,0 +1,99 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::ActiveFrame;
+
+/// An active frame that has been running longer than the configured maximum
+/// (`behavior.max_frame_hours`) — most likely someone forgot to stop it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overrun {
+    pub active: ActiveFrame,
+    /// How long the frame has been running as of the check.
+    pub elapsed: Duration,
+    /// The moment the frame crossed the threshold (`start + max`).
+    pub threshold: DateTime<Utc>,
+}
+
+/// Where a suggested stop time for an overrun frame comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopSuggestion {
+    /// The last recorded activity while the frame was running.
+    LastActivity,
+    /// The moment the frame crossed `max_frame_hours`.
+    Threshold,
+}
+
+impl Overrun {
+    /// Returns an `Overrun` if `active` has been running for longer than `max` at `now`.
+    pub fn detect(active: &ActiveFrame, max: Duration, now: DateTime<Utc>) -> Option<Self> {
+        let elapsed = now - active.start;
+        (elapsed > max).then(|| Overrun {
+            active: active.clone(),
+            elapsed,
+            threshold: active.start + max,
+        })
+    }
+
+    /// Candidate stop times, most plausible first. Last activity is only offered
+    /// when it was recorded after the frame started and before the threshold.
+    pub fn suggestions(&self) -> Vec<(StopSuggestion, DateTime<Utc>)> {
+        let mut out = Vec::new();
+        if let Some(at) = self
+            .active
+            .last_activity
+            .filter(|at| *at > self.active.start && *at < self.threshold)
+        {
+            out.push((StopSuggestion::LastActivity, at));
+        }
+        out.push((StopSuggestion::Threshold, self.threshold));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn t(h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 15, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn detect_returns_none_within_threshold() {
+        let active = ActiveFrame::new("backend", vec![], t(9));
+        assert!(Overrun::detect(&active, Duration::hours(8), t(17)).is_none());
+    }
+
+    #[test]
+    fn detect_flags_frame_past_threshold() {
+        let active = ActiveFrame::new("backend", vec![], t(8));
+        let overrun = Overrun::detect(&active, Duration::hours(8), t(20)).unwrap();
+        assert_eq!(overrun.elapsed, Duration::hours(12));
+        assert_eq!(overrun.threshold, t(16));
+    }
+
+    #[test]
+    fn suggestions_prefer_last_activity() {
+        let mut active = ActiveFrame::new("backend", vec![], t(8));
+        active.last_activity = Some(t(14));
+        let overrun = Overrun::detect(&active, Duration::hours(8), t(22)).unwrap();
+        assert_eq!(
+            overrun.suggestions(),
+            vec![
+                (StopSuggestion::LastActivity, t(14)),
+                (StopSuggestion::Threshold, t(16)),
+            ]
+        );
+    }
+
+    #[test]
+    fn suggestions_skip_last_activity_after_threshold() {
+        let mut active = ActiveFrame::new("backend", vec![], t(8));
+        active.last_activity = Some(t(18));
+        let overrun = Overrun::detect(&active, Duration::hours(8), t(22)).unwrap();
+        assert_eq!(
+            overrun.suggestions(),
+            vec![(StopSuggestion::Threshold, t(16))]
+        );
+    }
+}
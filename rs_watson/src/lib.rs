@@ -1,12 +1,30 @@
 pub mod config;
+mod config_file;
+pub mod context;
 mod convert;
+pub mod epic;
+pub mod filter;
 mod frame;
+pub mod gaps;
+pub mod git;
+pub mod hooks;
+pub mod idle;
+mod project;
+pub mod query;
 pub mod report;
+pub mod stats;
+pub mod strict;
 #[cfg(test)]
 mod testing;
 mod watson;
 
-pub use config::resolve_epic;
+pub use config_file::ConfigFile;
+pub use context::DirectoryContext;
+pub use epic::{EpicResolver, resolve_epic};
+pub use filter::FrameFilter;
 pub use frame::{ActiveFrame, Frame};
+pub use project::{Project, in_subtree, parse_hex_color};
+pub use query::{Query, QueryError};
 pub use report::Report;
+pub use stats::Stats;
 pub use watson::{StartResult, Watson, WatsonError};
@@ -0,0 +1,101 @@
+/// A project with its registry metadata. Projects used on frames but never
+/// registered have no description, color or client and are not archived.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Project {
+    pub name: String,
+    pub description: Option<String>,
+    /// Display color as `#rrggbb`.
+    pub color: Option<String>,
+    pub client: Option<String>,
+    /// Archived projects are hidden from project lists and autocompletion,
+    /// but their frames still count in logs and reports.
+    pub archived: bool,
+}
+
+impl Project {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// The color as RGB components, if set and well-formed.
+    pub fn rgb(&self) -> Option<(u8, u8, u8)> {
+        self.color.as_deref().and_then(parse_hex_color)
+    }
+}
+
+/// True when `project` is `root` or nested below it, e.g. `acme/website` is in
+/// the subtree of `acme` with separator `/`. Without a separator only `root` matches.
+pub fn in_subtree(project: &str, root: &str, separator: Option<&str>) -> bool {
+    project == root
+        || separator.is_some_and(|sep| {
+            !sep.is_empty()
+                && project
+                    .strip_prefix(root)
+                    .is_some_and(|rest| rest.starts_with(sep))
+        })
+}
+
+/// The new name of `project` when the subtree at `from` moves to `to`, or `None`
+/// if `project` is outside that subtree.
+pub fn move_subtree(
+    project: &str,
+    from: &str,
+    to: &str,
+    separator: Option<&str>,
+) -> Option<String> {
+    in_subtree(project, from, separator).then(|| format!("{to}{}", &project[from.len()..]))
+}
+
+/// Parses `#rrggbb` (the `#` is optional) into RGB components.
+pub fn parse_hex_color(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.trim().strip_prefix('#').unwrap_or(s.trim());
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_hex_color("#3366ff"), Some((0x33, 0x66, 0xff)));
+        assert_eq!(parse_hex_color("3366FF"), Some((0x33, 0x66, 0xff)));
+        assert_eq!(parse_hex_color("#36f"), None);
+        assert_eq!(parse_hex_color("#zzzzzz"), None);
+    }
+
+    #[test]
+    fn subtree_matches_descendants_only_with_separator() {
+        assert!(in_subtree("acme", "acme", None));
+        assert!(!in_subtree("acme/website", "acme", None));
+        assert!(in_subtree("acme/website", "acme", Some("/")));
+        assert!(in_subtree("acme/website/frontend", "acme", Some("/")));
+        assert!(!in_subtree("acme-corp", "acme", Some("/")));
+    }
+
+    #[test]
+    fn move_subtree_keeps_the_nested_part() {
+        let sep = Some("/");
+        assert_eq!(
+            move_subtree("acme", "acme", "globex", sep),
+            Some("globex".into())
+        );
+        assert_eq!(
+            move_subtree("acme/website/frontend", "acme/website", "globex/site", sep),
+            Some("globex/site/frontend".into())
+        );
+        assert_eq!(move_subtree("other", "acme", "globex", sep), None);
+    }
+
+    #[test]
+    fn rgb_is_none_without_color() {
+        assert_eq!(Project::new("backend").rgb(), None);
+    }
+}
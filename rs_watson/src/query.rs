@@ -0,0 +1,437 @@
+//! Filter expressions over a frame's project and tags, such as
+//! `project:acme and (tag:api or tag:db) and not tag:meeting`.
+//!
+//! ```text
+//! query := or
+//! or    := and ("or" and)*
+//! and   := unary (["and"] unary)*     -- adjacent terms are and-ed
+//! unary := "not" unary | "(" query ")" | term
+//! term  := ("project" | "tag") ":" (word | "quoted value")
+//! ```
+//!
+//! Keywords and field names are case-insensitive. Values follow the
+//! [`FrameFilter`](crate::FrameFilter) they are part of: `ignore_case` applies,
+//! and with a project separator `project:acme` also matches `acme/website`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::FrameFilter;
+use crate::config::AliasConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid query at column {column}: {message}")]
+pub struct QueryError {
+    /// 1-based character position of the problem.
+    pub column: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    Project(String),
+    Tag(String),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: input.chars().count() + 1,
+        };
+        let query = parser.or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some((column, Token::Close)) => Err(QueryError {
+                column: *column,
+                message: "unexpected \")\"".into(),
+            }),
+            Some((column, token)) => Err(QueryError {
+                column: *column,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+
+    /// Frames carrying at least one of `tags`; `None` for an empty list.
+    pub fn any_tag(tags: &[String]) -> Option<Self> {
+        match tags {
+            [] => None,
+            [tag] => Some(Self::Tag(tag.clone())),
+            _ => Some(Self::Or(tags.iter().cloned().map(Self::Tag).collect())),
+        }
+    }
+
+    /// Both `self` and `other` must match.
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::And(mut a), Self::And(b)) => {
+                a.extend(b);
+                Self::And(a)
+            }
+            (Self::And(mut a), b) => {
+                a.push(b);
+                Self::And(a)
+            }
+            (a, Self::And(mut b)) => {
+                b.insert(0, a);
+                Self::And(b)
+            }
+            (a, b) => Self::And(vec![a, b]),
+        }
+    }
+
+    /// Replaces project and tag aliases by their canonical names.
+    pub fn canonicalize(self, aliases: &AliasConfig) -> Self {
+        match self {
+            Self::Project(p) => Self::Project(aliases.canonical_project(&p).to_string()),
+            Self::Tag(t) => Self::Tag(aliases.canonical_tag(&t).to_string()),
+            Self::Not(q) => Self::Not(Box::new(q.canonicalize(aliases))),
+            Self::And(qs) => Self::And(qs.into_iter().map(|q| q.canonicalize(aliases)).collect()),
+            Self::Or(qs) => Self::Or(qs.into_iter().map(|q| q.canonicalize(aliases)).collect()),
+        }
+    }
+
+    /// Evaluates the query with `filter`'s name matching rules.
+    pub(crate) fn matches_parts(
+        &self,
+        project: &str,
+        tags: &[String],
+        filter: &FrameFilter,
+    ) -> bool {
+        match self {
+            Self::Project(p) => filter.same_project(project, p),
+            Self::Tag(t) => tags.iter().any(|ft| filter.same(ft, t)),
+            Self::Not(q) => !q.matches_parts(project, tags, filter),
+            Self::And(qs) => qs.iter().all(|q| q.matches_parts(project, tags, filter)),
+            Self::Or(qs) => qs.iter().any(|q| q.matches_parts(project, tags, filter)),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Writes the query back in the syntax [`Query::parse`] reads, fully parenthesised.
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |f: &mut fmt::Formatter<'_>, v: &str| {
+            if v.is_empty() || v.chars().any(|c| c.is_whitespace() || "()\"".contains(c)) {
+                write!(f, "\"{v}\"")
+            } else {
+                f.write_str(v)
+            }
+        };
+        let join = |f: &mut fmt::Formatter<'_>, qs: &[Query], op: &str| {
+            f.write_str("(")?;
+            for (i, q) in qs.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {op} ")?;
+                }
+                write!(f, "{q}")?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            Self::Project(p) => {
+                f.write_str("project:")?;
+                value(f, p)
+            }
+            Self::Tag(t) => {
+                f.write_str("tag:")?;
+                value(f, t)
+            }
+            Self::Not(q) => write!(f, "not {q}"),
+            Self::And(qs) => join(f, qs, "and"),
+            Self::Or(qs) => join(f, qs, "or"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Project(String),
+    Tag(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => f.write_str("\"(\""),
+            Token::Close => f.write_str("\")\""),
+            Token::And => f.write_str("\"and\""),
+            Token::Or => f.write_str("\"or\""),
+            Token::Not => f.write_str("\"not\""),
+            Token::Project(p) => write!(f, "\"project:{p}\""),
+            Token::Tag(t) => write!(f, "\"tag:{t}\""),
+        }
+    }
+}
+
+/// Splits `input` into tokens, each with its 1-based column.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push((column, Token::Open));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((column, Token::Close));
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !"()\":".contains(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if chars.get(i) != Some(&':') {
+                    let token = match word.to_lowercase().as_str() {
+                        "and" => Token::And,
+                        "or" => Token::Or,
+                        "not" => Token::Not,
+                        _ if word.is_empty() => {
+                            return Err(QueryError {
+                                column,
+                                message: "unexpected '\"'".into(),
+                            });
+                        }
+                        _ => {
+                            return Err(QueryError {
+                                column,
+                                message: format!(
+                                    "expected project:<name> or tag:<name>, got \"{word}\""
+                                ),
+                            });
+                        }
+                    };
+                    tokens.push((column, token));
+                    continue;
+                }
+                i += 1; // ':'
+                let value = read_value(&chars, &mut i, column, &word)?;
+                let token = match word.to_lowercase().as_str() {
+                    "project" => Token::Project(value),
+                    "tag" => Token::Tag(value),
+                    _ => {
+                        return Err(QueryError {
+                            column,
+                            message: format!("unknown field \"{word}\", expected project or tag"),
+                        });
+                    }
+                };
+                tokens.push((column, token));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Reads the value after `field:` at `chars[*i]`, bare or double-quoted.
+fn read_value(
+    chars: &[char],
+    i: &mut usize,
+    column: usize,
+    field: &str,
+) -> Result<String, QueryError> {
+    if chars.get(*i) == Some(&'"') {
+        let open = *i;
+        let end = chars[open + 1..]
+            .iter()
+            .position(|&c| c == '"')
+            .ok_or_else(|| QueryError {
+                column: open + 1,
+                message: "unterminated quote".into(),
+            })?;
+        *i = open + 1 + end + 1;
+        return Ok(chars[open + 1..open + 1 + end].iter().collect());
+    }
+    let start = *i;
+    while *i < chars.len() && !chars[*i].is_whitespace() && !"()\"".contains(chars[*i]) {
+        *i += 1;
+    }
+    if start == *i {
+        return Err(QueryError {
+            column,
+            message: format!("missing value after \"{field}:\""),
+        });
+    }
+    Ok(chars[start..*i].iter().collect())
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    pos: usize,
+    /// Column just past the input, for errors at the end.
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek().is_some_and(|(_, t)| t == token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Query, QueryError> {
+        let mut parts = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            parts.push(self.and()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Query::Or(parts)
+        })
+    }
+
+    fn and(&mut self) -> Result<Query, QueryError> {
+        let mut parts = vec![self.unary()?];
+        loop {
+            if self.eat(&Token::And) {
+                parts.push(self.unary()?);
+                continue;
+            }
+            match self.peek() {
+                Some((_, Token::Open | Token::Not | Token::Project(_) | Token::Tag(_))) => {
+                    parts.push(self.unary()?);
+                }
+                _ => break,
+            }
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Query::And(parts)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Query, QueryError> {
+        let Some((column, token)) = self.peek().cloned() else {
+            return Err(QueryError {
+                column: self.end,
+                message: "expected a term, got the end of the query".into(),
+            });
+        };
+        self.pos += 1;
+        match token {
+            Token::Not => Ok(Query::Not(Box::new(self.unary()?))),
+            Token::Open => {
+                let inner = self.or()?;
+                if !self.eat(&Token::Close) {
+                    return Err(QueryError {
+                        column,
+                        message: "unclosed \"(\"".into(),
+                    });
+                }
+                Ok(inner)
+            }
+            Token::Project(p) => Ok(Query::Project(p)),
+            Token::Tag(t) => Ok(Query::Tag(t)),
+            other => Err(QueryError {
+                column,
+                message: format!("expected a term, got {other}"),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(query: &str, project: &str, tags: &[&str]) -> bool {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        Query::parse(query)
+            .unwrap()
+            .matches_parts(project, &tags, &FrameFilter::default())
+    }
+
+    #[test]
+    fn parses_precedence_and_implicit_and() {
+        let q = Query::parse("project:acme and (tag:api or tag:db) and not tag:meeting").unwrap();
+        assert_eq!(
+            q,
+            Query::And(vec![
+                Query::Project("acme".into()),
+                Query::Or(vec![Query::Tag("api".into()), Query::Tag("db".into())]),
+                Query::Not(Box::new(Query::Tag("meeting".into()))),
+            ])
+        );
+        assert_eq!(
+            Query::parse("tag:a tag:b OR tag:c").unwrap(),
+            Query::Or(vec![
+                Query::And(vec![Query::Tag("a".into()), Query::Tag("b".into())]),
+                Query::Tag("c".into()),
+            ])
+        );
+        assert_eq!(
+            Query::parse(r#"Project:"client work""#).unwrap(),
+            Query::Project("client work".into())
+        );
+    }
+
+    #[test]
+    fn evaluates_against_project_and_tags() {
+        let q = "project:acme and (tag:api or tag:db) and not tag:meeting";
+        assert!(matches(q, "acme", &["api"]));
+        assert!(matches(q, "acme", &["db", "perf"]));
+        assert!(!matches(q, "acme", &["api", "meeting"]));
+        assert!(!matches(q, "other", &["api"]));
+        assert!(matches("project:a or project:b", "b", &[]));
+    }
+
+    #[test]
+    fn reports_errors_with_columns() {
+        let err = |q: &str| Query::parse(q).unwrap_err();
+        assert_eq!(err("tag:api and").column, 12);
+        assert_eq!(err("(tag:api").column, 1);
+        assert_eq!(err("tag:api)").column, 8);
+        assert!(err("client:acme").message.contains("unknown field"));
+        assert!(err("tag: api").message.contains("missing value"));
+        assert!(err("acme").message.contains("expected project:<name>"));
+        assert!(err(r#"tag:"open"#).message.contains("unterminated"));
+        assert!(err("").message.contains("end of the query"));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for q in [
+            "project:acme and (tag:api or tag:db) and not tag:meeting",
+            r#"tag:"two words" or not (project:a project:b)"#,
+        ] {
+            let parsed = Query::parse(q).unwrap();
+            assert_eq!(Query::parse(&parsed.to_string()).unwrap(), parsed);
+        }
+    }
+}
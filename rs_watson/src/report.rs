@@ -1,8 +1,12 @@
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
-use chrono::Duration;
+use chrono::{Datelike, Duration, Local};
+use uuid::Uuid;
 
-use crate::Frame;
+use crate::config::{EpicConfig, WeekStart};
+use crate::{EpicResolver, Frame};
 
 pub struct Report {
     pub total: Duration,
@@ -13,15 +17,63 @@ pub struct ProjectReport {
     pub name: String,
     pub total: Duration,
     pub tags: Vec<TagReport>,
+    /// True when the project's total includes the currently running frame.
+    pub running: bool,
 }
 
+#[derive(Clone)]
 pub struct TagReport {
     pub name: String,
     pub total: Duration,
 }
 
+/// One level of a nested project path, built by [`Report::tree`].
+pub struct ProjectNode {
+    /// Last path segment, e.g. `website`.
+    pub name: String,
+    /// Full project path, e.g. `acme/website`.
+    pub path: String,
+    /// Time tracked on this path and everything below it.
+    pub total: Duration,
+    /// Tags of frames tracked directly on this path, not on its children.
+    pub tags: Vec<TagReport>,
+    /// True when this node or one of its descendants includes the running frame.
+    pub running: bool,
+    pub children: Vec<ProjectNode>,
+}
+
+impl ProjectNode {
+    fn new(name: &str, path: String) -> Self {
+        Self {
+            name: name.to_string(),
+            path,
+            total: Duration::zero(),
+            tags: Vec::new(),
+            running: false,
+            children: Vec::new(),
+        }
+    }
+
+    fn sort(nodes: &mut [ProjectNode]) {
+        nodes.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(&b.name)));
+        for node in nodes {
+            Self::sort(&mut node.children);
+        }
+    }
+}
+
 impl Report {
     pub fn from_frames(frames: &[Frame]) -> Self {
+        Self::from_frames_with_running(frames, None)
+    }
+
+    /// Like [`Report::from_frames`], but flags the project that contains the frame
+    /// with ID `running` — typically the provisional frame from `Watson::running`.
+    pub fn from_frames_with_running(frames: &[Frame], running: Option<Uuid>) -> Self {
+        let running_project = running
+            .and_then(|id| frames.iter().find(|f| f.id == id))
+            .map(|f| f.project.as_str());
+
         let mut project_map: HashMap<String, (Duration, HashMap<String, Duration>)> =
             HashMap::new();
 
@@ -47,6 +99,7 @@ impl Report {
                 // sort tags by total descending, then alphabetically
                 tag_list.sort_by(|a, b| b.total.cmp(&a.total).then(a.name.cmp(&b.name)));
                 ProjectReport {
+                    running: running_project == Some(name.as_str()),
                     name,
                     total,
                     tags: tag_list,
@@ -62,6 +115,217 @@ impl Report {
 
         Report { total, projects }
     }
+
+    /// Arranges the projects as a tree by splitting their names on `separator`,
+    /// rolling totals up to every ancestor. `acme/website` and `acme/api` become
+    /// children of an `acme` node whose total covers both.
+    pub fn tree(&self, separator: &str) -> Vec<ProjectNode> {
+        let mut roots: Vec<ProjectNode> = Vec::new();
+        for project in &self.projects {
+            let mut level = &mut roots;
+            let mut path = String::new();
+            let segments: Vec<&str> = if separator.is_empty() {
+                vec![project.name.as_str()]
+            } else {
+                project.name.split(separator).collect()
+            };
+            let last = segments.len() - 1;
+            for (i, segment) in segments.into_iter().enumerate() {
+                if i > 0 {
+                    path.push_str(separator);
+                }
+                path.push_str(segment);
+                let pos = match level.iter().position(|n| n.name == segment) {
+                    Some(pos) => pos,
+                    None => {
+                        level.push(ProjectNode::new(segment, path.clone()));
+                        level.len() - 1
+                    }
+                };
+                let node = &mut level[pos];
+                node.total += project.total;
+                node.running |= project.running;
+                if i == last {
+                    node.tags = project.tags.clone();
+                }
+                level = &mut node.children;
+            }
+        }
+        ProjectNode::sort(&mut roots);
+        roots
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pivot
+// ---------------------------------------------------------------------------
+
+/// A level of a [`pivot`] report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Epic,
+    Project,
+    /// Frames with several tags count towards each of them.
+    Tag,
+    Day,
+    Week,
+    Weekday,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 6] = [
+        Dimension::Epic,
+        Dimension::Project,
+        Dimension::Tag,
+        Dimension::Day,
+        Dimension::Week,
+        Dimension::Weekday,
+    ];
+
+    /// Time dimensions list their groups in calendar order; the others by total.
+    pub fn is_chronological(self) -> bool {
+        matches!(self, Dimension::Day | Dimension::Week | Dimension::Weekday)
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Dimension::Epic => "epic",
+            Dimension::Project => "project",
+            Dimension::Tag => "tag",
+            Dimension::Day => "day",
+            Dimension::Week => "week",
+            Dimension::Weekday => "weekday",
+        })
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        Dimension::ALL
+            .into_iter()
+            .find(|d| d.to_string() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown dimension \"{s}\", expected one of: epic, project, tag, day, week, weekday"
+                )
+            })
+    }
+}
+
+/// What a [`pivot`] needs besides the frames.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PivotContext<'a> {
+    pub epics: &'a [EpicConfig],
+    pub week_start: WeekStart,
+    /// ID of the provisional running frame, to flag the groups containing it.
+    pub running: Option<Uuid>,
+}
+
+/// A group in a [`pivot`] report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PivotNode {
+    pub dimension: Dimension,
+    pub label: String,
+    pub total: Duration,
+    /// True when the group contains the running frame.
+    pub running: bool,
+    /// Groups by the next dimension; empty at the last level.
+    pub children: Vec<PivotNode>,
+}
+
+/// Groups `frames` by each of `dimensions` in turn, e.g. `[Epic, Day]` gives one
+/// node per epic with one child per day. Days are local dates of the frame start.
+pub fn pivot(frames: &[Frame], dimensions: &[Dimension], ctx: &PivotContext) -> Vec<PivotNode> {
+    let resolver = EpicResolver::new(ctx.epics);
+    let frames: Vec<&Frame> = frames.iter().collect();
+    pivot_level(&frames, dimensions, ctx, &resolver)
+}
+
+fn pivot_level(
+    frames: &[&Frame],
+    dimensions: &[Dimension],
+    ctx: &PivotContext,
+    resolver: &EpicResolver,
+) -> Vec<PivotNode> {
+    let Some((&dimension, rest)) = dimensions.split_first() else {
+        return Vec::new();
+    };
+
+    // (sort key, label, frames) in order of first appearance.
+    let mut groups: Vec<(String, String, Vec<&Frame>)> = Vec::new();
+    for frame in frames {
+        for (key, label) in group_keys(frame, dimension, ctx, resolver) {
+            match groups.iter_mut().find(|(k, _, _)| *k == key) {
+                Some(group) => group.2.push(frame),
+                None => groups.push((key, label, vec![frame])),
+            }
+        }
+    }
+
+    let mut nodes: Vec<(String, PivotNode)> = groups
+        .into_iter()
+        .map(|(key, label, members)| {
+            let node = PivotNode {
+                dimension,
+                label,
+                total: members
+                    .iter()
+                    .fold(Duration::zero(), |acc, f| acc + (f.end - f.start)),
+                running: ctx
+                    .running
+                    .is_some_and(|id| members.iter().any(|f| f.id == id)),
+                children: pivot_level(&members, rest, ctx, resolver),
+            };
+            (key, node)
+        })
+        .collect();
+    if dimension.is_chronological() {
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+    } else {
+        nodes.sort_by(|a, b| b.1.total.cmp(&a.1.total).then(a.1.label.cmp(&b.1.label)));
+    }
+    nodes.into_iter().map(|(_, node)| node).collect()
+}
+
+/// The groups `frame` falls into along `dimension`, as (sort key, label) pairs.
+fn group_keys(
+    frame: &Frame,
+    dimension: Dimension,
+    ctx: &PivotContext,
+    resolver: &EpicResolver,
+) -> Vec<(String, String)> {
+    let day = frame.start.with_timezone(&Local).date_naive();
+    let same = |s: &str| vec![(s.to_string(), s.to_string())];
+    match dimension {
+        Dimension::Epic => same(
+            resolver
+                .resolve(frame)
+                .map_or("Unassigned", |e| e.name.as_str()),
+        ),
+        Dimension::Project => same(&frame.project),
+        Dimension::Tag if frame.tags.is_empty() => same("(no tags)"),
+        Dimension::Tag => frame.tags.iter().map(|t| (t.clone(), t.clone())).collect(),
+        Dimension::Day => vec![(
+            day.format("%Y-%m-%d").to_string(),
+            day.format("%A, %d %B %Y").to_string(),
+        )],
+        Dimension::Week => {
+            let start = ctx.week_start.start_of_week(day);
+            vec![(
+                start.format("%Y-%m-%d").to_string(),
+                start.format("Week of %d %B %Y").to_string(),
+            )]
+        }
+        Dimension::Weekday => vec![(
+            ctx.week_start.days_into_week(day.weekday()).to_string(),
+            day.format("%A").to_string(),
+        )],
+    }
 }
 
 #[cfg(test)]
@@ -155,6 +419,179 @@ mod tests {
         assert_eq!(proj.tags[1].name, "small");
     }
 
+    #[test]
+    fn running_frame_flags_its_project_only() {
+        let live = frame("backend", &[], 11, 12);
+        let r = Report::from_frames_with_running(
+            &[frame("frontend", &[], 9, 10), live.clone()],
+            Some(live.id),
+        );
+        let backend = r.projects.iter().find(|p| p.name == "backend").unwrap();
+        let frontend = r.projects.iter().find(|p| p.name == "frontend").unwrap();
+        assert!(backend.running);
+        assert!(!frontend.running);
+    }
+
+    #[test]
+    fn running_id_not_in_frames_flags_nothing() {
+        let r = Report::from_frames_with_running(&[frame("a", &[], 9, 10)], Some(Uuid::new_v4()));
+        assert!(!r.projects[0].running);
+    }
+
+    #[test]
+    fn tree_rolls_totals_up_to_ancestors() {
+        let r = Report::from_frames(&[
+            frame("acme/website/frontend", &["css"], 9, 11),
+            frame("acme/website", &[], 11, 12),
+            frame("acme/api", &[], 12, 13),
+            frame("internal", &[], 13, 14),
+        ]);
+        let tree = r.tree("/");
+        assert_eq!(tree.len(), 2);
+        let acme = &tree[0];
+        assert_eq!(
+            (acme.name.as_str(), acme.total),
+            ("acme", Duration::hours(4))
+        );
+        assert!(acme.tags.is_empty());
+        let website = &acme.children[0];
+        assert_eq!(website.path, "acme/website");
+        assert_eq!(website.total, Duration::hours(3));
+        let frontend = &website.children[0];
+        assert_eq!(frontend.path, "acme/website/frontend");
+        assert_eq!(frontend.tags[0].name, "css");
+        assert_eq!(acme.children[1].name, "api");
+    }
+
+    #[test]
+    fn tree_marks_running_ancestors() {
+        let live = frame("acme/api", &[], 11, 12);
+        let r = Report::from_frames_with_running(
+            &[frame("internal", &[], 9, 10), live.clone()],
+            Some(live.id),
+        );
+        let tree = r.tree("/");
+        let acme = tree.iter().find(|n| n.name == "acme").unwrap();
+        assert!(acme.running && acme.children[0].running);
+        assert!(!tree.iter().find(|n| n.name == "internal").unwrap().running);
+    }
+
+    // --- pivot ---
+
+    fn on_day(project: &str, tags: &[&str], day: u32, start_h: u32, end_h: u32) -> Frame {
+        let at = |h| {
+            Local
+                .with_ymd_and_hms(2026, 1, day, h, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        Frame::new(
+            project,
+            tags.iter().map(|s| s.to_string()).collect(),
+            at(start_h),
+            at(end_h),
+        )
+    }
+
+    #[test]
+    fn dimension_parses_names() {
+        assert_eq!("Epic".parse::<Dimension>(), Ok(Dimension::Epic));
+        assert_eq!(" weekday".parse::<Dimension>(), Ok(Dimension::Weekday));
+        assert!("month".parse::<Dimension>().is_err());
+    }
+
+    #[test]
+    fn pivot_nests_dimensions_in_order() {
+        // 2026-01-15 is a Thursday, 2026-01-16 a Friday.
+        let frames = vec![
+            on_day("backend", &[], 15, 9, 11),
+            on_day("frontend", &[], 15, 11, 12),
+            on_day("backend", &[], 16, 9, 10),
+        ];
+        let nodes = pivot(
+            &frames,
+            &[Dimension::Project, Dimension::Day],
+            &PivotContext::default(),
+        );
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].label, "backend");
+        assert_eq!(nodes[0].total, Duration::hours(3));
+        let days: Vec<&str> = nodes[0].children.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(
+            days,
+            vec!["Thursday, 15 January 2026", "Friday, 16 January 2026"]
+        );
+        assert!(nodes[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn pivot_by_epic_and_tag() {
+        let epics = vec![EpicConfig {
+            name: "Backend".into(),
+            project: Some("backend".into()),
+            ..Default::default()
+        }];
+        let frames = vec![
+            on_day("backend", &["api", "db"], 15, 9, 11),
+            on_day("docs", &[], 15, 11, 12),
+        ];
+        let ctx = PivotContext {
+            epics: &epics,
+            ..Default::default()
+        };
+        let nodes = pivot(&frames, &[Dimension::Epic, Dimension::Tag], &ctx);
+        assert_eq!(nodes[0].label, "Backend");
+        let tags: Vec<_> = nodes[0]
+            .children
+            .iter()
+            .map(|n| (n.label.as_str(), n.total))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![("api", Duration::hours(2)), ("db", Duration::hours(2))]
+        );
+        assert_eq!(nodes[1].label, "Unassigned");
+        assert_eq!(nodes[1].children[0].label, "(no tags)");
+    }
+
+    #[test]
+    fn pivot_weeks_and_weekdays_follow_week_start() {
+        // Sunday 2026-01-18 and Monday 2026-01-19.
+        let frames = vec![on_day("a", &[], 19, 9, 10), on_day("a", &[], 18, 9, 10)];
+        let monday = pivot(&frames, &[Dimension::Week], &PivotContext::default());
+        assert_eq!(monday.len(), 2);
+        let sunday_ctx = PivotContext {
+            week_start: WeekStart::Sunday,
+            ..Default::default()
+        };
+        let sunday = pivot(&frames, &[Dimension::Week], &sunday_ctx);
+        assert_eq!(sunday.len(), 1);
+        assert_eq!(sunday[0].label, "Week of 18 January 2026");
+        let weekdays = pivot(&frames, &[Dimension::Weekday], &sunday_ctx);
+        let labels: Vec<&str> = weekdays.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, vec!["Sunday", "Monday"]);
+    }
+
+    #[test]
+    fn pivot_flags_running_groups() {
+        let live = on_day("backend", &[], 15, 11, 12);
+        let frames = vec![on_day("frontend", &[], 15, 9, 10), live.clone()];
+        let ctx = PivotContext {
+            running: Some(live.id),
+            ..Default::default()
+        };
+        let nodes = pivot(&frames, &[Dimension::Project], &ctx);
+        let backend = nodes.iter().find(|n| n.label == "backend").unwrap();
+        assert!(backend.running);
+        assert!(
+            !nodes
+                .iter()
+                .find(|n| n.label == "frontend")
+                .unwrap()
+                .running
+        );
+    }
+
     #[test]
     fn grand_total_equals_sum_of_project_totals() {
         let r = Report::from_frames(&[frame("a", &[], 9, 10), frame("b", &[], 10, 12)]);
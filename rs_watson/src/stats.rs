@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+
+use crate::Frame;
+use crate::config::WeekStart;
+
+/// What [`Stats::from_frames`] needs besides the frames.
+#[derive(Debug, Clone, Copy)]
+pub struct StatsContext {
+    pub week_start: WeekStart,
+    /// How many weeks the project trends cover, ending with the week of `today`.
+    pub weeks: usize,
+    pub today: NaiveDate,
+}
+
+impl StatsContext {
+    /// Trends over the last `weeks` weeks up to today, in local time.
+    pub fn new(week_start: WeekStart, weeks: usize) -> Self {
+        Self {
+            week_start,
+            weeks,
+            today: Local::now().date_naive(),
+        }
+    }
+}
+
+/// A run of consecutive days with tracked time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streak {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+impl Streak {
+    pub fn days(&self) -> i64 {
+        (self.last - self.first).num_days() + 1
+    }
+}
+
+/// Time tracked on one project in each week of the trend window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectTrend {
+    pub project: String,
+    /// One entry per week in [`Stats::trend_weeks`], oldest first.
+    pub weeks: Vec<Duration>,
+}
+
+impl ProjectTrend {
+    pub fn total(&self) -> Duration {
+        self.weeks.iter().fold(Duration::zero(), |acc, d| acc + *d)
+    }
+}
+
+/// Habits and trends across a set of frames. Days are local dates of the
+/// frame start.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub total: Duration,
+    pub frame_count: usize,
+    /// Days with at least one frame.
+    pub days_tracked: usize,
+    /// Average over the days with tracked time.
+    pub daily_average: Duration,
+    pub frame_average: Duration,
+    pub longest_streak: Option<Streak>,
+    /// Time per weekday in week order, starting with `week_start`.
+    pub weekdays: Vec<(Weekday, Duration)>,
+    /// Project changes between consecutive frames of a day, averaged over the
+    /// days with tracked time.
+    pub switches_per_day: f64,
+    /// First day of each week in the trend window, oldest first.
+    pub trend_weeks: Vec<NaiveDate>,
+    /// Projects tracked in the trend window, most time first.
+    pub trends: Vec<ProjectTrend>,
+}
+
+impl Stats {
+    pub fn from_frames(frames: &[Frame], ctx: &StatsContext) -> Self {
+        let mut by_day: BTreeMap<NaiveDate, Vec<&Frame>> = BTreeMap::new();
+        for frame in frames {
+            by_day
+                .entry(frame.start.with_timezone(&Local).date_naive())
+                .or_default()
+                .push(frame);
+        }
+
+        let total = frames
+            .iter()
+            .fold(Duration::zero(), |acc, f| acc + (f.end - f.start));
+        let days_tracked = by_day.len();
+        let average = |count: usize| match i32::try_from(count) {
+            Ok(n) if n > 0 => total / n,
+            _ => Duration::zero(),
+        };
+
+        let mut weekdays: Vec<(Weekday, Duration)> = ctx
+            .week_start
+            .weekdays()
+            .into_iter()
+            .map(|d| (d, Duration::zero()))
+            .collect();
+        let mut switches = 0usize;
+        for (day, day_frames) in &mut by_day {
+            let slot = ctx.week_start.days_into_week(day.weekday()) as usize;
+            for frame in day_frames.iter() {
+                weekdays[slot].1 += frame.end - frame.start;
+            }
+            day_frames.sort_by_key(|f| f.start);
+            switches += day_frames
+                .windows(2)
+                .filter(|pair| pair[0].project != pair[1].project)
+                .count();
+        }
+
+        Stats {
+            total,
+            frame_count: frames.len(),
+            days_tracked,
+            daily_average: average(days_tracked),
+            frame_average: average(frames.len()),
+            longest_streak: longest_streak(by_day.keys().copied()),
+            weekdays,
+            switches_per_day: if days_tracked == 0 {
+                0.0
+            } else {
+                switches as f64 / days_tracked as f64
+            },
+            trend_weeks: trend_weeks(ctx),
+            trends: trends(&by_day, ctx),
+        }
+    }
+
+    /// The weekday with the most tracked time, if any time was tracked.
+    pub fn busiest_weekday(&self) -> Option<(Weekday, Duration)> {
+        self.weekdays
+            .iter()
+            .copied()
+            .filter(|(_, d)| *d > Duration::zero())
+            .reduce(|best, day| if day.1 > best.1 { day } else { best })
+    }
+}
+
+/// The longest run of consecutive `days` (ascending); the earliest wins ties.
+fn longest_streak(days: impl Iterator<Item = NaiveDate>) -> Option<Streak> {
+    let mut best: Option<Streak> = None;
+    let mut current: Option<Streak> = None;
+    for day in days {
+        let streak = match current {
+            Some(s) if s.last.succ_opt() == Some(day) => Streak { last: day, ..s },
+            _ => Streak {
+                first: day,
+                last: day,
+            },
+        };
+        if best.is_none_or(|b| streak.days() > b.days()) {
+            best = Some(streak);
+        }
+        current = Some(streak);
+    }
+    best
+}
+
+/// Oldest first. Weeks before the earliest representable date are left out.
+fn trend_weeks(ctx: &StatsContext) -> Vec<NaiveDate> {
+    let this_week = ctx.week_start.start_of_week(ctx.today);
+    let mut weeks: Vec<NaiveDate> = (0..ctx.weeks)
+        .map_while(|i| {
+            let back = Duration::try_weeks(i64::try_from(i).ok()?)?;
+            this_week.checked_sub_signed(back)
+        })
+        .collect();
+    weeks.reverse();
+    weeks
+}
+
+fn trends(by_day: &BTreeMap<NaiveDate, Vec<&Frame>>, ctx: &StatsContext) -> Vec<ProjectTrend> {
+    let weeks = trend_weeks(ctx);
+    let Some(&first) = weeks.first() else {
+        return Vec::new();
+    };
+    let mut projects: HashMap<&str, Vec<Duration>> = HashMap::new();
+    for (day, day_frames) in by_day.range(first..=ctx.today) {
+        let week = (*day - first).num_weeks() as usize;
+        for frame in day_frames {
+            projects
+                .entry(frame.project.as_str())
+                .or_insert_with(|| vec![Duration::zero(); weeks.len()])[week] +=
+                frame.end - frame.start;
+        }
+    }
+    let mut trends: Vec<ProjectTrend> = projects
+        .into_iter()
+        .map(|(project, weeks)| ProjectTrend {
+            project: project.to_string(),
+            weeks,
+        })
+        .collect();
+    trends.sort_by(|a, b| {
+        b.total()
+            .cmp(&a.total())
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    trends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    /// A frame on 2026-01-`day` from `start_h` to `end_h` local time.
+    fn on_day(project: &str, day: u32, start_h: u32, end_h: u32) -> Frame {
+        let at = |h| {
+            Local
+                .with_ymd_and_hms(2026, 1, day, h, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        Frame::new(project, vec![], at(start_h), at(end_h))
+    }
+
+    fn ctx(weeks: usize) -> StatsContext {
+        StatsContext {
+            week_start: WeekStart::Monday,
+            weeks,
+            // A Wednesday.
+            today: NaiveDate::from_ymd_opt(2026, 1, 21).unwrap(),
+        }
+    }
+
+    #[test]
+    fn averages_streaks_and_switches() {
+        // Mon 12, Tue 13, Wed 14, then Fri 16.
+        let frames = vec![
+            on_day("a", 12, 9, 11),
+            on_day("b", 12, 11, 12),
+            on_day("a", 12, 13, 14),
+            on_day("a", 13, 9, 10),
+            on_day("a", 14, 9, 10),
+            on_day("b", 16, 9, 12),
+        ];
+        let stats = Stats::from_frames(&frames, &ctx(2));
+        assert_eq!(stats.total, Duration::hours(9));
+        assert_eq!(stats.days_tracked, 4);
+        assert_eq!(stats.daily_average, Duration::minutes(135));
+        assert_eq!(stats.frame_average, Duration::minutes(90));
+        let streak = stats.longest_streak.unwrap();
+        assert_eq!(streak.first, NaiveDate::from_ymd_opt(2026, 1, 12).unwrap());
+        assert_eq!(streak.days(), 3);
+        assert!((stats.switches_per_day - 0.5).abs() < f64::EPSILON);
+        assert_eq!(
+            stats.busiest_weekday(),
+            Some((Weekday::Mon, Duration::hours(4)))
+        );
+    }
+
+    #[test]
+    fn weekdays_follow_week_start() {
+        let sunday = StatsContext {
+            week_start: WeekStart::Sunday,
+            ..ctx(1)
+        };
+        let stats = Stats::from_frames(&[on_day("a", 18, 9, 10)], &sunday);
+        assert_eq!(stats.weekdays[0], (Weekday::Sun, Duration::hours(1)));
+        assert_eq!(stats.weekdays[6].0, Weekday::Sat);
+    }
+
+    #[test]
+    fn trends_cover_the_last_weeks() {
+        let frames = vec![
+            on_day("old", 2, 9, 10),
+            on_day("a", 12, 9, 10),
+            on_day("b", 19, 9, 12),
+            on_day("a", 20, 9, 11),
+        ];
+        let stats = Stats::from_frames(&frames, &ctx(2));
+        assert_eq!(
+            stats.trend_weeks,
+            vec![
+                NaiveDate::from_ymd_opt(2026, 1, 12).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 1, 19).unwrap(),
+            ]
+        );
+        let trends: Vec<(&str, &[Duration])> = stats
+            .trends
+            .iter()
+            .map(|t| (t.project.as_str(), t.weeks.as_slice()))
+            .collect();
+        assert_eq!(
+            trends,
+            vec![
+                ("a", &[Duration::hours(1), Duration::hours(2)][..]),
+                ("b", &[Duration::zero(), Duration::hours(3)][..]),
+            ]
+        );
+    }
+
+    #[test]
+    fn trend_weeks_stop_at_the_earliest_date() {
+        let early = StatsContext {
+            today: NaiveDate::MIN + Duration::days(20),
+            ..ctx(usize::MAX)
+        };
+        let stats = Stats::from_frames(&[], &early);
+        assert!((1..=4).contains(&stats.trend_weeks.len()));
+    }
+
+    #[test]
+    fn empty_frames_give_zeroes() {
+        let stats = Stats::from_frames(&[], &ctx(4));
+        assert_eq!(stats.daily_average, Duration::zero());
+        assert_eq!(stats.longest_streak, None);
+        assert_eq!(stats.busiest_weekday(), None);
+        assert!(stats.trends.is_empty());
+        assert_eq!(stats.trend_weeks.len(), 4);
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use rs_watson_storage::search::edit_distance;
+
+/// Which new project and tag names `Watson` accepts. With strict mode on, only
+/// names that were used before, registered, or allow-listed get through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Strictness {
+    pub projects: bool,
+    pub tags: bool,
+    pub allowed_projects: Vec<String>,
+    pub allowed_tags: Vec<String>,
+}
+
+impl Strictness {
+    pub fn is_enabled(&self) -> bool {
+        self.projects || self.tags
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Project,
+    Tag,
+}
+
+impl fmt::Display for NameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NameKind::Project => "project",
+            NameKind::Tag => "tag",
+        })
+    }
+}
+
+/// A name rejected by strict mode, with the closest known name if there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownName {
+    pub kind: NameKind,
+    pub name: String,
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown {} \"{}\"", self.kind, self.name)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean \"{suggestion}\"?)")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownName {}
+
+/// The candidate closest to `name` by edit distance (ignoring case), if it is
+/// close enough to be a plausible typo: at most one edit per three characters.
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let name = name.to_lowercase();
+    let limit = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(&name, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_transpositions_once() {
+        assert_eq!(edit_distance("backend", "backend"), 0);
+        assert_eq!(edit_distance("bakcend", "backend"), 1);
+        assert_eq!(edit_distance("backnd", "backend"), 1);
+        assert_eq!(edit_distance("api", "docs"), 4);
+    }
+
+    #[test]
+    fn suggests_closest_candidate() {
+        let known = ["backend", "frontend", "docs"];
+        assert_eq!(suggest("bakcend", known), Some("backend".into()));
+        assert_eq!(suggest("Frontend", known), Some("frontend".into()));
+        assert_eq!(suggest("research", known), None);
+    }
+
+    #[test]
+    fn unknown_name_mentions_suggestion() {
+        let name = UnknownName {
+            kind: NameKind::Project,
+            name: "bakcend".into(),
+            suggestion: Some("backend".into()),
+        };
+        assert_eq!(
+            name.to_string(),
+            "Unknown project \"bakcend\" (did you mean \"backend\"?)"
+        );
+    }
+}
@@ -1,12 +1,13 @@
 use std::cell::RefCell;
 
-use rs_watson_storage::{ActiveFrameRecord, FrameRecord, Storage};
+use rs_watson_storage::{ActiveFrameRecord, FrameRecord, ProjectRecord, Storage};
 
 /// In-memory `Storage` implementation for unit tests.
 /// Uses `Infallible` as the error type — no I/O, no failure paths.
 pub(crate) struct MemoryStorage {
     frames: RefCell<Vec<FrameRecord>>,
     active: RefCell<Option<ActiveFrameRecord>>,
+    projects: RefCell<Vec<ProjectRecord>>,
 }
 
 impl MemoryStorage {
@@ -14,6 +15,7 @@ impl MemoryStorage {
         Self {
             frames: RefCell::new(Vec::new()),
             active: RefCell::new(None),
+            projects: RefCell::new(Vec::new()),
         }
     }
 }
@@ -35,4 +37,11 @@ impl Storage for MemoryStorage {
         *self.active.borrow_mut() = frame.cloned();
         Ok(())
     }
+    fn load_projects(&self) -> Result<Vec<ProjectRecord>, Self::Error> {
+        Ok(self.projects.borrow().clone())
+    }
+    fn save_projects(&self, projects: &[ProjectRecord]) -> Result<(), Self::Error> {
+        *self.projects.borrow_mut() = projects.to_vec();
+        Ok(())
+    }
 }
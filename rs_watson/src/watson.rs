@@ -1,9 +1,19 @@
-use chrono::{DateTime, Utc};
-use rs_watson_storage::{ActiveFrameRecord, FrameRecord, Storage};
+use std::cell::RefCell;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use rs_watson_storage::{ActiveFrameRecord, FrameRecord, ProjectRecord, Storage};
 use thiserror::Error;
 use uuid::Uuid;
 
+use crate::config::{AliasConfig, EpicConfig, HooksConfig, WorkingHoursConfig};
+use crate::frame::{add_missing_tags, remove_listed_tags, replace_tag};
+use crate::gaps::{Gap, find_gaps};
+use crate::hooks::{self, Event, HookFailure};
+use crate::idle::Overrun;
+use crate::project::{move_subtree, parse_hex_color};
+use crate::strict::{NameKind, Strictness, UnknownName, suggest};
 use crate::{ActiveFrame, Frame};
+use crate::{FrameFilter, Project};
 
 #[derive(Debug, Error)]
 pub enum WatsonError<E: std::error::Error + 'static> {
@@ -15,10 +25,22 @@ pub enum WatsonError<E: std::error::Error + 'static> {
     FrameNotFound,
     #[error("Project \"{0}\" not found")]
     ProjectNotFound(String),
+    #[error("Tag \"{0}\" not found")]
+    TagNotFound(String),
+    #[error("Invalid color \"{0}\", expected #rrggbb")]
+    InvalidColor(String),
     #[error("End time must be after start time")]
     InvalidTimeRange,
     #[error("Time overlaps with existing frame for project \"{0}\"")]
     OverlappingFrame(String),
+    #[error("At least two frames are needed to merge")]
+    NothingToMerge,
+    #[error("Cannot merge frames of different projects (\"{0}\" and \"{1}\")")]
+    MixedProjects(String, String),
+    #[error("{0}")]
+    UnknownName(UnknownName),
+    #[error("{0}")]
+    Hook(HookFailure),
     #[error("Storage error: {0}")]
     Storage(E),
 }
@@ -54,11 +76,152 @@ pub struct StartResult {
 
 pub struct Watson<S: Storage> {
     storage: S,
+    aliases: AliasConfig,
+    strict: Strictness,
+    separator: Option<String>,
+    hooks: HooksConfig,
+    /// New names confirmed via [`Watson::accept_name`], let through strict mode.
+    accepted: RefCell<Vec<(NameKind, String)>>,
+    /// Failures of `warn` hooks not yet collected by [`Watson::take_hook_warnings`].
+    hook_warnings: RefCell<Vec<HookFailure>>,
 }
 
 impl<S: Storage> Watson<S> {
     pub fn new(storage: S) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            aliases: AliasConfig::default(),
+            strict: Strictness::default(),
+            separator: None,
+            hooks: HooksConfig::default(),
+            accepted: RefCell::new(Vec::new()),
+            hook_warnings: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Maps project and tag aliases to their canonical names whenever frames
+    /// are started, added, edited, split or imported.
+    pub fn with_aliases(mut self, aliases: AliasConfig) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Rejects unknown project and tag names when starting, adding, editing,
+    /// splitting or importing frames. See [`Strictness`].
+    pub fn with_strictness(mut self, strict: Strictness) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Treats `separator` as the project path separator, so that [`rename`](Self::rename)
+    /// moves whole subtrees (`acme` → `globex` also renames `acme/website`).
+    pub fn with_project_separator(mut self, separator: Option<String>) -> Self {
+        self.separator = separator.filter(|s| !s.is_empty());
+        self
+    }
+
+    /// Runs `hooks` for every frame started, stopped, cancelled, added, edited or
+    /// removed and every project renamed, before the change is saved. A failing
+    /// `abort` hook cancels the change with [`WatsonError::Hook`]; failures of
+    /// `warn` hooks are kept for [`take_hook_warnings`](Self::take_hook_warnings).
+    pub fn with_hooks(mut self, hooks: HooksConfig) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Failures of `warn` hooks since the last call, for the front end to report.
+    pub fn take_hook_warnings(&self) -> Vec<HookFailure> {
+        self.hook_warnings.take()
+    }
+
+    fn emit(&self, event: Event) -> Result<(), WatsonError<S::Error>> {
+        self.emit_all(&[event])
+    }
+
+    /// Emits the events of one change together, so an `abort` hook on any of
+    /// them cancels it before a `warn` hook has run.
+    fn emit_all(&self, events: &[Event]) -> Result<(), WatsonError<S::Error>> {
+        if self.hooks.run.is_empty() || events.is_empty() {
+            return Ok(());
+        }
+        let warnings = hooks::run(&self.hooks, events).map_err(WatsonError::Hook)?;
+        self.hook_warnings.borrow_mut().extend(warnings);
+        Ok(())
+    }
+
+    fn canonical(&self, project: String, tags: Vec<String>) -> (String, Vec<String>) {
+        (
+            self.aliases.canonical_project(&project).to_string(),
+            self.aliases.canonical_tags(tags),
+        )
+    }
+
+    /// Names among `frames` (canonical project and tags) that strict mode would
+    /// reject, each listed once, in order of appearance.
+    fn unknown_in<'a>(
+        &self,
+        frames: impl IntoIterator<Item = (&'a str, &'a [String])>,
+    ) -> Result<Vec<UnknownName>, WatsonError<S::Error>> {
+        if !self.strict.is_enabled() {
+            return Ok(Vec::new());
+        }
+        let projects = self.project_list()?;
+        let mut tags = self.tags()?;
+        tags.extend(self.load_active()?.map(|a| a.tags).unwrap_or_default());
+        let accepted = self.accepted.borrow();
+        let is_accepted =
+            |kind: NameKind, name: &str| accepted.iter().any(|(k, n)| *k == kind && n == name);
+
+        let mut unknown: Vec<UnknownName> = Vec::new();
+        let mut reject = |kind: NameKind, name: &str, candidates: Vec<&str>| {
+            if !unknown.iter().any(|u| u.kind == kind && u.name == name) {
+                unknown.push(UnknownName {
+                    kind,
+                    name: name.to_string(),
+                    suggestion: suggest(name, candidates),
+                });
+            }
+        };
+        for (project, frame_tags) in frames {
+            if self.strict.projects
+                && !projects.iter().any(|p| p.name == project)
+                && !self.strict.allowed_projects.iter().any(|p| p == project)
+                && !is_accepted(NameKind::Project, project)
+            {
+                let candidates = projects
+                    .iter()
+                    .filter(|p| !p.archived)
+                    .map(|p| p.name.as_str())
+                    .chain(self.strict.allowed_projects.iter().map(String::as_str))
+                    .collect();
+                reject(NameKind::Project, project, candidates);
+            }
+            if !self.strict.tags {
+                continue;
+            }
+            for tag in frame_tags {
+                if !tags.contains(tag)
+                    && !self.strict.allowed_tags.contains(tag)
+                    && !is_accepted(NameKind::Tag, tag)
+                {
+                    let candidates = tags
+                        .iter()
+                        .chain(&self.strict.allowed_tags)
+                        .map(String::as_str)
+                        .collect();
+                    reject(NameKind::Tag, tag, candidates);
+                }
+            }
+        }
+        Ok(unknown)
+    }
+
+    /// Fails with the first name strict mode rejects.
+    fn ensure_known(&self, project: &str, tags: &[String]) -> Result<(), WatsonError<S::Error>> {
+        match self.unknown_in([(project, tags)])?.into_iter().next() {
+            Some(unknown) => Err(WatsonError::UnknownName(unknown)),
+            None => Ok(()),
+        }
     }
 
     // --- Private storage proxies -------------------------------------------
@@ -84,6 +247,46 @@ impl<S: Storage> Watson<S> {
             .map_err(WatsonError::Storage)
     }
 
+    fn load_projects(&self) -> Result<Vec<ProjectRecord>, WatsonError<S::Error>> {
+        self.storage.load_projects().map_err(WatsonError::Storage)
+    }
+
+    fn save_projects(&self, projects: &[ProjectRecord]) -> Result<(), WatsonError<S::Error>> {
+        self.storage
+            .save_projects(projects)
+            .map_err(WatsonError::Storage)
+    }
+
+    /// Carries registry entries of renamed/merged projects over to `into`.
+    /// An existing entry for `into` wins; otherwise the first source entry is renamed.
+    fn move_registry_entries(
+        &self,
+        sources: &[&str],
+        into: &str,
+    ) -> Result<(), WatsonError<S::Error>> {
+        let mut projects = self.load_projects()?;
+        let mut has_target = projects.iter().any(|p| p.name == into);
+        let before = projects.len();
+        let mut moved = false;
+        projects.retain_mut(|p| {
+            if p.name == into || !sources.contains(&p.name.as_str()) {
+                return true;
+            }
+            moved = true;
+            if has_target {
+                return false;
+            }
+            p.name = into.to_string();
+            has_target = true;
+            true
+        });
+        if moved || projects.len() != before {
+            projects.sort_by(|a, b| a.name.cmp(&b.name));
+            self.save_projects(&projects)?;
+        }
+        Ok(())
+    }
+
     /// Loads frames, applies a mutation via `f`, and saves the result.
     /// Frames are only written if `f` succeeds.
     fn modify_frames<T, F>(&self, f: F) -> Result<T, WatsonError<S::Error>>
@@ -96,11 +299,32 @@ impl<S: Storage> Watson<S> {
         Ok(result)
     }
 
+    /// Applies `change` to every record and emits `edited` for each one it
+    /// reports as changed. Returns how many changed.
+    fn edit_each(
+        &self,
+        records: &mut [FrameRecord],
+        mut change: impl FnMut(&mut FrameRecord) -> bool,
+    ) -> Result<usize, WatsonError<S::Error>> {
+        let mut events = Vec::new();
+        for record in records.iter_mut() {
+            let previous = Frame::from(record.clone());
+            if change(record) {
+                events.push(Event::Edited {
+                    previous,
+                    frame: Frame::from(record.clone()),
+                });
+            }
+        }
+        self.emit_all(&events)?;
+        Ok(events.len())
+    }
+
     /// Validates `at` against `records`, then saves a new active frame.
     /// Shared by `start` and `start_or_replace`.
     fn begin_tracking(
         &self,
-        project: impl Into<String>,
+        project: String,
         tags: Vec<String>,
         at: DateTime<Utc>,
         records: &[FrameRecord],
@@ -109,6 +333,7 @@ impl<S: Storage> Watson<S> {
             return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
         }
         let active = ActiveFrame::new(project, tags, at);
+        self.emit(Event::Started(active.clone()))?;
         self.save_active(Some(&ActiveFrameRecord::from(&active)))?;
         Ok(active)
     }
@@ -124,6 +349,8 @@ impl<S: Storage> Watson<S> {
         if let Some(active) = self.load_active()? {
             return Err(WatsonError::AlreadyTracking(active.project));
         }
+        let (project, tags) = self.canonical(project.into(), tags);
+        self.ensure_known(&project, &tags)?;
         let records = self.load_frames()?;
         self.begin_tracking(project, tags, at, &records)
     }
@@ -136,6 +363,8 @@ impl<S: Storage> Watson<S> {
         tags: Vec<String>,
         at: DateTime<Utc>,
     ) -> Result<StartResult, WatsonError<S::Error>> {
+        let (project, tags) = self.canonical(project.into(), tags);
+        self.ensure_known(&project, &tags)?;
         let existing_active = self.load_active()?;
         let mut records = self.load_frames()?;
 
@@ -150,6 +379,7 @@ impl<S: Storage> Watson<S> {
             {
                 return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
             }
+            self.emit(Event::Stopped(completed.clone()))?;
             records.push(FrameRecord::from(&completed));
             Some(completed)
         } else {
@@ -174,6 +404,7 @@ impl<S: Storage> Watson<S> {
             if let Some(conflict) = find_overlap(frame.start, Some(frame.end), records, None) {
                 return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
             }
+            self.emit(Event::Stopped(frame.clone()))?;
             records.push(FrameRecord::from(&frame));
             Ok(())
         })?;
@@ -182,9 +413,10 @@ impl<S: Storage> Watson<S> {
     }
 
     pub fn cancel(&self) -> Result<ActiveFrame, WatsonError<S::Error>> {
-        let active = self.load_active()?.ok_or(WatsonError::NotTracking)?;
+        let active = ActiveFrame::from(self.load_active()?.ok_or(WatsonError::NotTracking)?);
+        self.emit(Event::Cancelled(active.clone()))?;
         self.save_active(None)?;
-        Ok(ActiveFrame::from(active))
+        Ok(active)
     }
 
     pub fn add(
@@ -197,11 +429,14 @@ impl<S: Storage> Watson<S> {
         if end <= start {
             return Err(WatsonError::InvalidTimeRange);
         }
+        let (project, tags) = self.canonical(project.into(), tags);
+        self.ensure_known(&project, &tags)?;
         let frame = Frame::new(project, tags, start, end);
         self.modify_frames(|records| {
             if let Some(conflict) = find_overlap(frame.start, Some(frame.end), records, None) {
                 return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
             }
+            self.emit(Event::Added(frame.clone()))?;
             records.push(FrameRecord::from(&frame));
             Ok(())
         })?;
@@ -219,9 +454,11 @@ impl<S: Storage> Watson<S> {
         if end <= start {
             return Err(WatsonError::InvalidTimeRange);
         }
+        let (project, tags) = self.canonical(project.into(), tags);
+        self.ensure_known(&project, &tags)?;
         let frame = Frame {
             id,
-            project: project.into(),
+            project,
             tags,
             start,
             end,
@@ -234,6 +471,10 @@ impl<S: Storage> Watson<S> {
             if let Some(conflict) = find_overlap(frame.start, Some(frame.end), records, Some(id)) {
                 return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
             }
+            self.emit(Event::Edited {
+                previous: Frame::from(records[pos].clone()),
+                frame: frame.clone(),
+            })?;
             records[pos] = FrameRecord::from(&frame);
             Ok(())
         })?;
@@ -246,72 +487,558 @@ impl<S: Storage> Watson<S> {
                 .iter()
                 .position(|r| r.id == id)
                 .ok_or(WatsonError::FrameNotFound)?;
+            self.emit(Event::Removed(Frame::from(records[pos].clone())))?;
             Ok(Frame::from(records.remove(pos)))
         })
     }
 
+    /// Cuts frame `id` in two at `at`. The first part keeps the frame's ID, project
+    /// and tags; the second part starts at `at` with `new_project` and `new_tags`.
+    pub fn split(
+        &self,
+        id: Uuid,
+        at: DateTime<Utc>,
+        new_project: impl Into<String>,
+        new_tags: Vec<String>,
+    ) -> Result<(Frame, Frame), WatsonError<S::Error>> {
+        let (new_project, new_tags) = self.canonical(new_project.into(), new_tags);
+        self.ensure_known(&new_project, &new_tags)?;
+        self.modify_frames(|records| {
+            let pos = records
+                .iter()
+                .position(|r| r.id == id)
+                .ok_or(WatsonError::FrameNotFound)?;
+            let original = Frame::from(records[pos].clone());
+            if at <= original.start || at >= original.end {
+                return Err(WatsonError::InvalidTimeRange);
+            }
+            let first = Frame {
+                end: at,
+                ..original.clone()
+            };
+            let second = Frame::new(new_project, new_tags, at, original.end);
+            for part in [&first, &second] {
+                if let Some(conflict) = find_overlap(part.start, Some(part.end), records, Some(id))
+                {
+                    return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
+                }
+            }
+            self.emit_all(&[
+                Event::Edited {
+                    previous: original.clone(),
+                    frame: first.clone(),
+                },
+                Event::Added(second.clone()),
+            ])?;
+            records[pos] = FrameRecord::from(&first);
+            records.insert(pos + 1, FrameRecord::from(&second));
+            Ok((first, second))
+        })
+    }
+
+    /// Merges the frames `ids` into a single frame spanning the earliest start to the
+    /// latest end, keeping the earliest frame's ID. All frames must belong to the same
+    /// project; their tags are combined in order of first appearance. Gaps between the
+    /// frames are absorbed, so the merged span must not overlap any other frame.
+    pub fn merge(&self, ids: &[Uuid]) -> Result<Frame, WatsonError<S::Error>> {
+        self.modify_frames(|records| {
+            let mut parts: Vec<Frame> = Vec::new();
+            for id in ids {
+                if parts.iter().any(|f| f.id == *id) {
+                    continue;
+                }
+                let record = records
+                    .iter()
+                    .find(|r| r.id == *id)
+                    .ok_or(WatsonError::FrameNotFound)?;
+                parts.push(Frame::from(record.clone()));
+            }
+            if parts.len() < 2 {
+                return Err(WatsonError::NothingToMerge);
+            }
+            parts.sort_by_key(|f| f.start);
+
+            let first = &parts[0];
+            if let Some(other) = parts.iter().find(|f| f.project != first.project) {
+                return Err(WatsonError::MixedProjects(
+                    first.project.clone(),
+                    other.project.clone(),
+                ));
+            }
+            let mut tags: Vec<String> = Vec::new();
+            for tag in parts.iter().flat_map(|f| &f.tags) {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            let merged = Frame {
+                id: first.id,
+                project: first.project.clone(),
+                tags,
+                start: first.start,
+                end: parts.iter().map(|f| f.end).max().unwrap_or(first.end),
+            };
+
+            records.retain(|r| !parts.iter().any(|f| f.id == r.id));
+            if let Some(conflict) = find_overlap(merged.start, Some(merged.end), records, None) {
+                return Err(WatsonError::OverlappingFrame(conflict.project.clone()));
+            }
+            let mut events = vec![Event::Edited {
+                previous: first.clone(),
+                frame: merged.clone(),
+            }];
+            events.extend(parts[1..].iter().cloned().map(Event::Removed));
+            self.emit_all(&events)?;
+            records.push(FrameRecord::from(&merged));
+            Ok(merged)
+        })
+    }
+
+    /// Renames project `from` to `to`. With a project separator set, nested
+    /// projects move along: `acme/website` becomes `to/website`.
     pub fn rename(
         &self,
         from: &str,
         to: impl Into<String>,
     ) -> Result<usize, WatsonError<S::Error>> {
         let to = to.into();
+        let renamed = |project: &str| move_subtree(project, from, &to, self.separator.as_deref());
 
-        let active_updated = if let Some(mut active) = self.load_active()? {
-            if active.project == from {
-                active.project = to.clone();
-                self.save_active(Some(&active))?;
-                true
-            } else {
-                false
-            }
-        } else {
-            false
-        };
+        // Saved only once the frames are, so a failing hook leaves both untouched.
+        let active = self.load_active()?.and_then(|mut active| {
+            active.project = renamed(&active.project)?;
+            Some(active)
+        });
+        let active_updated = active.is_some();
 
         let frame_count = self.modify_frames(|records| {
             let mut count = 0usize;
             for record in records.iter_mut() {
-                if record.project == from {
-                    record.project = to.clone();
+                if let Some(name) = renamed(&record.project) {
+                    record.project = name;
                     count += 1;
                 }
             }
             if count == 0 && !active_updated {
                 return Err(WatsonError::ProjectNotFound(from.to_string()));
             }
+            self.emit(Event::Renamed {
+                from: from.to_string(),
+                to: to.clone(),
+            })?;
             Ok(count)
         })?;
+        if let Some(active) = &active {
+            self.save_active(Some(active))?;
+        }
+        for project in self.load_projects()? {
+            if let Some(name) = renamed(&project.name) {
+                self.move_registry_entries(&[&project.name], &name)?;
+            }
+        }
 
         Ok(frame_count + usize::from(active_updated))
     }
 
+    /// Moves every frame (and the active frame) of any project in `sources` to
+    /// `into`, which may already exist. Sources that were never used are skipped;
+    /// fails with `ProjectNotFound` only when none of them were found.
+    pub fn merge_projects(
+        &self,
+        sources: &[String],
+        into: &str,
+    ) -> Result<usize, WatsonError<S::Error>> {
+        let from = |project: &str| project != into && sources.iter().any(|s| s == project);
+
+        // Saved only once the frames are, so a failing hook leaves both untouched.
+        let active = self.load_active()?.filter(|a| from(&a.project));
+        let active_updated = active.is_some();
+
+        let frame_count = self.modify_frames(|records| {
+            let mut found: Vec<&str> = active.iter().map(|a| a.project.as_str()).collect();
+            let mut count = 0usize;
+            for record in records.iter_mut().filter(|r| from(&r.project)) {
+                if let Some(source) = sources.iter().find(|s| **s == record.project)
+                    && !found.contains(&source.as_str())
+                {
+                    found.push(source);
+                }
+                record.project = into.to_string();
+                count += 1;
+            }
+            if count == 0 && !active_updated {
+                let missing = sources.first().cloned().unwrap_or_default();
+                return Err(WatsonError::ProjectNotFound(missing));
+            }
+            let events: Vec<Event> = found
+                .into_iter()
+                .map(|source| Event::Renamed {
+                    from: source.to_string(),
+                    to: into.to_string(),
+                })
+                .collect();
+            self.emit_all(&events)?;
+            Ok(count)
+        })?;
+        if let Some(mut active) = active {
+            active.project = into.to_string();
+            self.save_active(Some(&active))?;
+        }
+        let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+        self.move_registry_entries(&sources, into)?;
+
+        Ok(frame_count + usize::from(active_updated))
+    }
+
+    /// Replaces every tag in `sources` with `into` across all recorded frames and
+    /// the active frame, without creating duplicates. Fails with `TagNotFound`
+    /// only when none of the sources were found.
+    pub fn merge_tags(
+        &self,
+        sources: &[String],
+        into: &str,
+    ) -> Result<usize, WatsonError<S::Error>> {
+        let merge = |tags: &mut Vec<String>| {
+            let mut changed = false;
+            for source in sources.iter().filter(|s| s.as_str() != into) {
+                changed |= replace_tag(tags, source, into);
+            }
+            changed
+        };
+
+        // Saved only once the frames are, so a failing hook leaves both untouched.
+        let active = self
+            .load_active()?
+            .and_then(|mut active| merge(&mut active.tags).then_some(active));
+        let active_updated = active.is_some();
+
+        let frame_count = self.modify_frames(|records| {
+            let count = self.edit_each(records, |r| merge(&mut r.tags))?;
+            if count == 0 && !active_updated {
+                let missing = sources.first().cloned().unwrap_or_default();
+                return Err(WatsonError::TagNotFound(missing));
+            }
+            Ok(count)
+        })?;
+        if let Some(active) = &active {
+            self.save_active(Some(active))?;
+        }
+
+        Ok(frame_count + usize::from(active_updated))
+    }
+
+    /// Renames a tag across all recorded frames and the active frame. Frames that
+    /// already carry `to` simply lose `from`, so no duplicates are created.
+    /// Renaming a tag to itself changes nothing.
+    pub fn rename_tag(&self, from: &str, to: &str) -> Result<usize, WatsonError<S::Error>> {
+        if from == to {
+            return Ok(0);
+        }
+        // Saved only once the frames are, so a failing hook leaves both untouched.
+        let active = self
+            .load_active()?
+            .and_then(|mut active| replace_tag(&mut active.tags, from, to).then_some(active));
+        let active_updated = active.is_some();
+
+        let frame_count = self.modify_frames(|records| {
+            let count = self.edit_each(records, |r| replace_tag(&mut r.tags, from, to))?;
+            if count == 0 && !active_updated {
+                return Err(WatsonError::TagNotFound(from.to_string()));
+            }
+            Ok(count)
+        })?;
+        if let Some(active) = &active {
+            self.save_active(Some(active))?;
+        }
+
+        Ok(frame_count + usize::from(active_updated))
+    }
+
+    /// Adds `tags` to every recorded frame matching `filter`.
+    /// Returns the frames that changed, in their updated form.
+    pub fn add_tags(
+        &self,
+        filter: &FrameFilter,
+        tags: &[String],
+    ) -> Result<Vec<Frame>, WatsonError<S::Error>> {
+        self.retag(filter, tags, &[])
+    }
+
+    /// Removes `tags` from every recorded frame matching `filter`.
+    /// Returns the frames that changed, in their updated form.
+    pub fn remove_tags(
+        &self,
+        filter: &FrameFilter,
+        tags: &[String],
+    ) -> Result<Vec<Frame>, WatsonError<S::Error>> {
+        self.retag(filter, &[], tags)
+    }
+
+    /// Removes `remove` and then adds `add` on every recorded frame matching
+    /// `filter`, in a single pass — so filtering on a tag that is being
+    /// removed still selects the right frames. Returns the changed frames.
+    pub fn retag(
+        &self,
+        filter: &FrameFilter,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<Vec<Frame>, WatsonError<S::Error>> {
+        self.modify_frames(|records| {
+            let mut changed = Vec::new();
+            self.edit_each(records, |r| {
+                if !filter.matches_parts(&r.project, &r.tags, r.start) {
+                    return false;
+                }
+                let removed = remove_listed_tags(&mut r.tags, remove);
+                let added = add_missing_tags(&mut r.tags, add);
+                if removed || added {
+                    changed.push(Frame::from(r.clone()));
+                }
+                removed || added
+            })?;
+            changed.sort_by_key(|f| f.start);
+            Ok(changed)
+        })
+    }
+
     /// Imports a list of frames, appending them sorted by start time.
     /// Does not check for overlaps — suitable for bulk migration.
     pub fn import_frames(&self, frames: Vec<Frame>) -> Result<usize, WatsonError<S::Error>> {
         let count = frames.len();
+        let frames: Vec<Frame> = frames
+            .into_iter()
+            .map(|f| {
+                let (project, tags) = self.canonical(f.project, f.tags);
+                Frame { project, tags, ..f }
+            })
+            .collect();
+        if let Some(unknown) = self
+            .unknown_in(
+                frames
+                    .iter()
+                    .map(|f| (f.project.as_str(), f.tags.as_slice())),
+            )?
+            .into_iter()
+            .next()
+        {
+            return Err(WatsonError::UnknownName(unknown));
+        }
         self.modify_frames(|records| {
+            let events: Vec<Event> = frames.iter().cloned().map(Event::Added).collect();
+            self.emit_all(&events)?;
             records.extend(frames.iter().map(FrameRecord::from));
             records.sort_by_key(|r| r.start);
             Ok(count)
         })
     }
 
+    /// Project and tag names (after alias mapping) that strict mode would reject
+    /// for a frame with `project` and `tags`. Empty when strict mode is off.
+    pub fn unknown_names(
+        &self,
+        project: &str,
+        tags: &[String],
+    ) -> Result<Vec<UnknownName>, WatsonError<S::Error>> {
+        let (project, tags) = self.canonical(project.to_string(), tags.to_vec());
+        self.unknown_in([(project.as_str(), tags.as_slice())])
+    }
+
+    /// Like [`unknown_names`](Self::unknown_names), across a batch of frames to import.
+    pub fn unknown_names_in(
+        &self,
+        frames: &[Frame],
+    ) -> Result<Vec<UnknownName>, WatsonError<S::Error>> {
+        let canonical: Vec<(String, Vec<String>)> = frames
+            .iter()
+            .map(|f| self.canonical(f.project.clone(), f.tags.clone()))
+            .collect();
+        self.unknown_in(canonical.iter().map(|(p, t)| (p.as_str(), t.as_slice())))
+    }
+
+    /// Lets a name through strict mode for the lifetime of this `Watson` — for
+    /// front ends that asked the user to confirm creating it. Once saved on a
+    /// frame, the name counts as known anyway.
+    pub fn accept_name(&self, name: &UnknownName) {
+        self.accepted
+            .borrow_mut()
+            .push((name.kind, name.name.clone()));
+    }
+
+    /// Exact project names in `epic` that are neither used on a frame nor
+    /// registered, so the epic could never match them. Aliases count as unknown,
+    /// with their canonical name as the suggestion.
+    pub fn unknown_epic_projects(
+        &self,
+        epic: &EpicConfig,
+    ) -> Result<Vec<UnknownName>, WatsonError<S::Error>> {
+        let known: Vec<String> = self.project_list()?.into_iter().map(|p| p.name).collect();
+        Ok(epic
+            .project
+            .iter()
+            .chain(&epic.projects)
+            .filter(|name| !known.contains(name))
+            .map(|name| {
+                let canonical = self.aliases.canonical_project(name);
+                UnknownName {
+                    kind: NameKind::Project,
+                    name: name.clone(),
+                    suggestion: if canonical != name {
+                        Some(canonical.to_string())
+                    } else {
+                        suggest(name, known.iter().map(String::as_str))
+                    },
+                }
+            })
+            .collect())
+    }
+
     pub fn status(&self) -> Result<Option<ActiveFrame>, WatsonError<S::Error>> {
         Ok(self.load_active()?.map(ActiveFrame::from))
     }
 
+    /// Returns the active frame as a provisional [`Frame`] ending at `now`, if tracking.
+    /// Nothing is written — use this to include running time in totals and reports.
+    pub fn running(&self, now: DateTime<Utc>) -> Result<Option<Frame>, WatsonError<S::Error>> {
+        Ok(self.status()?.map(|active| active.provisional(now)))
+    }
+
+    /// Untracked intervals within working hours between `from` and `to` (dates in `tz`).
+    /// The running frame counts as tracked up to `now`.
+    pub fn gaps<Tz: TimeZone>(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        hours: &WorkingHoursConfig,
+        tz: &Tz,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Gap>, WatsonError<S::Error>> {
+        let mut frames = self.log()?;
+        frames.extend(self.running(now)?);
+        Ok(find_gaps(&frames, from, to, hours, tz, now))
+    }
+
+    /// Returns an [`Overrun`] if the active frame has been running longer than `max` at `now`.
+    pub fn overrun(
+        &self,
+        max: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Overrun>, WatsonError<S::Error>> {
+        Ok(self
+            .status()?
+            .and_then(|active| Overrun::detect(&active, max, now)))
+    }
+
+    /// Records user activity at `at` on the active frame, used later to suggest a stop
+    /// time for forgotten frames. Does nothing when idle, and once the frame has run past
+    /// `max` — otherwise the first command of the next morning would overwrite the
+    /// evening's last activity.
+    pub fn record_activity(
+        &self,
+        at: DateTime<Utc>,
+        max: Option<Duration>,
+    ) -> Result<(), WatsonError<S::Error>> {
+        let Some(mut active) = self.load_active()? else {
+            return Ok(());
+        };
+        if max.is_some_and(|max| at - active.start > max)
+            || active.last_activity.is_some_and(|last| last >= at)
+        {
+            return Ok(());
+        }
+        active.last_activity = Some(at);
+        self.save_active(Some(&active))
+    }
+
+    /// Completed frames whose project or tags match the words of `text`, best
+    /// match first. Ranking and fuzziness are up to the storage backend.
+    pub fn search(&self, text: &str) -> Result<Vec<Frame>, WatsonError<S::Error>> {
+        Ok(self
+            .storage
+            .search(text)
+            .map_err(WatsonError::Storage)?
+            .into_iter()
+            .map(|hit| Frame::from(hit.frame))
+            .collect())
+    }
+
+    /// Changes when another process writes the data; see [`Storage::data_version`].
+    pub fn data_version(&self) -> Result<Option<u64>, WatsonError<S::Error>> {
+        self.storage.data_version().map_err(WatsonError::Storage)
+    }
+
     pub fn log(&self) -> Result<Vec<Frame>, WatsonError<S::Error>> {
         let mut frames: Vec<Frame> = self.load_frames()?.into_iter().map(Frame::from).collect();
         frames.sort_by_key(|f| f.start);
         Ok(frames)
     }
 
+    /// Names of all projects in use or registered, excluding archived ones.
     pub fn projects(&self) -> Result<Vec<String>, WatsonError<S::Error>> {
-        let mut names: Vec<String> = self.load_frames()?.into_iter().map(|r| r.project).collect();
-        names.sort();
-        names.dedup();
-        Ok(names)
+        Ok(self
+            .project_list()?
+            .into_iter()
+            .filter(|p| !p.archived)
+            .map(|p| p.name)
+            .collect())
+    }
+
+    /// Every project that is used on a frame or registered, with its metadata,
+    /// sorted by name. Includes archived projects.
+    pub fn project_list(&self) -> Result<Vec<Project>, WatsonError<S::Error>> {
+        let mut projects: Vec<Project> = self
+            .load_projects()?
+            .into_iter()
+            .map(Project::from)
+            .collect();
+        let used = self
+            .load_frames()?
+            .into_iter()
+            .map(|r| r.project)
+            .chain(self.load_active()?.map(|a| a.project));
+        for name in used {
+            if !projects.iter().any(|p| p.name == name) {
+                projects.push(Project::new(name));
+            }
+        }
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(projects)
+    }
+
+    /// The project called `name` with its metadata, or `None` if it is neither
+    /// used on any frame nor registered.
+    pub fn project(&self, name: &str) -> Result<Option<Project>, WatsonError<S::Error>> {
+        Ok(self.project_list()?.into_iter().find(|p| p.name == name))
+    }
+
+    /// Stores `project`'s metadata, creating or replacing its registry entry.
+    pub fn update_project(&self, project: Project) -> Result<Project, WatsonError<S::Error>> {
+        if let Some(color) = &project.color
+            && parse_hex_color(color).is_none()
+        {
+            return Err(WatsonError::InvalidColor(color.clone()));
+        }
+        let mut projects = self.load_projects()?;
+        let record = ProjectRecord::from(&project);
+        match projects.iter_mut().find(|p| p.name == project.name) {
+            Some(existing) => *existing = record,
+            None => projects.push(record),
+        }
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+        self.save_projects(&projects)?;
+        Ok(project)
+    }
+
+    /// Archives or restores a known project.
+    pub fn set_archived(
+        &self,
+        name: &str,
+        archived: bool,
+    ) -> Result<Project, WatsonError<S::Error>> {
+        let mut project = self
+            .project(name)?
+            .ok_or_else(|| WatsonError::ProjectNotFound(name.to_string()))?;
+        project.archived = archived;
+        self.update_project(project)
     }
 
     pub fn tags(&self) -> Result<Vec<String>, WatsonError<S::Error>> {
@@ -552,6 +1279,103 @@ mod tests {
         assert!(w.status().unwrap().is_some());
     }
 
+    // --- split ---
+
+    #[test]
+    fn split_cuts_frame_in_two() {
+        let w = w();
+        let frame = w
+            .add("backend", vec!["api".into()], t(9, 0), t(12, 0))
+            .unwrap();
+        let (first, second) = w
+            .split(frame.id, t(10, 30), "frontend", vec!["ui".into()])
+            .unwrap();
+        assert_eq!(first.id, frame.id);
+        assert_eq!((first.start, first.end), (t(9, 0), t(10, 30)));
+        assert_eq!(first.project, "backend");
+        assert_eq!((second.start, second.end), (t(10, 30), t(12, 0)));
+        assert_eq!(second.project, "frontend");
+        assert_eq!(second.tags, vec!["ui"]);
+        assert_eq!(w.log().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn split_rejects_time_outside_frame() {
+        let w = w();
+        let frame = w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        for at in [t(9, 0), t(10, 0), t(11, 0)] {
+            assert!(matches!(
+                w.split(frame.id, at, "backend", vec![]).unwrap_err(),
+                WatsonError::InvalidTimeRange
+            ));
+        }
+    }
+
+    #[test]
+    fn split_unknown_id_returns_error() {
+        assert!(matches!(
+            w().split(Uuid::new_v4(), t(9, 0), "x", vec![]).unwrap_err(),
+            WatsonError::FrameNotFound
+        ));
+    }
+
+    // --- merge ---
+
+    #[test]
+    fn merge_combines_fragments_into_one_frame() {
+        let w = w();
+        let a = w
+            .add("backend", vec!["api".into()], t(9, 0), t(10, 0))
+            .unwrap();
+        let b = w
+            .add(
+                "backend",
+                vec!["db".into(), "api".into()],
+                t(10, 15),
+                t(11, 0),
+            )
+            .unwrap();
+        let merged = w.merge(&[b.id, a.id]).unwrap();
+        assert_eq!(merged.id, a.id);
+        assert_eq!((merged.start, merged.end), (t(9, 0), t(11, 0)));
+        assert_eq!(merged.tags, vec!["api", "db"]);
+        assert_eq!(w.log().unwrap(), vec![merged]);
+    }
+
+    #[test]
+    fn merge_rejects_different_projects() {
+        let w = w();
+        let a = w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        let b = w.add("frontend", vec![], t(10, 0), t(11, 0)).unwrap();
+        assert!(matches!(
+            w.merge(&[a.id, b.id]).unwrap_err(),
+            WatsonError::MixedProjects(_, _)
+        ));
+        assert_eq!(w.log().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn merge_rejects_span_overlapping_other_frame() {
+        let w = w();
+        let a = w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("meeting", vec![], t(10, 0), t(10, 30)).unwrap();
+        let b = w.add("backend", vec![], t(10, 30), t(11, 0)).unwrap();
+        assert!(matches!(
+            w.merge(&[a.id, b.id]).unwrap_err(),
+            WatsonError::OverlappingFrame(_)
+        ));
+    }
+
+    #[test]
+    fn merge_needs_at_least_two_frames() {
+        let w = w();
+        let a = w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        assert!(matches!(
+            w.merge(&[a.id, a.id]).unwrap_err(),
+            WatsonError::NothingToMerge
+        ));
+    }
+
     // --- rename ---
 
     #[test]
@@ -584,6 +1408,402 @@ mod tests {
         ));
     }
 
+    // --- rename_tag ---
+
+    #[test]
+    fn rename_tag_updates_frames_and_active() {
+        let w = w();
+        w.add("a", vec!["old".into(), "x".into()], t(9, 0), t(10, 0))
+            .unwrap();
+        w.add("b", vec!["other".into()], t(10, 0), t(11, 0))
+            .unwrap();
+        w.start("c", vec!["old".into()], t(11, 0)).unwrap();
+        assert_eq!(w.rename_tag("old", "new").unwrap(), 2);
+        let log = w.log().unwrap();
+        assert_eq!(log[0].tags, vec!["new", "x"]);
+        assert_eq!(log[1].tags, vec!["other"]);
+        assert_eq!(w.status().unwrap().unwrap().tags, vec!["new"]);
+    }
+
+    #[test]
+    fn rename_tag_into_existing_tag_does_not_duplicate() {
+        let w = w();
+        w.add("a", vec!["be".into(), "backend".into()], t(9, 0), t(10, 0))
+            .unwrap();
+        w.rename_tag("be", "backend").unwrap();
+        assert_eq!(w.log().unwrap()[0].tags, vec!["backend"]);
+    }
+
+    #[test]
+    fn rename_tag_to_itself_keeps_the_tag() {
+        let w = w();
+        w.add("a", vec!["api".into()], t(9, 0), t(10, 0)).unwrap();
+        w.start("b", vec!["api".into()], t(10, 0)).unwrap();
+        assert_eq!(w.rename_tag("api", "api").unwrap(), 0);
+        assert_eq!(w.log().unwrap()[0].tags, vec!["api"]);
+        assert_eq!(w.status().unwrap().unwrap().tags, vec!["api"]);
+    }
+
+    #[test]
+    fn rename_unknown_tag_returns_error() {
+        let w = w();
+        w.add("a", vec!["x".into()], t(9, 0), t(10, 0)).unwrap();
+        assert!(matches!(
+            w.rename_tag("ghost", "new").unwrap_err(),
+            WatsonError::TagNotFound(_)
+        ));
+    }
+
+    // --- add_tags / remove_tags ---
+
+    #[test]
+    fn add_tags_only_touches_matching_frames() {
+        let w = w();
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("frontend", vec![], t(10, 0), t(11, 0)).unwrap();
+        w.add("backend", vec!["api".into()], t(11, 0), t(12, 0))
+            .unwrap();
+        let filter = FrameFilter {
+            project: Some("backend".into()),
+            ..Default::default()
+        };
+        let changed = w.add_tags(&filter, &["api".into()]).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].start, t(9, 0));
+        let tags: Vec<_> = w.log().unwrap().into_iter().map(|f| f.tags).collect();
+        assert_eq!(tags, vec![vec!["api"], vec![], vec!["api"]]);
+    }
+
+    #[test]
+    fn remove_tags_uses_tag_filter() {
+        let w = w();
+        w.add("a", vec!["meeting".into(), "x".into()], t(9, 0), t(10, 0))
+            .unwrap();
+        w.add("a", vec!["meeting".into()], t(10, 0), t(11, 0))
+            .unwrap();
+        let filter = FrameFilter {
+            tags: vec!["x".into()],
+            ..Default::default()
+        };
+        let changed = w.remove_tags(&filter, &["meeting".into()]).unwrap();
+        assert_eq!(changed.len(), 1);
+        let tags: Vec<_> = w.log().unwrap().into_iter().map(|f| f.tags).collect();
+        assert_eq!(tags, vec![vec!["x"], vec!["meeting"]]);
+    }
+
+    #[test]
+    fn retag_filtering_on_removed_tag_still_adds() {
+        let w = w();
+        w.add("a", vec!["mtg".into()], t(9, 0), t(10, 0)).unwrap();
+        let filter = FrameFilter {
+            tags: vec!["mtg".into()],
+            ..Default::default()
+        };
+        let changed = w
+            .retag(&filter, &["meeting".into()], &["mtg".into()])
+            .unwrap();
+        assert_eq!(changed[0].tags, vec!["meeting"]);
+        assert_eq!(w.log().unwrap()[0].tags, vec!["meeting"]);
+    }
+
+    // --- aliases ---
+
+    fn aliased() -> Watson<MemoryStorage> {
+        let mut aliases = AliasConfig::default();
+        aliases
+            .projects
+            .insert("backend".into(), vec!["Backend".into(), "be".into()]);
+        aliases.tags.insert("meeting".into(), vec!["mtg".into()]);
+        w().with_aliases(aliases)
+    }
+
+    #[test]
+    fn aliases_apply_on_start_and_add() {
+        let w = aliased();
+        let frame = w
+            .add(
+                "be",
+                vec!["mtg".into(), "meeting".into()],
+                t(9, 0),
+                t(10, 0),
+            )
+            .unwrap();
+        assert_eq!(frame.project, "backend");
+        assert_eq!(frame.tags, vec!["meeting"]);
+        let active = w.start("Backend", vec!["mtg".into()], t(10, 0)).unwrap();
+        assert_eq!(active.project, "backend");
+        assert_eq!(active.tags, vec!["meeting"]);
+    }
+
+    #[test]
+    fn aliases_apply_on_import() {
+        let w = aliased();
+        w.import_frames(vec![Frame::new("be", vec![], t(9, 0), t(10, 0))])
+            .unwrap();
+        assert_eq!(w.projects().unwrap(), vec!["backend"]);
+    }
+
+    // --- merge_projects / merge_tags ---
+
+    #[test]
+    fn merge_projects_into_existing_project() {
+        let w = w();
+        w.add("Backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("be", vec![], t(10, 0), t(11, 0)).unwrap();
+        w.add("backend", vec![], t(11, 0), t(12, 0)).unwrap();
+        let count = w
+            .merge_projects(&["Backend".into(), "be".into(), "ghost".into()], "backend")
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(w.projects().unwrap(), vec!["backend"]);
+    }
+
+    #[test]
+    fn merge_projects_without_any_source_fails() {
+        let w = w();
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        assert!(matches!(
+            w.merge_projects(&["ghost".into()], "backend").unwrap_err(),
+            WatsonError::ProjectNotFound(_)
+        ));
+    }
+
+    #[test]
+    fn merge_tags_deduplicates() {
+        let w = w();
+        w.add(
+            "a",
+            vec!["mtg".into(), "Meeting".into(), "meeting".into()],
+            t(9, 0),
+            t(10, 0),
+        )
+        .unwrap();
+        let count = w
+            .merge_tags(&["mtg".into(), "Meeting".into()], "meeting")
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(w.log().unwrap()[0].tags, vec!["meeting"]);
+    }
+
+    // --- project registry ---
+
+    #[test]
+    fn project_list_combines_frames_and_registry() {
+        let w = w();
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.update_project(Project {
+            description: Some("Planned".into()),
+            ..Project::new("research")
+        })
+        .unwrap();
+        let names: Vec<_> = w
+            .project_list()
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["backend", "research"]);
+        assert_eq!(
+            w.project("research")
+                .unwrap()
+                .unwrap()
+                .description
+                .as_deref(),
+            Some("Planned")
+        );
+    }
+
+    #[test]
+    fn unknown_epic_projects_suggests_known_names() {
+        let w = w();
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        let epic = EpicConfig {
+            name: "Work".into(),
+            project: Some("backend".into()),
+            projects: vec!["bakend".into()],
+            ..Default::default()
+        };
+        let unknown = w.unknown_epic_projects(&epic).unwrap();
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].name, "bakend");
+        assert_eq!(unknown[0].suggestion.as_deref(), Some("backend"));
+    }
+
+    #[test]
+    fn archived_projects_are_hidden_from_projects() {
+        let w = w();
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("legacy", vec![], t(10, 0), t(11, 0)).unwrap();
+        w.set_archived("legacy", true).unwrap();
+        assert_eq!(w.projects().unwrap(), vec!["backend"]);
+        assert_eq!(w.log().unwrap().len(), 2);
+        w.set_archived("legacy", false).unwrap();
+        assert_eq!(w.projects().unwrap(), vec!["backend", "legacy"]);
+    }
+
+    #[test]
+    fn set_archived_unknown_project_fails() {
+        assert!(matches!(
+            w().set_archived("ghost", true).unwrap_err(),
+            WatsonError::ProjectNotFound(_)
+        ));
+    }
+
+    #[test]
+    fn update_project_rejects_bad_color() {
+        let project = Project {
+            color: Some("blue".into()),
+            ..Project::new("backend")
+        };
+        assert!(matches!(
+            w().update_project(project).unwrap_err(),
+            WatsonError::InvalidColor(_)
+        ));
+    }
+
+    #[test]
+    fn rename_carries_registry_entry() {
+        let w = w();
+        w.add("old", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.update_project(Project {
+            color: Some("#ff0000".into()),
+            ..Project::new("old")
+        })
+        .unwrap();
+        w.rename("old", "new").unwrap();
+        let project = w.project("new").unwrap().unwrap();
+        assert_eq!(project.color.as_deref(), Some("#ff0000"));
+        assert!(w.project("old").unwrap().is_none());
+    }
+
+    #[test]
+    fn merge_projects_keeps_target_registry_entry() {
+        let w = w();
+        w.add("be", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("backend", vec![], t(10, 0), t(11, 0)).unwrap();
+        for (name, color) in [("be", "#111111"), ("backend", "#222222")] {
+            w.update_project(Project {
+                color: Some(color.into()),
+                ..Project::new(name)
+            })
+            .unwrap();
+        }
+        w.merge_projects(&["be".into()], "backend").unwrap();
+        let list = w.project_list().unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].color.as_deref(), Some("#222222"));
+    }
+
+    #[test]
+    fn rename_moves_subtree_with_separator() {
+        let w = w().with_project_separator(Some("/".into()));
+        w.add("acme", vec![], t(8, 0), t(9, 0)).unwrap();
+        w.add("acme/website", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("acme-corp", vec![], t(10, 0), t(11, 0)).unwrap();
+        w.update_project(Project::new("acme/website/docs")).unwrap();
+        assert_eq!(w.rename("acme", "globex").unwrap(), 2);
+        assert_eq!(
+            w.projects().unwrap(),
+            vec![
+                "acme-corp",
+                "globex",
+                "globex/website",
+                "globex/website/docs"
+            ]
+        );
+    }
+
+    #[test]
+    fn rename_without_separator_is_exact() {
+        let w = w();
+        w.add("acme", vec![], t(8, 0), t(9, 0)).unwrap();
+        w.add("acme/website", vec![], t(9, 0), t(10, 0)).unwrap();
+        assert_eq!(w.rename("acme", "globex").unwrap(), 1);
+        assert_eq!(w.projects().unwrap(), vec!["acme/website", "globex"]);
+    }
+
+    // --- strict mode ---
+
+    /// A strict `Watson` that already knows "backend" and the tag "api".
+    fn strict() -> Watson<MemoryStorage> {
+        let w = w();
+        w.add("backend", vec!["api".into()], t(8, 0), t(9, 0))
+            .unwrap();
+        w.with_strictness(Strictness {
+            projects: true,
+            tags: true,
+            allowed_projects: vec!["research".into()],
+            allowed_tags: vec![],
+        })
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_project_with_suggestion() {
+        let err = strict().start("bakcend", vec![], t(10, 0)).unwrap_err();
+        let WatsonError::UnknownName(unknown) = err else {
+            panic!("expected UnknownName, got {err:?}");
+        };
+        assert_eq!(unknown.kind, NameKind::Project);
+        assert_eq!(unknown.suggestion.as_deref(), Some("backend"));
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_tags_on_add_edit_and_split() {
+        let w = strict();
+        let is_unknown_tag =
+            |e: WatsonError<_>| matches!(e, WatsonError::UnknownName(u) if u.kind == NameKind::Tag);
+        assert!(is_unknown_tag(
+            w.add("backend", vec!["apj".into()], t(10, 0), t(11, 0))
+                .unwrap_err()
+        ));
+        let id = w.log().unwrap()[0].id;
+        assert!(is_unknown_tag(
+            w.edit(id, "backend", vec!["new".into()], t(8, 0), t(9, 0))
+                .unwrap_err()
+        ));
+        assert!(is_unknown_tag(
+            w.split(id, t(8, 30), "backend", vec!["new".into()])
+                .unwrap_err()
+        ));
+    }
+
+    #[test]
+    fn strict_mode_accepts_known_allowed_and_accepted_names() {
+        let w = strict();
+        w.add("backend", vec!["api".into()], t(10, 0), t(11, 0))
+            .unwrap();
+        w.add("research", vec![], t(11, 0), t(12, 0)).unwrap();
+        let unknown = w.unknown_names("docs", &["writing".into()]).unwrap();
+        assert_eq!(unknown.len(), 2);
+        for name in &unknown {
+            w.accept_name(name);
+        }
+        w.add("docs", vec!["writing".into()], t(12, 0), t(13, 0))
+            .unwrap();
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_names_on_import() {
+        let w = strict();
+        let frames = vec![Frame::new("legacy", vec![], t(10, 0), t(11, 0))];
+        assert_eq!(w.unknown_names_in(&frames).unwrap().len(), 1);
+        assert!(matches!(
+            w.import_frames(frames).unwrap_err(),
+            WatsonError::UnknownName(_)
+        ));
+    }
+
+    #[test]
+    fn strict_mode_is_off_by_default() {
+        let w = w();
+        assert!(
+            w.unknown_names("anything", &["new".into()])
+                .unwrap()
+                .is_empty()
+        );
+        w.start("anything", vec![], t(9, 0)).unwrap();
+    }
+
     // --- import_frames ---
 
     #[test]
@@ -619,6 +1839,22 @@ mod tests {
         assert_eq!(frames[1].project, "second");
     }
 
+    #[test]
+    fn search_ranks_project_matches_first() {
+        let w = w();
+        w.add("docs", vec!["website".into()], t(8, 0), t(9, 0))
+            .unwrap();
+        w.add("website", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.add("backend", vec![], t(10, 0), t(11, 0)).unwrap();
+        let projects: Vec<String> = w
+            .search("website")
+            .unwrap()
+            .into_iter()
+            .map(|f| f.project)
+            .collect();
+        assert_eq!(projects, vec!["website", "docs"]);
+    }
+
     // --- projects ---
 
     #[test]
@@ -668,6 +1904,78 @@ mod tests {
         assert_eq!(active.start, t(9, 0));
     }
 
+    // --- running ---
+
+    #[test]
+    fn running_returns_none_when_idle() {
+        assert!(w().running(t(10, 0)).unwrap().is_none());
+    }
+
+    #[test]
+    fn running_materializes_active_frame_without_saving() {
+        let w = w();
+        w.start("backend", vec!["api".into()], t(9, 0)).unwrap();
+        let frame = w.running(t(10, 30)).unwrap().unwrap();
+        assert_eq!(frame.project, "backend");
+        assert_eq!(frame.end - frame.start, Duration::minutes(90));
+        assert!(w.log().unwrap().is_empty());
+        assert!(w.status().unwrap().is_some());
+    }
+
+    // --- gaps ---
+
+    #[test]
+    fn gaps_treat_running_frame_as_tracked() {
+        let w = w();
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        w.start("frontend", vec![], t(11, 0)).unwrap();
+        let day = t(0, 0).date_naive();
+        let gaps = w
+            .gaps(day, day, &WorkingHoursConfig::default(), &Utc, t(12, 0))
+            .unwrap();
+        assert_eq!(
+            gaps.iter().map(|g| (g.start, g.end)).collect::<Vec<_>>(),
+            vec![(t(10, 0), t(11, 0))]
+        );
+    }
+
+    // --- overrun / record_activity ---
+
+    #[test]
+    fn overrun_detects_frame_past_max() {
+        let w = w();
+        w.start("backend", vec![], t(8, 0)).unwrap();
+        assert!(w.overrun(Duration::hours(8), t(15, 0)).unwrap().is_none());
+        let overrun = w.overrun(Duration::hours(8), t(18, 0)).unwrap().unwrap();
+        assert_eq!(overrun.threshold, t(16, 0));
+    }
+
+    #[test]
+    fn record_activity_updates_active_frame() {
+        let w = w();
+        w.start("backend", vec![], t(9, 0)).unwrap();
+        w.record_activity(t(11, 0), None).unwrap();
+        assert_eq!(w.status().unwrap().unwrap().last_activity, Some(t(11, 0)));
+    }
+
+    #[test]
+    fn record_activity_ignored_once_past_max() {
+        let w = w();
+        w.start("backend", vec![], t(8, 0)).unwrap();
+        w.record_activity(t(12, 0), Some(Duration::hours(8)))
+            .unwrap();
+        w.record_activity(t(20, 0), Some(Duration::hours(8)))
+            .unwrap();
+        assert_eq!(w.status().unwrap().unwrap().last_activity, Some(t(12, 0)));
+    }
+
+    #[test]
+    fn record_activity_when_idle_is_noop() {
+        let w = w();
+        w.record_activity(t(11, 0), None).unwrap();
+        assert!(w.status().unwrap().is_none());
+    }
+
     // --- start_or_replace ---
 
     #[test]
@@ -778,4 +2086,126 @@ mod tests {
         let err = w.stop(t(10, 30)).unwrap_err();
         assert!(matches!(err, WatsonError::OverlappingFrame(_)));
     }
+
+    // --- hooks ---
+
+    #[cfg(unix)]
+    fn with_hook(command: &str, policy: crate::hooks::FailurePolicy) -> Watson<MemoryStorage> {
+        w().with_hooks(HooksConfig {
+            run: vec![crate::config::HookConfig {
+                command: command.into(),
+                on_failure: Some(policy),
+                ..Default::default()
+            }],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn aborting_hook_cancels_the_change() {
+        use crate::hooks::{EventKind, FailurePolicy};
+        let w = with_hook(
+            "[ \"$WATSON_PROJECT$WATSON_TO\" != secret ]",
+            FailurePolicy::Abort,
+        );
+        w.add("backend", vec![], t(9, 0), t(10, 0)).unwrap();
+        let err = w.add("secret", vec![], t(10, 0), t(11, 0)).unwrap_err();
+        assert!(matches!(err, WatsonError::Hook(f) if f.event == EventKind::Added));
+        assert_eq!(w.log().unwrap().len(), 1);
+
+        w.start("secret", vec![], t(12, 0)).unwrap_err();
+        assert!(w.status().unwrap().is_none());
+        let err = w.rename("backend", "secret").unwrap_err();
+        assert!(matches!(err, WatsonError::Hook(_)));
+        assert_eq!(w.projects().unwrap(), vec!["backend"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn aborting_a_split_on_its_second_event_runs_no_warn_hooks() {
+        use crate::config::HookConfig;
+        use crate::hooks::{EventKind, FailurePolicy};
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events");
+        let w = w().with_hooks(HooksConfig {
+            run: vec![
+                HookConfig {
+                    command: format!("echo $WATSON_EVENT >> '{}'", events.display()),
+                    ..Default::default()
+                },
+                HookConfig {
+                    command: "[ \"$WATSON_EVENT\" != added ]".into(),
+                    on: vec![EventKind::Edited, EventKind::Added],
+                    on_failure: Some(FailurePolicy::Abort),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        });
+        w.start("backend", vec![], t(9, 0)).unwrap();
+        let frame = w.stop(t(11, 0)).unwrap();
+        std::fs::remove_file(&events).unwrap();
+
+        let err = w.split(frame.id, t(10, 0), "docs", vec![]).unwrap_err();
+        assert!(matches!(err, WatsonError::Hook(f) if f.event == EventKind::Added));
+        assert!(!events.exists());
+        assert_eq!(w.log().unwrap(), vec![frame]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn bulk_changes_run_hooks() {
+        use crate::hooks::FailurePolicy;
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events");
+        let w = with_hook(
+            &format!(
+                "echo \"$WATSON_EVENT $WATSON_FROM$WATSON_TAGS\" >> '{}'",
+                events.display()
+            ),
+            FailurePolicy::Warn,
+        );
+        w.import_frames(vec![Frame::new("old", vec!["a".into()], t(9, 0), t(10, 0))])
+            .unwrap();
+        w.rename_tag("a", "b").unwrap();
+        w.add_tags(&FrameFilter::default(), &["c".into()]).unwrap();
+        w.merge_tags(&["c".into()], "d").unwrap();
+        w.merge_projects(&["old".into()], "new").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&events).unwrap(),
+            "added a\nedited b\nedited b,c\nedited b,d\nrenamed old\n"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn aborting_hook_leaves_the_active_frame_alone() {
+        use crate::hooks::FailurePolicy;
+        let w = with_hook("[ \"$WATSON_EVENT\" = started ]", FailurePolicy::Abort);
+        w.start("old", vec!["a".into()], t(9, 0)).unwrap();
+        w.merge_projects(&["old".into()], "new").unwrap_err();
+        assert_eq!(w.status().unwrap().unwrap().project, "old");
+
+        let w = with_hook("[ \"$WATSON_EVENT\" != edited ]", FailurePolicy::Abort);
+        w.start("backend", vec!["a".into()], t(9, 0)).unwrap();
+        w.add("backend", vec!["a".into()], t(7, 0), t(8, 0))
+            .unwrap();
+        w.rename_tag("a", "b").unwrap_err();
+        w.merge_tags(&["a".into()], "b").unwrap_err();
+        assert_eq!(w.status().unwrap().unwrap().tags, vec!["a"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn warning_hook_lets_the_change_through() {
+        use crate::hooks::FailurePolicy;
+        let w = with_hook("exit 1", FailurePolicy::Warn);
+        w.start("backend", vec![], t(9, 0)).unwrap();
+        w.stop(t(10, 0)).unwrap();
+        let warnings = w.take_hook_warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(w.take_hook_warnings().is_empty());
+        assert_eq!(w.log().unwrap().len(), 1);
+    }
 }
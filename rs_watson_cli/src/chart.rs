@@ -0,0 +1,558 @@
+//! Unicode bar charts and heatmaps for `report --chart`. Charts are rendered to
+//! strings that fit the terminal width; colors are dropped when `NO_COLOR` is set.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use owo_colors::OwoColorize;
+use rs_watson::config::WeekStart;
+use rs_watson::{Frame, Report};
+
+use crate::format::{fmt_duration, weekday_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChartKind {
+    /// One bar per project with its share of the total.
+    Projects,
+    /// One bar per day, stacked by project.
+    Days,
+    /// Weekday × hour-of-day heatmap.
+    Weekdays,
+    /// A year of days as a calendar heatmap, one column per week.
+    Calendar,
+}
+
+impl ChartKind {
+    pub(crate) const ALL: [ChartKind; 4] = [
+        ChartKind::Projects,
+        ChartKind::Days,
+        ChartKind::Weekdays,
+        ChartKind::Calendar,
+    ];
+}
+
+impl fmt::Display for ChartKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChartKind::Projects => "projects",
+            ChartKind::Days => "days",
+            ChartKind::Weekdays => "weekdays",
+            ChartKind::Calendar => "calendar",
+        })
+    }
+}
+
+impl FromStr for ChartKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        ChartKind::ALL
+            .into_iter()
+            .find(|k| k.to_string() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown chart \"{s}\", expected one of: projects, days, weekdays, calendar"
+                )
+            })
+    }
+}
+
+type Rgb = (u8, u8, u8);
+
+/// Colors for projects without a registry color, in order of their total.
+const PALETTE: [Rgb; 6] = [
+    (229, 192, 123),
+    (86, 182, 194),
+    (198, 120, 221),
+    (152, 195, 121),
+    (97, 175, 239),
+    (224, 108, 117),
+];
+/// Heatmap colors from the lowest to the highest level.
+const HEAT: [Rgb; 5] = [
+    (88, 88, 88),
+    (14, 68, 41),
+    (0, 109, 50),
+    (38, 166, 65),
+    (57, 211, 83),
+];
+/// Heatmap cells from empty to full; they stay readable without color.
+const SHADES: [char; 5] = ['·', '░', '▒', '▓', '█'];
+/// Fills for stacked segments without color, so neighbours stay apart.
+const PATTERNS: [char; 4] = ['█', '▓', '▒', '░'];
+/// Partial blocks for the fraction of a bar's last cell, in eighths.
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// How charts are drawn.
+pub(crate) struct ChartStyle {
+    /// Columns available, usually the terminal width.
+    pub(crate) width: usize,
+    pub(crate) color: bool,
+    /// Registry colors by project name.
+    pub(crate) project_colors: HashMap<String, Rgb>,
+}
+
+impl ChartStyle {
+    /// Width from `COLUMNS`, then the terminal, else 80; colors unless `NO_COLOR`
+    /// is set to a non-empty value.
+    pub(crate) fn detect(project_colors: HashMap<String, Rgb>) -> Self {
+        let width = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|c| c.trim().parse().ok())
+            .or_else(|| {
+                console::Term::stdout()
+                    .size_checked()
+                    .map(|(_, w)| usize::from(w))
+            })
+            .unwrap_or(80);
+        Self {
+            width,
+            color: std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            project_colors,
+        }
+    }
+
+    fn paint(&self, text: &str, (r, g, b): Rgb) -> String {
+        if self.color {
+            text.truecolor(r, g, b).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn dim(&self, text: &str) -> String {
+        if self.color {
+            text.bright_black().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// The registry color of `project`, else the palette color for its rank.
+    fn project_rgb(&self, project: &str, rank: usize) -> Rgb {
+        self.project_colors
+            .get(project)
+            .copied()
+            .unwrap_or(PALETTE[rank % PALETTE.len()])
+    }
+
+    /// The fill of a stacked segment: solid when colored, a pattern otherwise.
+    fn fill(&self, rank: usize) -> char {
+        if self.color {
+            '█'
+        } else {
+            PATTERNS[rank % PATTERNS.len()]
+        }
+    }
+
+    fn heat(&self, level: usize, cell: &str) -> String {
+        self.paint(cell, HEAT[level])
+    }
+}
+
+/// Renders `kind` for `frames`. The calendar covers the year up to `end`.
+pub(crate) fn render(
+    kind: ChartKind,
+    frames: &[Frame],
+    style: &ChartStyle,
+    week_start: WeekStart,
+    end: NaiveDate,
+) -> String {
+    match kind {
+        ChartKind::Projects => projects_chart(frames, style),
+        ChartKind::Days => days_chart(frames, style),
+        ChartKind::Weekdays => weekdays_chart(frames, style, week_start),
+        ChartKind::Calendar => calendar_chart(frames, style, week_start, end),
+    }
+}
+
+fn ratio(part: Duration, whole: Duration) -> f64 {
+    if whole <= Duration::zero() {
+        0.0
+    } else {
+        part.num_seconds() as f64 / whole.num_seconds() as f64
+    }
+}
+
+/// A bar `cells` wide, with eighth blocks for the fraction.
+fn bar(cells: f64) -> String {
+    let eighths = (cells.max(0.0) * 8.0).round() as usize;
+    let (full, rest) = (eighths / 8, eighths % 8);
+    let mut bar = "█".repeat(full);
+    if rest > 0 {
+        bar.push(EIGHTHS[rest]);
+    }
+    bar
+}
+
+/// `text` cut to `width` characters, ending in `…` when shortened.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// Heat level 0–4 of `value` against the largest value.
+fn level(value: Duration, max: Duration) -> usize {
+    if value <= Duration::zero() {
+        0
+    } else {
+        ((ratio(value, max) * 4.0).ceil() as usize).clamp(1, 4)
+    }
+}
+
+fn short_weekday(day: chrono::Weekday) -> &'static str {
+    &weekday_name(day)[..3]
+}
+
+fn projects_chart(frames: &[Frame], style: &ChartStyle) -> String {
+    let report = Report::from_frames(frames);
+    let name_width = report
+        .projects
+        .iter()
+        .map(|p| p.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(24);
+    let max = report
+        .projects
+        .first()
+        .map_or(Duration::zero(), |p| p.total);
+    // name, bar, duration and share, with two spaces between columns
+    let bar_width = style.width.saturating_sub(name_width + 26).max(10);
+
+    let mut out = String::new();
+    for (rank, project) in report.projects.iter().enumerate() {
+        let bar = format!(
+            "{:<bar_width$}",
+            bar(ratio(project.total, max) * bar_width as f64)
+        );
+        out += &format!(
+            "{:<name_width$}  {}  {:>12}  {:>5.1}%\n",
+            truncate(&project.name, name_width),
+            style.paint(&bar, style.project_rgb(&project.name, rank)),
+            fmt_duration(project.total),
+            ratio(project.total, report.total) * 100.0,
+        );
+    }
+    out
+}
+
+fn days_chart(frames: &[Frame], style: &ChartStyle) -> String {
+    let report = Report::from_frames(frames);
+    let mut by_day: BTreeMap<NaiveDate, HashMap<&str, Duration>> = BTreeMap::new();
+    for frame in frames {
+        *by_day
+            .entry(frame.start.with_timezone(&Local).date_naive())
+            .or_default()
+            .entry(frame.project.as_str())
+            .or_insert_with(Duration::zero) += frame.end - frame.start;
+    }
+    let day_total = |projects: &HashMap<&str, Duration>| {
+        projects.values().fold(Duration::zero(), |acc, d| acc + *d)
+    };
+    let max = by_day
+        .values()
+        .map(day_total)
+        .max()
+        .unwrap_or_else(Duration::zero);
+    let (Some(&first), Some(&last)) = (by_day.keys().next(), by_day.keys().next_back()) else {
+        return String::new();
+    };
+    // "Mon 12 Jan", bar, duration
+    let bar_width = style.width.saturating_sub(26).max(10);
+
+    let mut out = String::new();
+    for day in first.iter_days().take_while(|d| *d <= last) {
+        let projects = by_day.remove(&day).unwrap_or_default();
+        let mut bar = String::new();
+        let (mut sum, mut drawn) = (Duration::zero(), 0);
+        for (rank, project) in report.projects.iter().enumerate() {
+            let Some(d) = projects.get(project.name.as_str()) else {
+                continue;
+            };
+            sum += *d;
+            let end = (ratio(sum, max) * bar_width as f64).round() as usize;
+            let cells = style.fill(rank).to_string().repeat(end - drawn);
+            bar += &style.paint(&cells, style.project_rgb(&project.name, rank));
+            drawn = end;
+        }
+        out += &format!(
+            "{} {}  {}{}  {}\n",
+            short_weekday(day.weekday()),
+            day.format("%d %b"),
+            bar,
+            " ".repeat(bar_width - drawn),
+            fmt_duration(sum),
+        );
+    }
+
+    let mut legend = String::new();
+    let mut line_width = 0;
+    for (rank, project) in report.projects.iter().enumerate() {
+        let entry_width = project.name.chars().count() + 4;
+        if line_width > 0 && line_width + entry_width > style.width {
+            legend.push('\n');
+            line_width = 0;
+        }
+        legend += &format!(
+            "{} {}  ",
+            style.paint(
+                &style.fill(rank).to_string(),
+                style.project_rgb(&project.name, rank)
+            ),
+            project.name
+        );
+        line_width += entry_width;
+    }
+    out + "\n" + legend.trim_end() + "\n"
+}
+
+/// Adds the time between `start` and `end` (local) to `grid` by weekday slot
+/// and hour of day.
+fn spread_by_hour(
+    grid: &mut [[Duration; 24]; 7],
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    week_start: WeekStart,
+) {
+    let mut at = start;
+    while at < end {
+        let hour_start = at.date().and_hms_opt(at.hour(), 0, 0).unwrap_or(at);
+        let next = (hour_start + Duration::hours(1)).min(end);
+        let slot = week_start.days_into_week(at.weekday()) as usize;
+        grid[slot][at.hour() as usize] += next - at;
+        at = next;
+    }
+}
+
+fn weekdays_chart(frames: &[Frame], style: &ChartStyle, week_start: WeekStart) -> String {
+    let mut grid = [[Duration::zero(); 24]; 7];
+    for frame in frames {
+        spread_by_hour(
+            &mut grid,
+            frame.start.with_timezone(&Local).naive_local(),
+            frame.end.with_timezone(&Local).naive_local(),
+            week_start,
+        );
+    }
+    let max = grid
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or_else(Duration::zero);
+    // label, 24 cells, total
+    let cell_width = if style.width >= 4 + 48 + 14 { 2 } else { 1 };
+    let label_every = if cell_width == 2 { 3 } else { 6 };
+
+    let mut header = " ".repeat(4);
+    for hour in (0..24).step_by(label_every) {
+        header += &format!("{:<w$}", hour, w = label_every * cell_width);
+    }
+    let mut out = style.dim(header.trim_end()) + "\n";
+    for (slot, hours) in grid.iter().enumerate() {
+        let mut row = format!("{} ", short_weekday(week_start.weekdays()[slot]));
+        for d in hours {
+            let level = level(*d, max);
+            let cell = SHADES[level].to_string().repeat(cell_width);
+            row += &style.heat(level, &cell);
+        }
+        let total = hours.iter().fold(Duration::zero(), |acc, d| acc + *d);
+        out += &format!("{row}  {}\n", fmt_duration(total));
+    }
+    out + &legend(style)
+}
+
+/// "Less · ░ ▒ ▓ █ More" under the heatmaps.
+fn legend(style: &ChartStyle) -> String {
+    let shades: Vec<String> = SHADES
+        .iter()
+        .enumerate()
+        .map(|(level, c)| style.heat(level, &c.to_string()))
+        .collect();
+    format!(
+        "    {} {} {}\n",
+        style.dim("Less"),
+        shades.join(" "),
+        style.dim("More")
+    )
+}
+
+fn calendar_chart(
+    frames: &[Frame],
+    style: &ChartStyle,
+    week_start: WeekStart,
+    end: NaiveDate,
+) -> String {
+    let mut daily: HashMap<NaiveDate, Duration> = HashMap::new();
+    for frame in frames {
+        *daily
+            .entry(frame.start.with_timezone(&Local).date_naive())
+            .or_insert_with(Duration::zero) += frame.end - frame.start;
+    }
+    let cell_width = if style.width >= 4 + 53 * 2 { 2 } else { 1 };
+    let weeks = (style.width.saturating_sub(4) / cell_width).clamp(1, 53);
+    let last_week = week_start.start_of_week(end);
+    let first_week = last_week - Duration::weeks(weeks as i64 - 1);
+    let week_of = |column: usize| first_week + Duration::weeks(column as i64);
+    let in_range = |date: &NaiveDate| *date >= first_week && *date <= end;
+    let max = daily
+        .iter()
+        .filter(|(date, _)| in_range(date))
+        .map(|(_, d)| *d)
+        .max()
+        .unwrap_or_else(Duration::zero);
+
+    // Month names above the week they start in, when there is room.
+    let mut months = " ".repeat(4);
+    for column in 0..weeks {
+        let start = week_of(column);
+        let new_month = column == 0 || start.month() != week_of(column - 1).month();
+        let at = 4 + column * cell_width;
+        let fits = months.chars().count() <= at && at + 3 <= 4 + weeks * cell_width;
+        if new_month && fits {
+            months += &" ".repeat(at - months.chars().count());
+            months += &start.format("%b ").to_string();
+        }
+    }
+    let mut out = style.dim(months.trim_end()) + "\n";
+
+    for (slot, day) in week_start.weekdays().into_iter().enumerate() {
+        let mut row = format!("{} ", short_weekday(day));
+        for column in 0..weeks {
+            let date = week_of(column) + Duration::days(slot as i64);
+            if date > end {
+                break;
+            }
+            let level = level(daily.get(&date).copied().unwrap_or_default(), max);
+            row += &style.heat(level, &SHADES[level].to_string());
+            if cell_width == 2 {
+                row.push(' ');
+            }
+        }
+        out += row.trim_end();
+        out.push('\n');
+    }
+
+    let total = daily
+        .iter()
+        .filter(|(date, _)| in_range(date))
+        .fold(Duration::zero(), |acc, (_, d)| acc + *d);
+    out + &legend(style)
+        + &format!(
+            "    {} since {}\n",
+            fmt_duration(total),
+            first_week.format("%d %b %Y")
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn plain(width: usize) -> ChartStyle {
+        ChartStyle {
+            width,
+            color: false,
+            project_colors: HashMap::new(),
+        }
+    }
+
+    /// A frame on 2026-01-`day` from `start_h` to `end_h` local time.
+    fn on_day(project: &str, day: u32, start_h: u32, end_h: u32) -> Frame {
+        let at = |h| {
+            Local
+                .with_ymd_and_hms(2026, 1, day, h, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        Frame::new(project, vec![], at(start_h), at(end_h))
+    }
+
+    #[test]
+    fn chart_kind_parses_names() {
+        assert_eq!(" Days".parse::<ChartKind>(), Ok(ChartKind::Days));
+        assert!("pie".parse::<ChartKind>().is_err());
+    }
+
+    #[test]
+    fn bar_uses_eighth_blocks() {
+        assert_eq!(bar(2.5), "██▌");
+        assert_eq!(bar(0.0), "");
+        assert_eq!(bar(0.99), "█");
+    }
+
+    #[test]
+    fn projects_chart_scales_to_the_largest_project() {
+        let frames = vec![on_day("backend", 12, 9, 12), on_day("docs", 12, 13, 14)];
+        let chart = projects_chart(&frames, &plain(60));
+        let lines: Vec<&str> = chart.lines().collect();
+        // 60 columns leave 27 for the bar.
+        assert!(lines[0].starts_with(&format!("backend  {}", "█".repeat(27))));
+        assert!(lines[0].ends_with("3h 0m 0s   75.0%"));
+        assert!(lines[1].starts_with(&format!("docs     {} ", "█".repeat(9))));
+        assert!(chart.lines().all(|l| l.chars().count() <= 60));
+    }
+
+    #[test]
+    fn days_chart_stacks_projects_with_patterns_without_color() {
+        let frames = vec![
+            on_day("backend", 12, 9, 11),
+            on_day("docs", 12, 11, 12),
+            on_day("docs", 14, 9, 10),
+        ];
+        let chart = days_chart(&frames, &plain(46));
+        let lines: Vec<&str> = chart.lines().collect();
+        // 46 columns leave 20 for the bar.
+        assert!(lines[0].starts_with(&format!("Mon 12 Jan  {}{}", "█".repeat(13), "▓".repeat(7))));
+        assert!(lines[1].starts_with("Tue 13 Jan  "));
+        assert!(lines[1].ends_with("0s"));
+        assert!(lines[2].contains(&"▓".repeat(7)));
+        assert_eq!(lines.last(), Some(&"█ backend  ▓ docs"));
+    }
+
+    #[test]
+    fn weekdays_chart_spreads_frames_over_hours() {
+        let mut grid = [[Duration::zero(); 24]; 7];
+        let at = |h, m| {
+            NaiveDate::from_ymd_opt(2026, 1, 12)
+                .unwrap()
+                .and_hms_opt(h, m, 0)
+                .unwrap()
+        };
+        spread_by_hour(&mut grid, at(9, 30), at(11, 15), WeekStart::Monday);
+        assert_eq!(grid[0][9], Duration::minutes(30));
+        assert_eq!(grid[0][10], Duration::minutes(60));
+        assert_eq!(grid[0][11], Duration::minutes(15));
+        assert_eq!(grid[1][9], Duration::zero());
+
+        let chart = weekdays_chart(&[on_day("a", 13, 9, 10)], &plain(80), WeekStart::Monday);
+        let tuesday = chart.lines().nth(2).unwrap();
+        assert!(tuesday.starts_with(&format!("Tue {}██{}", "··".repeat(9), "··")));
+        assert!(tuesday.ends_with("1h 0m 0s"));
+    }
+
+    #[test]
+    fn calendar_fits_the_width_and_stops_at_the_end_date() {
+        let frames = vec![on_day("a", 12, 9, 13), on_day("a", 14, 9, 10)];
+        let end = NaiveDate::from_ymd_opt(2026, 1, 14).unwrap();
+        let chart = calendar_chart(&frames, &plain(40), WeekStart::Monday, end);
+        assert!(chart.lines().all(|l| l.chars().count() <= 40));
+        let lines: Vec<&str> = chart.lines().collect();
+        // 36 one-character weeks; the last column is the week of 12 January.
+        assert!(lines[1].starts_with("Mon "));
+        assert!(lines[1].ends_with('█'));
+        assert!(lines[3].ends_with('░'));
+        assert!(lines[4].ends_with('·'));
+        assert_eq!(lines[4].chars().count(), 4 + 35);
+        assert!(chart.contains("5h 0m 0s since"));
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use owo_colors::OwoColorize;
+use rs_watson::config::{Config, ConfigError};
+use rs_watson::{ConfigFile, DirectoryContext, Watson};
+use rs_watson_storage::Storage;
+
+use super::{ConfigAction, w_err};
+
+pub(super) fn cmd_config<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    action: ConfigAction,
+    config: &Config,
+) -> Result<()> {
+    match action {
+        ConfigAction::Show => {
+            let path = Config::path().map_err(w_err)?;
+            let effective = config.effective().map_err(w_err)?;
+            println!("{}", format!("# {}", path.display()).bright_black());
+            if let Some(context) = &config.context {
+                let line = format!("# layered with {}", context.path.display());
+                println!("{}", line.bright_black());
+            }
+            print!("{}", toml::to_string(&effective)?);
+            Ok(())
+        }
+        ConfigAction::Get { key } => {
+            let value = config
+                .get(&key)
+                .map_err(w_err)?
+                .ok_or_else(|| anyhow::anyhow!("\"{key}\" is not set"))?;
+            match value {
+                toml::Value::String(s) => println!("{s}"),
+                toml::Value::Table(table) => print!("{}", toml::to_string(&table)?),
+                other => println!("{other}"),
+            }
+            Ok(())
+        }
+        ConfigAction::Set { key, value } => cmd_set(&key, &value),
+        ConfigAction::Unset { key } => cmd_unset(&key),
+        ConfigAction::Validate => cmd_validate(watson, config.context.as_ref()),
+    }
+}
+
+/// `watson config` when `config.toml` does not load: edits still work so the
+/// file can be repaired, `validate` reports the error, the rest fail with it.
+pub(crate) fn cmd_config_unloaded(action: ConfigAction, error: ConfigError) -> Result<()> {
+    match action {
+        ConfigAction::Set { key, value } => cmd_set(&key, &value),
+        ConfigAction::Unset { key } => cmd_unset(&key),
+        ConfigAction::Validate => {
+            println!("{} {error}", "✗".red().bold());
+            anyhow::bail!("Config file is invalid.");
+        }
+        _ => Err(error.into()),
+    }
+}
+
+fn cmd_set(key: &str, value: &str) -> Result<()> {
+    let mut file = ConfigFile::open().map_err(w_err)?;
+    file.set(key, value).map_err(w_err)?;
+    file.save().map_err(w_err)?;
+    let value = file
+        .config()
+        .map_err(w_err)?
+        .get(key)
+        .map_err(w_err)?
+        .map(|v| v.to_string())
+        .unwrap_or_default();
+    println!("{}  {key} = {}", "Set  ".green().bold(), value.cyan());
+    Ok(())
+}
+
+fn cmd_unset(key: &str) -> Result<()> {
+    let mut file = ConfigFile::open().map_err(w_err)?;
+    if file.unset(key).map_err(w_err)? {
+        file.save().map_err(w_err)?;
+        println!("{}  {key}", "Unset".yellow().bold());
+    } else {
+        println!("{}", format!("\"{key}\" was not set.").bright_black());
+    }
+    Ok(())
+}
+
+/// Reports errors, then warnings for settings that load but do nothing useful.
+fn cmd_validate<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    context: Option<&DirectoryContext>,
+) -> Result<()> {
+    let file = ConfigFile::open().map_err(w_err)?;
+    println!("{}", file.path().display().to_string().bright_black());
+    let config = match file.config() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{} {e}", "✗".red().bold());
+            anyhow::bail!("Config file is invalid.");
+        }
+    };
+
+    let mut warnings: Vec<String> = file
+        .unknown_keys()
+        .into_iter()
+        .map(|key| format!("Unknown key \"{key}\" is ignored"))
+        .collect();
+    for epic in &config.epics {
+        for name in watson.unknown_epic_projects(epic).map_err(w_err)? {
+            warnings.push(format!("Epic \"{}\": {name}", epic.name));
+        }
+    }
+    if let Some(context) = context {
+        println!("{}", context.path.display().to_string().bright_black());
+        let local = ConfigFile::open_at(context.path.clone()).map_err(w_err)?;
+        for key in local.unknown_keys() {
+            if !DirectoryContext::is_context_key(&key) {
+                warnings.push(format!("Unknown key \"{key}\" in .watson.toml is ignored"));
+            }
+        }
+        if let Err(e) = context.start_defaults(&config.epics) {
+            warnings.push(format!(".watson.toml: {e}"));
+        }
+    }
+
+    if warnings.is_empty() {
+        println!("{} Config is valid.", "✓".green().bold());
+    }
+    for warning in &warnings {
+        println!("{} {}", "⚠".yellow().bold(), warning.yellow());
+    }
+    Ok(())
+}
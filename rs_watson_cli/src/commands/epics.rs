@@ -0,0 +1,133 @@
+use std::io::IsTerminal;
+
+use anyhow::Result;
+use dialoguer::{Confirm, theme::ColorfulTheme};
+use owo_colors::OwoColorize;
+use rs_watson::config::{ConfigError, EpicConfig};
+use rs_watson::{ConfigFile, Watson};
+use rs_watson_storage::Storage;
+
+use super::meta::epic_rules;
+use super::{EpicAction, EpicRuleArgs, w_err};
+
+impl EpicRuleArgs {
+    /// Overwrites the rules of `epic` that were given on the command line. An
+    /// empty value clears the rule.
+    fn apply(self, epic: &mut EpicConfig) {
+        if !self.projects.is_empty() {
+            let mut projects = non_empty(self.projects);
+            (epic.project, epic.projects) = if projects.len() == 1 {
+                (projects.pop(), Vec::new())
+            } else {
+                (None, projects)
+            };
+        }
+        if let Some(glob) = self.glob {
+            epic.project_glob = (!glob.is_empty()).then_some(glob);
+        }
+        if let Some(regex) = self.regex {
+            epic.project_regex = (!regex.is_empty()).then_some(regex);
+        }
+        if !self.tags.is_empty() {
+            epic.tags = non_empty(self.tags);
+        }
+        if !self.exclude_tags.is_empty() {
+            epic.exclude_tags = non_empty(self.exclude_tags);
+        }
+        if let Some(priority) = self.priority {
+            epic.priority = priority;
+        }
+    }
+}
+
+fn non_empty(values: Vec<String>) -> Vec<String> {
+    values.into_iter().filter(|v| !v.is_empty()).collect()
+}
+
+pub(super) fn cmd_epic_action<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    action: EpicAction,
+) -> Result<()> {
+    let mut file = ConfigFile::open().map_err(w_err)?;
+    match action {
+        EpicAction::Add { name, rules, force } => {
+            let mut epic = EpicConfig {
+                name,
+                ..Default::default()
+            };
+            rules.apply(&mut epic);
+            check_projects(watson, &epic, force)?;
+            file.add_epic(&epic).map_err(w_err)?;
+            file.save().map_err(w_err)?;
+            print_epic("Added epic  ".green().bold(), &epic);
+        }
+        EpicAction::Edit {
+            name,
+            rename,
+            rules,
+            force,
+        } => {
+            let config = file.config().map_err(w_err)?;
+            let mut epic = config
+                .epics
+                .into_iter()
+                .find(|e| e.name == name)
+                .ok_or_else(|| w_err(ConfigError::UnknownEpic(name.clone())))?;
+            if let Some(rename) = rename {
+                epic.name = rename;
+            }
+            rules.apply(&mut epic);
+            check_projects(watson, &epic, force)?;
+            file.update_epic(&name, &epic).map_err(w_err)?;
+            file.save().map_err(w_err)?;
+            print_epic("Updated epic".yellow().bold(), &epic);
+        }
+        EpicAction::Remove { name } => {
+            let epic = file.remove_epic(&name).map_err(w_err)?;
+            file.save().map_err(w_err)?;
+            println!(
+                "{}  {}",
+                "Removed epic".red().bold(),
+                epic.name.cyan().bold()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Refuses projects the epic could never match because no frame or registry
+/// entry uses them, unless `force` is set or the user confirms on a terminal.
+fn check_projects<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    epic: &EpicConfig,
+    force: bool,
+) -> Result<()> {
+    if force {
+        return Ok(());
+    }
+    for name in watson.unknown_epic_projects(epic).map_err(w_err)? {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!("{name}. Pass --force to use it anyway.");
+        }
+        println!(
+            "{} {}",
+            "Warning:".yellow().bold(),
+            name.to_string().yellow()
+        );
+        let keep = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Use project \"{}\" anyway?", name.name))
+            .default(false)
+            .interact()?;
+        if !keep {
+            return Err(w_err(name));
+        }
+    }
+    Ok(())
+}
+
+fn print_epic(header: impl std::fmt::Display, epic: &EpicConfig) {
+    println!("{header}  {}", epic.name.cyan().bold());
+    for (label, value) in epic_rules(epic) {
+        println!("  {:<9}{value}", label.bright_black());
+    }
+}
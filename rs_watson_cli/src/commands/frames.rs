@@ -1,56 +1,37 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use chrono::Local;
-use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
+use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
 use owo_colors::OwoColorize;
-use rs_watson::Watson;
+use rs_watson::git::{self, Commit};
+use rs_watson::report::{Dimension, PivotContext, pivot};
+use rs_watson::stats::StatsContext;
+use rs_watson::{Frame, FrameFilter, Stats, Watson};
 use rs_watson_storage::Storage;
+use uuid::Uuid;
 
+use crate::chart::{self, ChartKind, ChartStyle};
 use crate::epic::print_epic_report;
 use crate::format::{
-    fmt_duration, fmt_tags, fmt_time, print_frame_summary, print_frames_grouped,
-    print_report_grouped,
+    fmt_duration, fmt_tags, fmt_time, print_frame_summary, print_frames_grouped, print_pivot,
+    print_report_grouped, print_search_results, print_stats,
 };
-use crate::time_utils::{check_future, parse_at, prompt_time};
+use crate::time_utils::{check_future, parse_at, parse_at_on, prompt_time};
 use rs_watson::config::Config;
 
-use super::{apply_date_filter, apply_project_tag_filter, w_err};
+use super::{confirm_new_names, load_with_running, w_err};
 
-#[allow(clippy::too_many_arguments)]
 pub(super) fn cmd_log<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
-    from: Option<String>,
-    to: Option<String>,
-    project: Option<String>,
-    tags: Vec<String>,
+    filter: &FrameFilter,
     limit: Option<usize>,
     offset: Option<usize>,
+    commits: bool,
     config: &Config,
 ) -> Result<()> {
-    use chrono::Utc;
-
-    let mut frames = apply_date_filter(
-        watson.log().map_err(w_err)?,
-        from.clone(),
-        to.clone(),
-        config.behavior.week_start,
-    )?;
-
-    // Append the active frame (snapped to now) if it falls in the date range.
-    if let Some(active) = watson.status().map_err(w_err)? {
-        let now = Utc::now();
-        let virtual_frame = active.stop(now);
-        let in_range = apply_date_filter(
-            vec![virtual_frame.clone()],
-            from,
-            to,
-            config.behavior.week_start,
-        )?;
-        if !in_range.is_empty() {
-            frames.push(virtual_frame);
-        }
-    }
-
-    let mut frames = apply_project_tag_filter(frames, project.as_deref(), &tags);
+    let (mut frames, running) = load_with_running(watson, true)?;
+    frames.retain(|f| filter.matches(f));
 
     let effective_limit =
         limit.or_else(|| (config.log.default_limit > 0).then_some(config.log.default_limit));
@@ -61,95 +42,177 @@ pub(super) fn cmd_log<S: Storage<Error: std::error::Error + Send + Sync + 'stati
     if frames.is_empty() {
         println!("{}", "No frames recorded.".bright_black());
     } else {
-        print_frames_grouped(&frames);
+        let commits = if commits {
+            frame_commits(&frames)?
+        } else {
+            HashMap::new()
+        };
+        print_frames_grouped(&frames, running, &commits);
+    }
+    Ok(())
+}
+
+/// Commits in the current directory's repository, grouped by the frame they
+/// were made in.
+fn frame_commits(frames: &[Frame]) -> Result<HashMap<Uuid, Vec<Commit>>> {
+    let (Some(from), Some(to)) = (
+        frames.iter().map(|f| f.start).min(),
+        frames.iter().map(|f| f.end).max(),
+    ) else {
+        return Ok(HashMap::new());
+    };
+    let commits = git::commits(&std::env::current_dir()?, from, to)?;
+    Ok(git::commits_by_frame(frames, &commits))
+}
+
+pub(super) fn cmd_search<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    text: &str,
+    filter: &FrameFilter,
+    limit: usize,
+) -> Result<()> {
+    let mut frames = watson.search(text).map_err(w_err)?;
+    frames.retain(|f| filter.matches(f));
+    frames.truncate(limit);
+    if frames.is_empty() {
+        println!("{}", "No frames match.".bright_black());
+    } else {
+        print_search_results(&frames);
     }
     Ok(())
 }
 
 pub(super) fn cmd_today<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
-    project: Option<String>,
-    tags: Vec<String>,
+    filter: &FrameFilter,
     epic: bool,
+    include_running: bool,
     config: &Config,
 ) -> Result<()> {
-    use chrono::Utc;
-
-    let now = Utc::now();
-    let today = now.with_timezone(&Local).date_naive();
-    let mut frames: Vec<_> = watson
-        .log()
-        .map_err(w_err)?
+    let today = Local::now().date_naive();
+    let (frames, running) = load_with_running(watson, include_running)?;
+    let frames: Vec<_> = frames
         .into_iter()
         .filter(|f| f.start.with_timezone(&Local).date_naive() == today)
+        .filter(|f| filter.matches(f))
         .collect();
 
-    // Include the active frame if it started today.
-    if let Some(active) = watson.status().map_err(w_err)?
-        && active.start.with_timezone(&Local).date_naive() == today
-    {
-        frames.push(active.stop(now));
-    }
-
-    let frames = apply_project_tag_filter(frames, project.as_deref(), &tags);
-
     if frames.is_empty() {
         println!("{}", "No frames recorded today.".bright_black());
     } else if epic {
         if config.epics.is_empty() {
             anyhow::bail!("No epics configured. Add [[epics]] entries to config.toml.");
         }
-        print_epic_report(&frames, &config.epics, false);
+        print_epic_report(
+            &frames,
+            &config.epics,
+            false,
+            running,
+            config.behavior.project_separator.as_deref(),
+        );
     } else {
-        print_report_grouped(&frames, false);
+        print_report_grouped(
+            &frames,
+            false,
+            running,
+            config.behavior.project_separator.as_deref(),
+        );
     }
     Ok(())
 }
 
 pub(super) fn cmd_report<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
-    from: Option<String>,
-    to: Option<String>,
-    project: Option<String>,
-    tags: Vec<String>,
+    filter: &FrameFilter,
     epic: bool,
+    group_by: &[Dimension],
+    include_running: bool,
     config: &Config,
 ) -> Result<()> {
-    use chrono::Utc;
-
-    let mut frames = apply_date_filter(
-        watson.log().map_err(w_err)?,
-        from.clone(),
-        to.clone(),
-        config.behavior.week_start,
-    )?;
-
-    // Include the active frame if it falls in the date range.
-    if let Some(active) = watson.status().map_err(w_err)? {
-        let now = Utc::now();
-        let virtual_frame = active.stop(now);
-        let in_range = apply_date_filter(
-            vec![virtual_frame.clone()],
-            from,
-            to,
-            config.behavior.week_start,
-        )?;
-        if !in_range.is_empty() {
-            frames.push(virtual_frame);
-        }
-    }
-
-    let frames = apply_project_tag_filter(frames, project.as_deref(), &tags);
+    let (mut frames, running) = load_with_running(watson, include_running)?;
+    frames.retain(|f| filter.matches(f));
 
     if frames.is_empty() {
         println!("{}", "No frames recorded.".bright_black());
+    } else if !group_by.is_empty() {
+        if group_by.contains(&Dimension::Epic) && config.epics.is_empty() {
+            anyhow::bail!("No epics configured. Add [[epics]] entries to config.toml.");
+        }
+        let ctx = PivotContext {
+            epics: &config.epics,
+            week_start: config.behavior.week_start,
+            running,
+        };
+        print_pivot(&frames, &pivot(&frames, group_by, &ctx));
     } else if epic {
         if config.epics.is_empty() {
             anyhow::bail!("No epics configured. Add [[epics]] entries to config.toml.");
         }
-        print_epic_report(&frames, &config.epics, true);
+        print_epic_report(
+            &frames,
+            &config.epics,
+            true,
+            running,
+            config.behavior.project_separator.as_deref(),
+        );
     } else {
-        print_report_grouped(&frames, true);
+        print_report_grouped(
+            &frames,
+            true,
+            running,
+            config.behavior.project_separator.as_deref(),
+        );
+    }
+    Ok(())
+}
+
+pub(super) fn cmd_chart<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    filter: &FrameFilter,
+    kind: ChartKind,
+    include_running: bool,
+    config: &Config,
+) -> Result<()> {
+    let (mut frames, _) = load_with_running(watson, include_running)?;
+    frames.retain(|f| filter.matches(f));
+    if frames.is_empty() {
+        println!("{}", "No frames recorded.".bright_black());
+        return Ok(());
+    }
+    let colors = watson
+        .project_list()
+        .map_err(w_err)?
+        .into_iter()
+        .filter_map(|p| Some((p.rgb()?, p.name)))
+        .map(|(rgb, name)| (name, rgb))
+        .collect();
+    let end = filter.to.unwrap_or_else(|| Local::now().date_naive());
+    print!(
+        "{}",
+        chart::render(
+            kind,
+            &frames,
+            &ChartStyle::detect(colors),
+            config.behavior.week_start,
+            end,
+        )
+    );
+    Ok(())
+}
+
+pub(super) fn cmd_stats<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    filter: &FrameFilter,
+    weeks: usize,
+    config: &Config,
+) -> Result<()> {
+    let mut frames = watson.log().map_err(w_err)?;
+    frames.retain(|f| filter.matches(f));
+    if frames.is_empty() {
+        println!("{}", "No frames recorded.".bright_black());
+    } else {
+        let ctx = StatsContext::new(config.behavior.week_start, weeks);
+        print_stats(&Stats::from_frames(&frames, &ctx));
     }
     Ok(())
 }
@@ -166,6 +229,10 @@ pub(super) fn cmd_add<S: Storage<Error: std::error::Error + Send + Sync + 'stati
     let end = parse_at(&to)?;
     check_future(start, config)?;
     check_future(end, config)?;
+    confirm_new_names(
+        watson,
+        watson.unknown_names(&project, &tags).map_err(w_err)?,
+    )?;
     let frame = watson.add(&project, tags, start, end).map_err(w_err)?;
     print_frame_summary("Added   ".green().bold(), &frame);
     Ok(())
@@ -182,19 +249,7 @@ pub(super) fn cmd_edit<S: Storage<Error: std::error::Error + Send + Sync + 'stat
     }
     frames.reverse();
 
-    let frame = if let Some(short) = id {
-        find_by_short_id(&frames, &short)?.clone()
-    } else {
-        let recent = recent_frames(&frames);
-        let items = frame_selector_items(recent);
-        let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select frame to edit")
-            .items(&items)
-            .default(0)
-            .interact()?;
-        recent[selection].clone()
-    };
-    let frame = &frame;
+    let frame = &select_frame(&frames, id, "Select frame to edit")?;
     println!();
 
     let new_project: String = Input::with_theme(&ColorfulTheme::default())
@@ -220,6 +275,12 @@ pub(super) fn cmd_edit<S: Storage<Error: std::error::Error + Send + Sync + 'stat
         anyhow::bail!("End time must be after start time");
     }
 
+    confirm_new_names(
+        watson,
+        watson
+            .unknown_names(&new_project, &new_tags)
+            .map_err(w_err)?,
+    )?;
     let updated = watson
         .edit(frame.id, new_project, new_tags, new_start, new_end)
         .map_err(w_err)?;
@@ -240,19 +301,7 @@ pub(super) fn cmd_remove<S: Storage<Error: std::error::Error + Send + Sync + 'st
     }
     frames.reverse();
 
-    let frame = if let Some(short) = id {
-        find_by_short_id(&frames, &short)?.clone()
-    } else {
-        let recent = recent_frames(&frames);
-        let items = frame_selector_items(recent);
-        let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("Select frame to remove")
-            .items(&items)
-            .default(0)
-            .interact()?;
-        recent[selection].clone()
-    };
-    let frame = &frame;
+    let frame = &select_frame(&frames, id, "Select frame to remove")?;
     let confirmed = Confirm::with_theme(&ColorfulTheme::default())
         .with_prompt(format!(
             "Remove \"{}\" ({} → {})?",
@@ -278,11 +327,95 @@ pub(super) fn cmd_remove<S: Storage<Error: std::error::Error + Send + Sync + 'st
     Ok(())
 }
 
+pub(super) fn cmd_split<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    id: Option<String>,
+    at: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+    config: &Config,
+) -> Result<()> {
+    let mut frames = watson.log().map_err(w_err)?;
+    if frames.is_empty() {
+        println!("{}", "No frames to split.".bright_black());
+        return Ok(());
+    }
+    frames.reverse();
+
+    let frame = &select_frame(&frames, id, "Select frame to split")?;
+    let at = match at {
+        Some(s) => parse_at_on(&s, frame.start)?,
+        None => {
+            let midpoint = frame.start + (frame.end - frame.start) / 2;
+            prompt_time("Split at  (HH:MM or YYYY-MM-DD HH:MM)", midpoint)?
+        }
+    };
+    check_future(at, config)?;
+
+    let new_project = project.unwrap_or_else(|| frame.project.clone());
+    let new_tags = if tags.is_empty() {
+        frame.tags.clone()
+    } else {
+        tags
+    };
+    confirm_new_names(
+        watson,
+        watson
+            .unknown_names(&new_project, &new_tags)
+            .map_err(w_err)?,
+    )?;
+    let (first, second) = watson
+        .split(frame.id, at, new_project, new_tags)
+        .map_err(w_err)?;
+
+    print_frame_summary("Split   ".green().bold(), &first);
+    println!();
+    print_frame_summary("        ", &second);
+    Ok(())
+}
+
+pub(super) fn cmd_merge<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    ids: Vec<String>,
+) -> Result<()> {
+    let mut frames = watson.log().map_err(w_err)?;
+    if frames.len() < 2 {
+        println!("{}", "Not enough frames to merge.".bright_black());
+        return Ok(());
+    }
+    frames.reverse();
+
+    let selected: Vec<rs_watson::Frame> = if ids.is_empty() {
+        let recent = recent_frames(&frames);
+        let items = frame_selector_items(recent);
+        let selection = MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select frames to merge  (space to toggle)")
+            .items(&items)
+            .interact()?;
+        selection.into_iter().map(|i| recent[i].clone()).collect()
+    } else {
+        ids.iter()
+            .map(|short| find_by_short_id(&frames, short).cloned())
+            .collect::<Result<_>>()?
+    };
+    if selected.len() < 2 {
+        anyhow::bail!("Select at least two frames to merge");
+    }
+
+    let ids: Vec<_> = selected.iter().map(|f| f.id).collect();
+    let merged = watson.merge(&ids).map_err(w_err)?;
+    print_frame_summary(
+        format!("{} {}", "Merged".green().bold(), ids.len()),
+        &merged,
+    );
+    Ok(())
+}
+
 /// How many frames the interactive selector shows (most recent first).
 const SELECTOR_LIMIT: usize = 25;
 
 /// Returns a short 8-character ID prefix for display.
-fn short_id(frame: &rs_watson::Frame) -> String {
+pub(crate) fn short_id(frame: &rs_watson::Frame) -> String {
     frame.id.to_string().replace('-', "")[..8].to_string()
 }
 
@@ -292,7 +425,7 @@ fn recent_frames(frames: &[rs_watson::Frame]) -> &[rs_watson::Frame] {
 }
 
 /// Finds a frame by short ID prefix (case-insensitive). Errors if 0 or >1 match.
-fn find_by_short_id<'a>(
+pub(super) fn find_by_short_id<'a>(
     frames: &'a [rs_watson::Frame],
     prefix: &str,
 ) -> Result<&'a rs_watson::Frame> {
@@ -312,7 +445,27 @@ fn find_by_short_id<'a>(
     }
 }
 
-/// Builds the display strings for the interactive frame selector used by edit and remove.
+/// Resolves the frame to act on: by short ID when given, otherwise via the
+/// interactive selector. `frames` must be in newest-first order.
+fn select_frame(
+    frames: &[rs_watson::Frame],
+    id: Option<String>,
+    prompt: &str,
+) -> Result<rs_watson::Frame> {
+    if let Some(short) = id {
+        return Ok(find_by_short_id(frames, &short)?.clone());
+    }
+    let recent = recent_frames(frames);
+    let items = frame_selector_items(recent);
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact()?;
+    Ok(recent[selection].clone())
+}
+
+/// Builds the display strings for the interactive frame selectors.
 fn frame_selector_items(frames: &[rs_watson::Frame]) -> Vec<String> {
     frames
         .iter()
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, Utc};
+use dialoguer::{Input, Select, theme::ColorfulTheme};
+use owo_colors::OwoColorize;
+use rs_watson::Watson;
+use rs_watson::config::Config;
+use rs_watson::gaps::Gap;
+use rs_watson_storage::Storage;
+
+use crate::format::{fmt_duration, fmt_time, print_frame_summary};
+use crate::time_utils::{check_future, parse_date, prompt_time};
+
+use super::{confirm_new_names, w_err};
+
+pub(super) fn cmd_gaps<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    from: Option<String>,
+    to: Option<String>,
+    fill: bool,
+    config: &Config,
+) -> Result<()> {
+    let today = Local::now().date_naive();
+    let week_start = config.behavior.week_start;
+    let from = from
+        .map(|s| parse_date(&s, week_start))
+        .transpose()?
+        .unwrap_or(today);
+    let to = to
+        .map(|s| parse_date(&s, week_start))
+        .transpose()?
+        .unwrap_or(today);
+
+    loop {
+        let gaps = watson
+            .gaps(from, to, &config.working_hours, &Local, Utc::now())
+            .map_err(w_err)?;
+        if gaps.is_empty() {
+            println!(
+                "{}",
+                "No untracked time within working hours.".bright_black()
+            );
+            return Ok(());
+        }
+        if !fill {
+            print_gaps(&gaps);
+            return Ok(());
+        }
+
+        let mut items: Vec<String> = gaps.iter().map(gap_selector_item).collect();
+        items.push("Done".to_string());
+        let selection = Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Select gap to fill")
+            .items(&items)
+            .default(0)
+            .interact()?;
+        if selection == gaps.len() {
+            return Ok(());
+        }
+        println!();
+        if let Err(e) = fill_gap(watson, &gaps[selection], config) {
+            eprintln!("{} {e}", "Error:".red().bold());
+        }
+        println!();
+    }
+}
+
+/// Prompts for a frame covering `gap`, pre-filled with the neighbouring frame's
+/// project and tags, and adds it.
+fn fill_gap<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    gap: &Gap,
+    config: &Config,
+) -> Result<()> {
+    let mut frames = watson.log().map_err(w_err)?;
+    frames.extend(watson.running(Utc::now()).map_err(w_err)?);
+    let neighbor = gap.neighbor(&frames);
+
+    let project: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Project")
+        .with_initial_text(neighbor.map(|f| f.project.as_str()).unwrap_or_default())
+        .interact_text()?;
+    let tags_input: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Tags  (comma-separated, empty for none)")
+        .with_initial_text(neighbor.map(|f| f.tags.join(", ")).unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+    let tags: Vec<String> = tags_input
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let start = prompt_time("Start  (HH:MM or YYYY-MM-DD HH:MM)", gap.start)?;
+    let end = prompt_time("End    (HH:MM or YYYY-MM-DD HH:MM)", gap.end)?;
+    check_future(start, config)?;
+    check_future(end, config)?;
+
+    confirm_new_names(
+        watson,
+        watson.unknown_names(&project, &tags).map_err(w_err)?,
+    )?;
+    let frame = watson.add(&project, tags, start, end).map_err(w_err)?;
+    println!();
+    print_frame_summary("Added   ".green().bold(), &frame);
+    Ok(())
+}
+
+fn gap_selector_item(gap: &Gap) -> String {
+    format!(
+        "{}  {}  →  {}   {}",
+        gap.start.with_timezone(&Local).format("%a %d %b"),
+        fmt_time(gap.start),
+        fmt_time(gap.end),
+        fmt_duration(gap.duration()),
+    )
+}
+
+fn print_gaps(gaps: &[Gap]) {
+    let mut by_day: BTreeMap<NaiveDate, Vec<&Gap>> = BTreeMap::new();
+    for gap in gaps {
+        by_day
+            .entry(gap.start.with_timezone(&Local).date_naive())
+            .or_default()
+            .push(gap);
+    }
+
+    for (date, day_gaps) in &by_day {
+        let total = day_gaps
+            .iter()
+            .fold(Duration::zero(), |acc, g| acc + g.duration());
+        println!(
+            "{}  {}",
+            date.format("%A, %d %B %Y").to_string().bold().white(),
+            format!("({} untracked)", fmt_duration(total)).bright_black(),
+        );
+        for gap in day_gaps {
+            println!(
+                "  {}  {}  {}   {}",
+                fmt_time(gap.start).bright_white(),
+                "→".white(),
+                fmt_time(gap.end).bright_white(),
+                fmt_duration(gap.duration()).red().bold(),
+            );
+        }
+        println!();
+    }
+
+    if by_day.len() > 1 {
+        let total = gaps
+            .iter()
+            .fold(Duration::zero(), |acc, g| acc + g.duration());
+        println!(
+            "{}  {}",
+            "Total untracked".bold(),
+            fmt_duration(total).red().bold()
+        );
+    }
+}
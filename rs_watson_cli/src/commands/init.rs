@@ -4,14 +4,16 @@ use owo_colors::OwoColorize;
 
 use rs_watson::config::{
     BehaviorConfig, Config, LogConfig, StorageConfig, StorageProvider, WeekStart,
+    WorkingHoursConfig,
 };
 
 #[allow(clippy::vec_init_then_push)] // cfg-gated pushes require this pattern
 pub(crate) fn cmd_init() -> Result<()> {
-    let config_dir = dirs::config_dir()
+    let config_path = Config::path()?;
+    let config_dir = config_path
+        .parent()
         .context("Could not determine config directory")?
-        .join("rs_watson");
-    let config_path = config_dir.join("config.toml");
+        .to_path_buf();
 
     if config_path.exists() {
         let overwrite = Confirm::with_theme(&ColorfulTheme::default())
@@ -60,6 +62,12 @@ pub(crate) fn cmd_init() -> Result<()> {
         _ => WeekStart::Monday,
     };
 
+    let max_frame_hours_str: String = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt("Warn about frames running longer than N hours  (0 = off)")
+        .with_initial_text("0")
+        .interact_text()?;
+    let max_frame_hours = max_frame_hours_str.trim().parse::<u32>().unwrap_or(0);
+
     println!();
 
     // [log]
@@ -70,6 +78,7 @@ pub(crate) fn cmd_init() -> Result<()> {
     let default_limit = default_limit_str.trim().parse::<usize>().unwrap_or(0);
 
     let config = Config {
+        version: Config::CURRENT_VERSION,
         storage: StorageConfig {
             provider,
             data_dir: None,
@@ -77,9 +86,21 @@ pub(crate) fn cmd_init() -> Result<()> {
         behavior: BehaviorConfig {
             allow_future_times,
             week_start,
+            max_frame_hours,
+            ignore_case: false,
+            strict_projects: false,
+            strict_tags: false,
+            allowed_projects: vec![],
+            allowed_tags: vec![],
+            project_separator: None,
         },
         log: LogConfig { default_limit },
+        working_hours: WorkingHoursConfig::default(),
+        aliases: Default::default(),
+        git: Default::default(),
+        hooks: Default::default(),
         epics: vec![],
+        context: None,
     };
 
     std::fs::create_dir_all(&config_dir).with_context(|| {
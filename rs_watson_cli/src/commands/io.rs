@@ -1,21 +1,21 @@
 use std::fs;
-use std::io;
+use std::io::{self, IsTerminal, Write};
 
 use anyhow::{Context, Result};
 use chrono::TimeZone;
+use dialoguer::{Confirm, theme::ColorfulTheme};
 use owo_colors::OwoColorize;
-use rs_watson::Watson;
+use rs_watson::config::ConfigError;
+use rs_watson::strict::UnknownName;
+use rs_watson::{EpicResolver, Frame, FrameFilter, Watson};
+use rs_watson_export::csv::CsvExporter;
+use rs_watson_export::{ExportOptions, Registry};
 use rs_watson_storage::Storage;
 
 use crate::format::{fmt_tags, fmt_time};
 use rs_watson::config::Config;
 
-use super::{apply_date_filter, w_err};
-
-#[derive(clap::ValueEnum, Clone, Copy)]
-pub(crate) enum ExportFormat {
-    Csv,
-}
+use super::w_err;
 
 #[derive(clap::ValueEnum, Clone, Copy)]
 pub(crate) enum ImportSource {
@@ -24,46 +24,84 @@ pub(crate) enum ImportSource {
 
 pub(super) fn cmd_export<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
-    format: ExportFormat,
+    format: &str,
+    options: &[String],
     output: Option<String>,
-    from: Option<String>,
-    to: Option<String>,
+    filter: &FrameFilter,
+    epic: Option<String>,
     config: &Config,
 ) -> Result<()> {
-    use rs_watson_export::Exporter;
-    use rs_watson_export::csv::CsvExporter;
-
-    let frames = apply_date_filter(
-        watson.log().map_err(w_err)?,
-        from,
-        to,
-        config.behavior.week_start,
-    )?;
+    let mut registry = Registry::discover();
+    registry.replace(Box::new(
+        CsvExporter::default().with_epics(config.epics.clone()),
+    ));
+    let Some(exporter) = registry.get(format) else {
+        let available: Vec<&str> = registry.iter().map(|e| e.name()).collect();
+        anyhow::bail!(
+            "Unknown export format \"{format}\" (available: {})",
+            available.join(", ")
+        );
+    };
+    let options = ExportOptions::parse(options).map_err(w_err)?;
+
+    if let Some(name) = &epic
+        && !config.epics.iter().any(|e| e.name == *name)
+    {
+        return Err(w_err(ConfigError::UnknownEpic(name.clone())));
+    }
+    let resolver = EpicResolver::new(&config.epics);
+    let frames: Vec<Frame> = watson
+        .log()
+        .map_err(w_err)?
+        .into_iter()
+        .filter(|f| filter.matches(f))
+        .filter(|f| {
+            epic.as_deref()
+                .is_none_or(|name| resolver.resolve(f).is_some_and(|e| e.name == name))
+        })
+        .collect();
     if frames.is_empty() {
         println!("{}", "No frames to export.".bright_black());
         return Ok(());
     }
 
-    match format {
-        ExportFormat::Csv => match output {
-            Some(path) => {
-                let file = fs::File::create(&path)
-                    .with_context(|| format!("Could not create file: {path}"))?;
-                CsvExporter.export(&frames, file).context("Export failed")?;
-                println!(
-                    "{} {} {} {}",
-                    "Exported".green().bold(),
-                    frames.len().to_string().yellow().bold(),
-                    "frames to".bright_black(),
-                    path.bright_white(),
-                );
-            }
-            None => {
-                CsvExporter
-                    .export(&frames, io::stdout())
-                    .context("Export failed")?;
-            }
-        },
+    // Export into memory first so a failing exporter leaves no partial file.
+    let mut buf = Vec::new();
+    exporter
+        .export(&frames, &options, &mut buf)
+        .map_err(|e| anyhow::anyhow!("Export failed: {e}"))?;
+    match output {
+        Some(path) => {
+            fs::write(&path, &buf).with_context(|| format!("Could not write file: {path}"))?;
+            println!(
+                "{} {} {} {}",
+                "Exported".green().bold(),
+                frames.len().to_string().yellow().bold(),
+                "frames to".bright_black(),
+                path.bright_white(),
+            );
+        }
+        None => io::stdout().write_all(&buf)?,
+    }
+    Ok(())
+}
+
+/// Lists the built-in and discovered export formats with their options.
+pub(super) fn cmd_export_formats() -> Result<()> {
+    for exporter in Registry::discover().iter() {
+        println!(
+            "{}  {}  {}",
+            exporter.name().yellow().bold(),
+            format!(".{}", exporter.extension()).bright_black(),
+            exporter.description(),
+        );
+        for option in exporter.options() {
+            println!(
+                "    {}  {}",
+                format!("{}={}", option.name, option.default).cyan(),
+                option.description.bright_black(),
+            );
+        }
     }
     Ok(())
 }
@@ -87,6 +125,7 @@ pub(super) fn cmd_import<S: Storage<Error: std::error::Error + Send + Sync + 'st
 
     let frames = parse_watson_frames(&content)?;
     let count = frames.len();
+    let unknown = watson.unknown_names_in(&frames).map_err(w_err)?;
 
     if dry_run {
         println!(
@@ -105,7 +144,11 @@ pub(super) fn cmd_import<S: Storage<Error: std::error::Error + Send + Sync + 'st
                 fmt_tags(&frame.tags),
             );
         }
+        print_new_names(&unknown);
     } else {
+        if !unknown.is_empty() {
+            confirm_import_names(watson, unknown)?;
+        }
         watson.import_frames(frames).map_err(w_err)?;
         println!(
             "{} {} {}",
@@ -117,6 +160,42 @@ pub(super) fn cmd_import<S: Storage<Error: std::error::Error + Send + Sync + 'st
     Ok(())
 }
 
+fn print_new_names(unknown: &[UnknownName]) {
+    for name in unknown {
+        println!(
+            "  {} {} {}",
+            "new".yellow().bold(),
+            name.kind.to_string().bright_black(),
+            name.name.bold(),
+        );
+    }
+}
+
+/// Strict mode: lists the projects and tags the import would create and asks once
+/// for all of them. Without a terminal, fails with the first unknown name.
+fn confirm_import_names<S: Storage>(watson: &Watson<S>, unknown: Vec<UnknownName>) -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Err(w_err(&unknown[0]));
+    }
+    println!(
+        "{} {}",
+        "Warning:".yellow().bold(),
+        "the import contains names not known yet:".yellow()
+    );
+    print_new_names(&unknown);
+    let create = Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!("Create {} new names?", unknown.len()))
+        .default(false)
+        .interact()?;
+    if !create {
+        anyhow::bail!("Import cancelled");
+    }
+    for name in &unknown {
+        watson.accept_name(name);
+    }
+    Ok(())
+}
+
 /// Parses the original Watson frames file format.
 /// Each frame is stored as: [start_ts, stop_ts, project, id, [tags], updated_ts]
 fn parse_watson_frames(content: &str) -> Result<Vec<rs_watson::Frame>> {
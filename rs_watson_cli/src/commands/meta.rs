@@ -1,26 +1,119 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 use owo_colors::OwoColorize;
-use rs_watson::Watson;
+use rs_watson::config::EpicConfig;
+use rs_watson::{EpicResolver, Frame, FrameFilter, Project, Watson, WatsonError};
 use rs_watson_storage::Storage;
 
+use crate::format::{fmt_tags, fmt_time};
 use rs_watson::config::Config;
 
+use super::frames::{find_by_short_id, short_id};
 use super::w_err;
 
 pub(super) fn cmd_projects<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
+    all: bool,
 ) -> Result<()> {
-    let projects = watson.projects().map_err(w_err)?;
+    let projects: Vec<Project> = watson
+        .project_list()
+        .map_err(w_err)?
+        .into_iter()
+        .filter(|p| all || !p.archived)
+        .collect();
     if projects.is_empty() {
         println!("{}", "No projects recorded yet.".bright_black());
-    } else {
-        for name in &projects {
-            println!("{}", name.yellow().bold());
+        return Ok(());
+    }
+    for project in &projects {
+        let mut line = project_name(project);
+        if let Some(client) = &project.client {
+            line.push_str(&format!("  {}", format!("[{client}]").bright_blue()));
         }
+        if let Some(description) = &project.description {
+            line.push_str(&format!("  {}", description.bright_black()));
+        }
+        if project.archived {
+            line.push_str(&format!("  {}", "(archived)".bright_black().italic()));
+        }
+        println!("{line}");
     }
     Ok(())
 }
 
+/// Requested changes for `watson project`; `None` leaves a field untouched.
+pub(super) struct ProjectUpdate {
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub client: Option<String>,
+    pub archived: Option<bool>,
+}
+
+impl ProjectUpdate {
+    fn is_empty(&self) -> bool {
+        self.description.is_none()
+            && self.color.is_none()
+            && self.client.is_none()
+            && self.archived.is_none()
+    }
+}
+
+pub(super) fn cmd_project<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    name: String,
+    update: ProjectUpdate,
+) -> Result<()> {
+    let existing = watson.project(&name).map_err(w_err)?;
+    if update.is_empty() {
+        let project =
+            existing.ok_or_else(|| w_err(WatsonError::<S::Error>::ProjectNotFound(name)))?;
+        println!("{}", project_name(&project));
+        print_project(&project);
+        return Ok(());
+    }
+
+    let mut project = existing.unwrap_or_else(|| Project::new(&name));
+    let non_empty = |s: String| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    if let Some(description) = update.description {
+        project.description = non_empty(description);
+    }
+    if let Some(color) = update.color {
+        project.color = non_empty(color).map(|c| c.to_lowercase());
+    }
+    if let Some(client) = update.client {
+        project.client = non_empty(client);
+    }
+    if let Some(archived) = update.archived {
+        project.archived = archived;
+    }
+    let project = watson.update_project(project).map_err(w_err)?;
+    println!("{} {}", "Updated".green().bold(), project_name(&project));
+    print_project(&project);
+    Ok(())
+}
+
+fn print_project(project: &Project) {
+    let field = |label: &str, value: Option<&str>| {
+        println!("  {:<12} {}", label.bright_black(), value.unwrap_or("—"));
+    };
+    field("Description", project.description.as_deref());
+    field("Client", project.client.as_deref());
+    field("Color", project.color.as_deref());
+    field(
+        "Archived",
+        Some(if project.archived { "yes" } else { "no" }),
+    );
+}
+
+/// The project name in its registry color, falling back to the usual yellow.
+fn project_name(project: &Project) -> String {
+    match project.rgb() {
+        Some((r, g, b)) => project.name.truecolor(r, g, b).bold().to_string(),
+        None => project.name.yellow().bold().to_string(),
+    }
+}
+
 pub(super) fn cmd_tags<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
 ) -> Result<()> {
@@ -45,13 +138,92 @@ pub(super) fn cmd_epics(config: &Config) -> Result<()> {
     }
     for epic in &config.epics {
         println!("{}", epic.name.cyan().bold());
-        println!("  project  {}", epic.project.yellow());
-        if epic.tags.is_empty() {
-            println!("  tags     {}", "(any)".bright_black());
+        for (label, value) in epic_rules(epic) {
+            println!("  {:<9}{value}", label.bright_black());
+        }
+    }
+    Ok(())
+}
+
+/// The configured rules of `epic` as label/value pairs, for listing.
+pub(super) fn epic_rules(epic: &EpicConfig) -> Vec<(&'static str, String)> {
+    let mut rules = Vec::new();
+    let projects: Vec<&str> = epic
+        .project
+        .iter()
+        .chain(&epic.projects)
+        .map(String::as_str)
+        .collect();
+    if !projects.is_empty() {
+        rules.push(("project", projects.join(", ").yellow().to_string()));
+    }
+    if let Some(glob) = &epic.project_glob {
+        rules.push(("glob", glob.yellow().to_string()));
+    }
+    if let Some(regex) = &epic.project_regex {
+        rules.push(("regex", regex.yellow().to_string()));
+    }
+    if rules.is_empty() {
+        rules.push(("project", "(any)".bright_black().to_string()));
+    }
+    if epic.tags.is_empty() {
+        rules.push(("tags", "(any)".bright_black().to_string()));
+    } else {
+        rules.push(("tags", epic.tags.join(", ").cyan().to_string()));
+    }
+    if !epic.exclude_tags.is_empty() {
+        rules.push(("exclude", epic.exclude_tags.join(", ").red().to_string()));
+    }
+    if epic.priority != 0 {
+        rules.push(("priority", epic.priority.to_string()));
+    }
+    rules
+}
+
+pub(super) fn cmd_explain_epic<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    id: String,
+    config: &Config,
+) -> Result<()> {
+    let frames = watson.log().map_err(w_err)?;
+    let frame = find_by_short_id(&frames, &id)?;
+    println!(
+        "{}  {}{}  {} → {}",
+        short_id(frame).bright_black(),
+        frame.project.yellow().bold(),
+        fmt_tags(&frame.tags),
+        fmt_time(frame.start),
+        fmt_time(frame.end),
+    );
+    println!();
+
+    let resolver = EpicResolver::new(&config.epics);
+    for explanation in resolver.explain(frame) {
+        let epic = explanation.epic;
+        let mark = if explanation.matched {
+            "✓".green().bold().to_string()
+        } else {
+            "✗".red().bold().to_string()
+        };
+        let priority = if epic.priority != 0 {
+            format!("  (priority {})", epic.priority)
         } else {
-            println!("  tags     {}", epic.tags.join(", ").cyan());
+            String::new()
+        };
+        println!(
+            "{mark} {}{}",
+            epic.name.cyan().bold(),
+            priority.bright_black()
+        );
+        for reason in &explanation.reasons {
+            println!("    {}", reason.bright_black());
         }
     }
+    println!();
+    match resolver.resolve(frame) {
+        Some(epic) => println!("{} {}", "Epic:".bold(), epic.name.cyan().bold()),
+        None => println!("{} {}", "Epic:".bold(), "Unassigned".bright_black()),
+    }
     Ok(())
 }
 
@@ -71,3 +243,179 @@ pub(super) fn cmd_rename<S: Storage<Error: std::error::Error + Send + Sync + 'st
     );
     Ok(())
 }
+
+pub(super) fn cmd_merge_projects<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    sources: Vec<String>,
+    into: Option<String>,
+    config: &Config,
+) -> Result<()> {
+    let from_config = into.is_none();
+    let merges = merge_plan(sources, into, &config.aliases.projects)?;
+    let mut total = 0;
+    for (sources, into) in merges {
+        match watson.merge_projects(&sources, &into) {
+            Ok(count) => {
+                total += count;
+                println!(
+                    "{}  {}  {}  {} {}",
+                    "Merged".green().bold(),
+                    sources.join(", ").yellow(),
+                    "→".white(),
+                    into.yellow().bold(),
+                    format!("({count} updated)").bright_black(),
+                );
+            }
+            // Aliases that never made it into the history are nothing to consolidate.
+            Err(WatsonError::ProjectNotFound(_)) if from_config => {}
+            Err(e) => return Err(w_err(e)),
+        }
+    }
+    if total == 0 {
+        println!("{}", "Nothing to merge.".bright_black());
+    }
+    Ok(())
+}
+
+pub(super) fn cmd_merge_tags<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    sources: Vec<String>,
+    into: Option<String>,
+    config: &Config,
+) -> Result<()> {
+    let from_config = into.is_none();
+    let merges = merge_plan(sources, into, &config.aliases.tags)?;
+    let mut total = 0;
+    for (sources, into) in merges {
+        match watson.merge_tags(&sources, &into) {
+            Ok(count) => {
+                total += count;
+                println!(
+                    "{}  {}  {}  {} {}",
+                    "Merged".green().bold(),
+                    sources.join(", ").cyan(),
+                    "→".white(),
+                    into.cyan().bold(),
+                    format!("({count} updated)").bright_black(),
+                );
+            }
+            Err(WatsonError::TagNotFound(_)) if from_config => {}
+            Err(e) => return Err(w_err(e)),
+        }
+    }
+    if total == 0 {
+        println!("{}", "Nothing to merge.".bright_black());
+    }
+    Ok(())
+}
+
+/// Explicit `SOURCE... --into TARGET` yields one merge; no arguments yields one
+/// merge per canonical name configured in `[aliases]`.
+fn merge_plan(
+    sources: Vec<String>,
+    into: Option<String>,
+    aliases: &BTreeMap<String, Vec<String>>,
+) -> Result<Vec<(Vec<String>, String)>> {
+    match into {
+        Some(into) => Ok(vec![(sources, into)]),
+        None if sources.is_empty() => {
+            if aliases.is_empty() {
+                anyhow::bail!("No aliases configured. Pass SOURCE... --into TARGET instead.");
+            }
+            Ok(aliases
+                .iter()
+                .filter(|(_, names)| !names.is_empty())
+                .map(|(canonical, names)| (names.clone(), canonical.clone()))
+                .collect())
+        }
+        None => anyhow::bail!("Missing --into: which name should the sources be merged into?"),
+    }
+}
+
+pub(super) fn cmd_rename_tag<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    from: String,
+    to: String,
+) -> Result<()> {
+    let count = watson.rename_tag(&from, &to).map_err(w_err)?;
+    println!(
+        "{}  {}  {}  {} {}",
+        "Renamed".green().bold(),
+        from.cyan(),
+        "→".white(),
+        to.cyan(),
+        format!("({count} updated)").bright_black(),
+    );
+    Ok(())
+}
+
+pub(super) fn cmd_retag<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    filter: &FrameFilter,
+    add: Vec<String>,
+    remove: Vec<String>,
+    dry_run: bool,
+) -> Result<()> {
+    if add.is_empty() && remove.is_empty() {
+        anyhow::bail!("Nothing to do: pass --add and/or --remove");
+    }
+
+    // Preview the result per frame; removals apply before additions, like the real run.
+    let changes: Vec<(Frame, Frame)> = watson
+        .log()
+        .map_err(w_err)?
+        .into_iter()
+        .filter(|f| filter.matches(f))
+        .filter_map(|before| {
+            let mut after = before.clone();
+            let removed = after.remove_tags(&remove);
+            let added = after.add_tags(&add);
+            (removed || added).then_some((before, after))
+        })
+        .collect();
+
+    if changes.is_empty() {
+        println!("{}", "No frames need changing.".bright_black());
+        return Ok(());
+    }
+
+    let count = changes.len();
+    if dry_run {
+        println!(
+            "{} {} {} {}",
+            "Would retag".bright_black(),
+            count.to_string().yellow().bold(),
+            "frames".bright_black(),
+            "(dry run — no changes made)".bright_black(),
+        );
+    } else {
+        watson.retag(filter, &add, &remove).map_err(w_err)?;
+        println!(
+            "{} {} {}",
+            "Retagged".green().bold(),
+            count.to_string().yellow().bold(),
+            "frames".bright_black(),
+        );
+    }
+    for (before, after) in &changes {
+        println!(
+            "  {} {}  {}{}  {} {}",
+            before
+                .start
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+                .bright_black(),
+            fmt_time(before.start).bright_white(),
+            before.project.yellow().bold(),
+            fmt_tags(&before.tags),
+            "→".white(),
+            if after.tags.is_empty() {
+                "(no tags)".bright_black().to_string()
+            } else {
+                fmt_tags(&after.tags).trim_start().to_string()
+            },
+        );
+    }
+    Ok(())
+}
@@ -1,17 +1,31 @@
+mod config;
+mod epics;
 mod frames;
+mod gaps;
 mod init;
 mod io;
 mod meta;
 mod tracking;
 
+use std::io::IsTerminal;
+
 use anyhow::Result;
-use clap::Subcommand;
-use rs_watson::Watson;
+use chrono::Utc;
+use clap::{Args, Subcommand};
+use dialoguer::{Confirm, theme::ColorfulTheme};
+use owo_colors::OwoColorize;
+use rs_watson::report::Dimension;
+use rs_watson::strict::UnknownName;
+use rs_watson::{FrameFilter, Query, Watson};
 use rs_watson_storage::Storage;
+use uuid::Uuid;
 
+use crate::chart::ChartKind;
 use crate::time_utils::parse_date;
-use rs_watson::config::{Config, WeekStart};
+use rs_watson::config::Config;
 
+pub(crate) use config::cmd_config_unloaded;
+pub(crate) use frames::short_id;
 pub(crate) use init::cmd_init;
 
 // ---------------------------------------------------------------------------
@@ -24,9 +38,9 @@ pub(crate) enum Commands {
     Init,
     /// Start tracking time on a project
     Start {
-        /// Project name
+        /// Project name (default: the one set by the nearest .watson.toml)
         #[arg(value_name = "PROJECT")]
-        project: String,
+        project: Option<String>,
         /// Tags (can be specified multiple times)
         #[arg(short = 't', long = "tag")]
         tags: Vec<String>,
@@ -48,54 +62,110 @@ pub(crate) enum Commands {
     Statusline,
     /// List completed frames, grouped by day
     Log {
-        /// Filter by project name (exact match)
-        #[arg(value_name = "PROJECT")]
-        project: Option<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
         /// Start date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
         #[arg(long, value_name = "DATE")]
         from: Option<String>,
         /// End date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
         #[arg(long, value_name = "DATE")]
         to: Option<String>,
-        /// Filter by tag — can be given multiple times (all must match)
-        #[arg(short = 't', long = "tag", value_name = "TAG")]
-        tags: Vec<String>,
         /// Show only the last N frames
         #[arg(long, value_name = "N")]
         limit: Option<usize>,
         /// Skip the last N frames (combine with --limit for pagination)
         #[arg(long, value_name = "N")]
         offset: Option<usize>,
+        /// List the commits made during each frame in the current git repository
+        #[arg(long)]
+        commits: bool,
     },
     /// Show aggregated report for today
     Today {
-        /// Filter by project name (exact match)
-        #[arg(value_name = "PROJECT")]
-        project: Option<String>,
-        /// Filter by tag — can be given multiple times (all must match)
-        #[arg(short = 't', long = "tag", value_name = "TAG")]
-        tags: Vec<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
         /// Group by epic instead of project (requires epics in config.toml)
         #[arg(long)]
         epic: bool,
+        /// Leave out the currently running frame
+        #[arg(long)]
+        no_running: bool,
     },
     /// Show aggregated report for all recorded time
     Report {
-        /// Filter by project name (exact match)
-        #[arg(value_name = "PROJECT")]
-        project: Option<String>,
+        #[command(flatten)]
+        filter: FilterArgs,
         /// Start date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
         #[arg(long, value_name = "DATE")]
         from: Option<String>,
         /// End date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
         #[arg(long, value_name = "DATE")]
         to: Option<String>,
-        /// Filter by tag — can be given multiple times (all must match)
-        #[arg(short = 't', long = "tag", value_name = "TAG")]
-        tags: Vec<String>,
         /// Group by epic instead of project (requires epics in config.toml)
-        #[arg(long)]
+        #[arg(long, conflicts_with = "group_by")]
         epic: bool,
+        /// Group by nested dimensions, outermost first: epic, project, tag, day, week, weekday
+        #[arg(long, value_name = "DIMS", value_delimiter = ',')]
+        group_by: Vec<Dimension>,
+        /// Draw a chart instead: projects (default), days, weekdays or calendar
+        #[arg(
+            long,
+            value_name = "KIND",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "projects",
+            conflicts_with_all = ["epic", "group_by"]
+        )]
+        chart: Option<ChartKind>,
+        /// Leave out the currently running frame
+        #[arg(long)]
+        no_running: bool,
+    },
+    /// Show averages, streaks, habits and per-project weekly trends
+    Stats {
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Start date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
+        #[arg(long, value_name = "DATE")]
+        from: Option<String>,
+        /// End date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
+        #[arg(long, value_name = "DATE")]
+        to: Option<String>,
+        /// Number of weeks in the project trend, ending with this week (1-52)
+        #[arg(
+            long,
+            value_name = "N",
+            default_value_t = 4,
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=52)
+        )]
+        weeks: usize,
+    },
+    /// Show untracked time within working hours
+    Gaps {
+        /// First day to check (YYYY-MM-DD or shortcuts: today, yesterday, week, month; default: today)
+        #[arg(long, value_name = "DATE")]
+        from: Option<String>,
+        /// Last day to check (YYYY-MM-DD or shortcuts: today, yesterday, week, month; default: today)
+        #[arg(long, value_name = "DATE")]
+        to: Option<String>,
+        /// Pick gaps interactively and fill them with new frames
+        #[arg(long)]
+        fill: bool,
+    },
+    /// Find frames whose project or tags match TEXT, best match first
+    Search {
+        /// Words to look for; typos and partial words still match
+        #[arg(required = true, num_args = 1..)]
+        text: Vec<String>,
+        /// Start date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
+        #[arg(long, value_name = "DATE")]
+        from: Option<String>,
+        /// End date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
+        #[arg(long, value_name = "DATE")]
+        to: Option<String>,
+        /// Show at most N matches
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        limit: usize,
     },
     /// Edit a recorded frame interactively
     Edit {
@@ -124,6 +194,27 @@ pub(crate) enum Commands {
         #[arg(value_name = "ID")]
         id: Option<String>,
     },
+    /// Split a recorded frame in two at a given time
+    Split {
+        /// Short frame ID to split directly, skipping the selector (e.g. "a1b2c3d4")
+        #[arg(value_name = "ID")]
+        id: Option<String>,
+        /// Split time: 12:30 (on the frame's own date) or 2026-05-14 12:30 (default: prompt)
+        #[arg(long, value_name = "DATETIME")]
+        at: Option<String>,
+        /// Project for the second part (default: the original frame's project)
+        #[arg(short = 'p', long, value_name = "PROJECT")]
+        project: Option<String>,
+        /// Tags for the second part (default: the original frame's tags)
+        #[arg(short = 't', long = "tag")]
+        tags: Vec<String>,
+    },
+    /// Merge recorded frames of the same project into one
+    Merge {
+        /// Short frame IDs to merge directly, skipping the selector
+        #[arg(value_name = "ID")]
+        ids: Vec<String>,
+    },
     /// Rename a project across all recorded frames
     Rename {
         /// Current project name
@@ -131,12 +222,97 @@ pub(crate) enum Commands {
         /// New project name
         to: String,
     },
+    /// Rename a tag across all recorded frames
+    RenameTag {
+        /// Current tag name
+        from: String,
+        /// New tag name (frames that already have it just lose the old one)
+        to: String,
+    },
+    /// Move all frames of one or more projects into another (possibly existing) project
+    MergeProjects {
+        /// Projects to merge away (default: all aliases from config.toml)
+        #[arg(value_name = "SOURCE")]
+        sources: Vec<String>,
+        /// Project to merge into
+        #[arg(long, value_name = "PROJECT", requires = "sources")]
+        into: Option<String>,
+    },
+    /// Replace one or more tags with another (possibly existing) tag
+    MergeTags {
+        /// Tags to merge away (default: all aliases from config.toml)
+        #[arg(value_name = "SOURCE")]
+        sources: Vec<String>,
+        /// Tag to merge into
+        #[arg(long, value_name = "TAG", requires = "sources")]
+        into: Option<String>,
+    },
+    /// Add or remove tags on every frame matching a filter
+    Retag {
+        /// Filter by project name (exact match)
+        #[arg(value_name = "PROJECT")]
+        project: Option<String>,
+        /// Start date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
+        #[arg(long, value_name = "DATE")]
+        from: Option<String>,
+        /// End date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
+        #[arg(long, value_name = "DATE")]
+        to: Option<String>,
+        /// Filter by tag — can be given multiple times (all must match)
+        #[arg(short = 't', long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Tag to add — can be given multiple times
+        #[arg(long = "add", value_name = "TAG")]
+        add: Vec<String>,
+        /// Tag to remove — can be given multiple times
+        #[arg(long = "remove", value_name = "TAG")]
+        remove: Vec<String>,
+        /// Show which frames would change without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// List all tags that have been used
     Tags,
-    /// List all projects that have been tracked
-    Projects,
-    /// List all configured epics
-    Epics,
+    /// List all projects that have been tracked or registered
+    Projects {
+        /// Include archived projects
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show or update a project's description, color, client and archive state
+    Project {
+        /// Project name
+        name: String,
+        /// Free-text description (empty string clears it)
+        #[arg(long, value_name = "TEXT")]
+        description: Option<String>,
+        /// Display color as #rrggbb (empty string clears it)
+        #[arg(long, value_name = "COLOR")]
+        color: Option<String>,
+        /// Client the project is billed to (empty string clears it)
+        #[arg(long, value_name = "CLIENT")]
+        client: Option<String>,
+        /// Hide the project from project lists and autocompletion
+        #[arg(long, conflicts_with = "unarchive")]
+        archive: bool,
+        /// Restore an archived project
+        #[arg(long)]
+        unarchive: bool,
+    },
+    /// List all configured epics, or add, edit and remove them
+    #[command(args_conflicts_with_subcommands = true)]
+    Epics {
+        /// Show which epic rules match a frame and why (short frame ID, e.g. "a1b2c3d4")
+        #[arg(long, value_name = "ID")]
+        explain: Option<String>,
+        #[command(subcommand)]
+        action: Option<EpicAction>,
+    },
+    /// Show, change or check the configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
     /// Print shell completion script to stdout
     Completions {
         /// Shell to generate completions for
@@ -144,18 +320,29 @@ pub(crate) enum Commands {
     },
     /// Export frames to a file or stdout
     Export {
-        /// Output format
-        #[arg(long, value_enum, default_value = "csv")]
-        format: io::ExportFormat,
+        /// Output format: csv, json or an external `watson-export-<name>` on PATH
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// Exporter option, repeatable (see --list)
+        #[arg(long = "option", value_name = "KEY=VALUE")]
+        options: Vec<String>,
+        /// List the available formats and their options
+        #[arg(long)]
+        list: bool,
         /// Output file (default: stdout)
         #[arg(long, value_name = "FILE")]
         output: Option<String>,
-        /// Start date filter
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Start date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
         #[arg(long, value_name = "DATE")]
         from: Option<String>,
-        /// End date filter
+        /// End date filter (YYYY-MM-DD or shortcuts: today, yesterday, week, month)
         #[arg(long, value_name = "DATE")]
         to: Option<String>,
+        /// Only frames belonging to this epic
+        #[arg(long, value_name = "EPIC")]
+        epic: Option<String>,
     },
     /// Import frames from an external source
     Import {
@@ -171,6 +358,144 @@ pub(crate) enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub(crate) enum ConfigAction {
+    /// Print the effective configuration, with defaults and overrides applied
+    Show,
+    /// Print one setting, e.g. "behavior.week_start"
+    Get {
+        /// Dotted key
+        key: String,
+    },
+    /// Change one setting in config.toml, keeping comments and formatting
+    Set {
+        /// Dotted key, e.g. "behavior.max_frame_hours"
+        key: String,
+        /// TOML value (8, true, ["a", "b"]) or plain text
+        value: String,
+    },
+    /// Remove a setting from config.toml so its default applies
+    Unset {
+        /// Dotted key
+        key: String,
+    },
+    /// Check config.toml for errors, unknown keys and epics naming unknown projects
+    Validate,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum EpicAction {
+    /// Add an epic to config.toml
+    Add {
+        /// Epic name
+        name: String,
+        #[command(flatten)]
+        rules: EpicRuleArgs,
+        /// Accept projects that have never been used or registered
+        #[arg(long)]
+        force: bool,
+    },
+    /// Change an epic's name or rules; options not given stay as they are
+    Edit {
+        /// Current epic name
+        name: String,
+        /// New name
+        #[arg(long, value_name = "NAME")]
+        rename: Option<String>,
+        #[command(flatten)]
+        rules: EpicRuleArgs,
+        /// Accept projects that have never been used or registered
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove an epic from config.toml
+    Remove {
+        /// Epic name
+        name: String,
+    },
+}
+
+/// Epic rules shared by `epics add` and `epics edit`. For `edit`, an empty value
+/// clears the rule.
+#[derive(Args)]
+pub(crate) struct EpicRuleArgs {
+    /// Exact project name — can be given multiple times
+    #[arg(short = 'p', long = "project", value_name = "PROJECT")]
+    projects: Vec<String>,
+    /// Glob on the project name (`*` any run of characters, `?` one character)
+    #[arg(long, value_name = "GLOB")]
+    glob: Option<String>,
+    /// Regular expression matching the whole project name
+    #[arg(long, value_name = "REGEX")]
+    regex: Option<String>,
+    /// Required tag — can be given multiple times (all must match)
+    #[arg(short = 't', long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+    /// Tag that keeps a frame out of the epic — can be given multiple times
+    #[arg(long = "exclude-tag", value_name = "TAG")]
+    exclude_tags: Vec<String>,
+    /// Among matching epics the highest priority wins
+    #[arg(long, allow_negative_numbers = true)]
+    priority: Option<i32>,
+}
+
+/// Frame selection shared by `log`, `today`, `report` and `export`.
+#[derive(Args)]
+pub(crate) struct FilterArgs {
+    /// Filter by project name — several match any of them
+    #[arg(value_name = "PROJECT")]
+    projects: Vec<String>,
+    /// Filter by tag — can be given multiple times (all must match)
+    #[arg(short = 't', long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+    /// Filter by tag — can be given multiple times (any must match)
+    #[arg(long = "any-tag", value_name = "TAG")]
+    any_tags: Vec<String>,
+    /// Leave out a project — can be given multiple times
+    #[arg(long = "exclude-project", value_name = "PROJECT")]
+    exclude_projects: Vec<String>,
+    /// Leave out frames with a tag — can be given multiple times
+    #[arg(long = "exclude-tag", value_name = "TAG")]
+    exclude_tags: Vec<String>,
+    /// Filter expression, e.g. "project:acme and (tag:api or tag:db) and not tag:meeting"
+    #[arg(short = 'q', long, value_name = "EXPR")]
+    query: Option<String>,
+}
+
+impl FilterArgs {
+    /// The core filter for these arguments plus `--from` / `--to`. All criteria
+    /// must match; several projects, `--any-tag` and `--query` become one [`Query`].
+    pub(super) fn into_filter(
+        self,
+        from: Option<String>,
+        to: Option<String>,
+        config: &Config,
+    ) -> Result<FrameFilter> {
+        let mut projects = self.projects;
+        let (project, any_project) = if projects.len() > 1 {
+            let any = Query::Or(projects.into_iter().map(Query::Project).collect());
+            (None, Some(any))
+        } else {
+            (projects.pop(), None)
+        };
+        let query = self.query.as_deref().map(Query::parse).transpose()?;
+        let aliases = &config.aliases;
+        let mut filter = build_filter(project, self.tags, from, to, config)?;
+        filter.exclude_projects = self
+            .exclude_projects
+            .iter()
+            .map(|p| aliases.canonical_project(p).to_string())
+            .collect();
+        filter.exclude_tags = aliases.canonical_tags(self.exclude_tags);
+        filter.query = [any_project, Query::any_tag(&self.any_tags), query]
+            .into_iter()
+            .flatten()
+            .reduce(Query::and)
+            .map(|q| q.canonicalize(aliases));
+        Ok(filter)
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Shared helpers (accessible to all submodules via `super::`)
 // ---------------------------------------------------------------------------
@@ -179,35 +504,71 @@ fn w_err<E: std::fmt::Display>(e: E) -> anyhow::Error {
     anyhow::anyhow!("{e}")
 }
 
-pub(super) fn apply_project_tag_filter(
-    frames: Vec<rs_watson::Frame>,
-    project: Option<&str>,
-    tags: &[String],
-) -> Vec<rs_watson::Frame> {
-    frames
-        .into_iter()
-        .filter(|f| {
-            project.is_none_or(|p| f.project == p) && tags.iter().all(|t| f.tags.contains(t))
-        })
-        .collect()
+/// In strict mode, asks before creating each project or tag in `unknown`. Declining,
+/// or running without a terminal, fails with the "did you mean" error instead.
+pub(super) fn confirm_new_names<S: Storage>(
+    watson: &Watson<S>,
+    unknown: Vec<UnknownName>,
+) -> Result<()> {
+    for name in unknown {
+        if !std::io::stdin().is_terminal() {
+            return Err(w_err(name));
+        }
+        println!(
+            "{} {}",
+            "Warning:".yellow().bold(),
+            name.to_string().yellow()
+        );
+        let create = Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Create new {} \"{}\"?", name.kind, name.name))
+            .default(false)
+            .interact()?;
+        if !create {
+            return Err(w_err(name));
+        }
+        watson.accept_name(&name);
+    }
+    Ok(())
+}
+
+/// Loads all completed frames and, when `include_running` is set, appends the
+/// active frame as a provisional frame ending now. Returns the provisional frame's
+/// ID alongside so the output can mark it as running.
+pub(super) fn load_with_running<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
+    watson: &Watson<S>,
+    include_running: bool,
+) -> Result<(Vec<rs_watson::Frame>, Option<Uuid>)> {
+    let mut frames = watson.log().map_err(w_err)?;
+    let running = if include_running {
+        watson.running(Utc::now()).map_err(w_err)?
+    } else {
+        None
+    };
+    let running_id = running.as_ref().map(|f| f.id);
+    frames.extend(running);
+    Ok((frames, running_id))
 }
 
-pub(super) fn apply_date_filter(
-    frames: Vec<rs_watson::Frame>,
+/// Builds a core [`FrameFilter`] from the CLI's project / `--tag` / `--from` / `--to`
+/// arguments. Aliases are resolved to canonical names and `behavior.ignore_case` applies.
+pub(super) fn build_filter(
+    project: Option<String>,
+    tags: Vec<String>,
     from: Option<String>,
     to: Option<String>,
-    week_start: WeekStart,
-) -> Result<Vec<rs_watson::Frame>> {
-    use chrono::Local;
-    let from = from.map(|s| parse_date(&s, week_start)).transpose()?;
-    let to = to.map(|s| parse_date(&s, week_start)).transpose()?;
-    Ok(frames
-        .into_iter()
-        .filter(|f| {
-            let d = f.start.with_timezone(&Local).date_naive();
-            from.is_none_or(|fd| d >= fd) && to.is_none_or(|td| d <= td)
-        })
-        .collect())
+    config: &Config,
+) -> Result<FrameFilter> {
+    let week_start = config.behavior.week_start;
+    let aliases = &config.aliases;
+    Ok(FrameFilter {
+        project: project.map(|p| aliases.canonical_project(&p).to_string()),
+        tags: aliases.canonical_tags(tags),
+        from: from.map(|s| parse_date(&s, week_start)).transpose()?,
+        to: to.map(|s| parse_date(&s, week_start)).transpose()?,
+        ignore_case: config.behavior.ignore_case,
+        separator: config.behavior.project_separator.clone(),
+        ..Default::default()
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -219,7 +580,31 @@ pub(crate) fn dispatch<S: Storage<Error: std::error::Error + Send + Sync + 'stat
     command: Commands,
     config: &Config,
 ) -> Result<()> {
-    match command {
+    let watson = watson
+        .with_aliases(config.aliases.clone())
+        .with_strictness(config.behavior.strictness())
+        .with_project_separator(config.behavior.project_separator.clone())
+        .with_hooks(config.hooks.clone());
+    // Only commands that change the tracked time count as user activity, and
+    // only when `max_frame_hours` is set to make use of it.
+    let max_frame = config.behavior.max_frame_duration();
+    let is_activity = max_frame.is_some()
+        && matches!(
+            command,
+            Commands::Start { .. }
+                | Commands::Edit { .. }
+                | Commands::Add { .. }
+                | Commands::Remove { .. }
+                | Commands::Split { .. }
+                | Commands::Merge { .. }
+                | Commands::Rename { .. }
+                | Commands::RenameTag { .. }
+                | Commands::MergeProjects { .. }
+                | Commands::MergeTags { .. }
+                | Commands::Retag { .. }
+                | Commands::Import { dry_run: false, .. }
+        );
+    let result = match command {
         Commands::Init | Commands::Completions { .. } => {
             unreachable!("handled before dispatch")
         }
@@ -228,28 +613,61 @@ pub(crate) fn dispatch<S: Storage<Error: std::error::Error + Send + Sync + 'stat
         }
         Commands::Stop { at } => tracking::cmd_stop(&watson, at, config),
         Commands::Cancel => tracking::cmd_cancel(&watson),
-        Commands::Status => tracking::cmd_status(&watson),
+        Commands::Status => tracking::cmd_status(&watson, config),
         Commands::Statusline => tracking::cmd_statusline(&watson),
         Commands::Log {
+            filter,
             from,
             to,
-            project,
-            tags,
             limit,
             offset,
-        } => frames::cmd_log(&watson, from, to, project, tags, limit, offset, config),
+            commits,
+        } => {
+            let filter = filter.into_filter(from, to, config)?;
+            frames::cmd_log(&watson, &filter, limit, offset, commits, config)
+        }
         Commands::Today {
-            project,
-            tags,
+            filter,
             epic,
-        } => frames::cmd_today(&watson, project, tags, epic, config),
+            no_running,
+        } => {
+            let filter = filter.into_filter(None, None, config)?;
+            frames::cmd_today(&watson, &filter, epic, !no_running, config)
+        }
         Commands::Report {
+            filter,
             from,
             to,
-            project,
-            tags,
             epic,
-        } => frames::cmd_report(&watson, from, to, project, tags, epic, config),
+            group_by,
+            chart,
+            no_running,
+        } => {
+            let filter = filter.into_filter(from, to, config)?;
+            match chart {
+                Some(kind) => frames::cmd_chart(&watson, &filter, kind, !no_running, config),
+                None => frames::cmd_report(&watson, &filter, epic, &group_by, !no_running, config),
+            }
+        }
+        Commands::Stats {
+            filter,
+            from,
+            to,
+            weeks,
+        } => {
+            let filter = filter.into_filter(from, to, config)?;
+            frames::cmd_stats(&watson, &filter, weeks, config)
+        }
+        Commands::Gaps { from, to, fill } => gaps::cmd_gaps(&watson, from, to, fill, config),
+        Commands::Search {
+            text,
+            from,
+            to,
+            limit,
+        } => {
+            let filter = build_filter(None, Vec::new(), from, to, config)?;
+            frames::cmd_search(&watson, &text.join(" "), &filter, limit)
+        }
         Commands::Add {
             project,
             tags,
@@ -258,20 +676,97 @@ pub(crate) fn dispatch<S: Storage<Error: std::error::Error + Send + Sync + 'stat
         } => frames::cmd_add(&watson, project, tags, from, to, config),
         Commands::Edit { id } => frames::cmd_edit(&watson, id),
         Commands::Remove { id } => frames::cmd_remove(&watson, id),
+        Commands::Split {
+            id,
+            at,
+            project,
+            tags,
+        } => frames::cmd_split(&watson, id, at, project, tags, config),
+        Commands::Merge { ids } => frames::cmd_merge(&watson, ids),
         Commands::Rename { from, to } => meta::cmd_rename(&watson, from, to),
-        Commands::Projects => meta::cmd_projects(&watson),
+        Commands::MergeProjects { sources, into } => {
+            meta::cmd_merge_projects(&watson, sources, into, config)
+        }
+        Commands::MergeTags { sources, into } => {
+            meta::cmd_merge_tags(&watson, sources, into, config)
+        }
+        Commands::RenameTag { from, to } => meta::cmd_rename_tag(&watson, from, to),
+        Commands::Retag {
+            project,
+            from,
+            to,
+            tags,
+            add,
+            remove,
+            dry_run,
+        } => {
+            let filter = build_filter(project, tags, from, to, config)?;
+            meta::cmd_retag(&watson, &filter, add, remove, dry_run)
+        }
+        Commands::Projects { all } => meta::cmd_projects(&watson, all),
+        Commands::Project {
+            name,
+            description,
+            color,
+            client,
+            archive,
+            unarchive,
+        } => meta::cmd_project(
+            &watson,
+            name,
+            meta::ProjectUpdate {
+                description,
+                color,
+                client,
+                archived: (archive || unarchive).then_some(archive),
+            },
+        ),
         Commands::Tags => meta::cmd_tags(&watson),
-        Commands::Epics => meta::cmd_epics(config),
+        Commands::Epics {
+            action: Some(action),
+            ..
+        } => epics::cmd_epic_action(&watson, action),
+        Commands::Epics {
+            explain: None,
+            action: None,
+        } => meta::cmd_epics(config),
+        Commands::Epics {
+            explain: Some(id),
+            action: None,
+        } => meta::cmd_explain_epic(&watson, id, config),
+        Commands::Config { action } => config::cmd_config(&watson, action, config),
+        Commands::Export { list: true, .. } => io::cmd_export_formats(),
         Commands::Export {
             format,
+            options,
+            list: false,
             output,
+            filter,
             from,
             to,
-        } => io::cmd_export(&watson, format, output, from, to, config),
+            epic,
+        } => {
+            let filter = filter.into_filter(from, to, config)?;
+            io::cmd_export(&watson, &format, &options, output, &filter, epic, config)
+        }
         Commands::Import {
             source,
             file,
             dry_run,
         } => io::cmd_import(&watson, source, file, dry_run),
+    };
+
+    for failure in watson.take_hook_warnings() {
+        eprintln!("{} {failure}", "Warning:".yellow().bold());
+    }
+    if result.is_ok() && is_activity {
+        // The command itself succeeded, so a failure here is only worth a warning.
+        if let Err(e) = watson.record_activity(Utc::now(), max_frame) {
+            eprintln!(
+                "{} could not record activity: {e}",
+                "Warning:".yellow().bold()
+            );
+        }
     }
+    result
 }
@@ -1,28 +1,58 @@
+use std::io::IsTerminal;
+
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
+use dialoguer::{Select, theme::ColorfulTheme};
 use owo_colors::OwoColorize;
 use rs_watson::Watson;
 use rs_watson_storage::Storage;
 
 use crate::format::{fmt_duration, fmt_tags, fmt_time, print_frame_summary};
-use crate::time_utils::{check_future, parse_at};
-use rs_watson::StartResult;
+use crate::time_utils::{check_future, parse_at, prompt_time};
 use rs_watson::config::Config;
+use rs_watson::git;
+use rs_watson::idle::{Overrun, StopSuggestion};
+use rs_watson::{ActiveFrame, StartResult};
 
-use super::w_err;
+use super::{confirm_new_names, w_err};
 
 pub(super) fn cmd_start<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
-    project: String,
+    project: Option<String>,
     tags: Vec<String>,
     at: Option<String>,
     config: &Config,
 ) -> Result<()> {
+    let (project, mut tags) = match project {
+        Some(project) => (project, tags),
+        None => context_start(tags, config)?,
+    };
+    if let Ok(dir) = std::env::current_dir()
+        && let Some(tag) = git::start_tag(&dir, &config.git)
+        && !tags.contains(&tag)
+    {
+        tags.push(tag);
+    }
     let time = at
         .map(|s| parse_at(&s))
         .transpose()?
         .unwrap_or_else(Utc::now);
     check_future(time, config)?;
+
+    // A forgotten frame would otherwise be stopped at `time`, possibly many hours late.
+    if let Some(max) = config.behavior.max_frame_duration()
+        && let Some(overrun) = watson.overrun(max, time).map_err(w_err)?
+        && let Some(at) = offer_overrun_stop(&overrun, config)?
+    {
+        let stopped = watson.stop(at).map_err(w_err)?;
+        print_frame_summary("Stopped ".red().bold(), &stopped);
+        println!();
+    }
+
+    confirm_new_names(
+        watson,
+        watson.unknown_names(&project, &tags).map_err(w_err)?,
+    )?;
     let StartResult { replaced, active } = watson
         .start_or_replace(&project, tags, time)
         .map_err(w_err)?;
@@ -42,6 +72,27 @@ pub(super) fn cmd_start<S: Storage<Error: std::error::Error + Send + Sync + 'sta
     Ok(())
 }
 
+/// Project and tags for `start` without a project, from the nearest
+/// `.watson.toml`. Tags given on the command line are kept after its own.
+fn context_start(tags: Vec<String>, config: &Config) -> Result<(String, Vec<String>)> {
+    let Some(context) = &config.context else {
+        anyhow::bail!("No project given and no .watson.toml found in this directory or above.");
+    };
+    let (project, mut defaults) = context.start_defaults(&config.epics).map_err(w_err)?;
+    let project = project.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No project given and {} does not set one.",
+            context.path.display()
+        )
+    })?;
+    for tag in tags {
+        if !defaults.contains(&tag) {
+            defaults.push(tag);
+        }
+    }
+    Ok((project, defaults))
+}
+
 pub(super) fn cmd_stop<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
     at: Option<String>,
@@ -81,17 +132,13 @@ pub(super) fn cmd_cancel<S: Storage<Error: std::error::Error + Send + Sync + 'st
 pub(super) fn cmd_statusline<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
 ) -> Result<()> {
-    use chrono::Local;
-
-    let active = watson.status().map_err(w_err)?;
-    let Some(ref frame) = active else {
+    let now = Utc::now();
+    let Some(frame) = watson.running(now).map_err(w_err)? else {
         println!("No project started.");
         return Ok(());
     };
 
-    let now = Utc::now();
     let today = now.with_timezone(&Local).date_naive();
-
     let completed_today: i64 = watson
         .log()
         .map_err(w_err)?
@@ -100,8 +147,7 @@ pub(super) fn cmd_statusline<S: Storage<Error: std::error::Error + Send + Sync +
         .map(|f| (f.end - f.start).num_seconds())
         .sum();
 
-    let active_elapsed = (now - frame.start).num_seconds().max(0);
-    let total = completed_today + active_elapsed;
+    let total = completed_today + (frame.end - frame.start).num_seconds();
     let h = total / 3600;
     let m = (total % 3600) / 60;
 
@@ -111,24 +157,136 @@ pub(super) fn cmd_statusline<S: Storage<Error: std::error::Error + Send + Sync +
 
 pub(super) fn cmd_status<S: Storage<Error: std::error::Error + Send + Sync + 'static>>(
     watson: &Watson<S>,
+    config: &Config,
 ) -> Result<()> {
     let now = Utc::now();
-    match watson.status().map_err(w_err)? {
-        Some(frame) => {
-            println!(
-                "{} {}{}",
-                "Tracking".blue().bold(),
-                frame.project.yellow().bold(),
-                fmt_tags(&frame.tags),
-            );
-            println!(
-                "  {} {}  {}",
-                "since".bright_black(),
-                fmt_time(frame.start).bright_white(),
-                fmt_duration(now - frame.start).magenta().bold(),
-            );
+    let Some(frame) = watson.status().map_err(w_err)? else {
+        println!("{}", "Not tracking anything.".bright_black());
+        return Ok(());
+    };
+
+    println!(
+        "{} {}{}",
+        "Tracking".blue().bold(),
+        frame.project.yellow().bold(),
+        fmt_tags(&frame.tags),
+    );
+    println!(
+        "  {}  {}  {}",
+        "since".bright_black(),
+        fmt_local_datetime(frame.start).bright_white(),
+        fmt_duration(now - frame.start).magenta().bold(),
+    );
+
+    let overrun = config
+        .behavior
+        .max_frame_duration()
+        .and_then(|max| Overrun::detect(&frame, max, now));
+    if let Some(overrun) = overrun {
+        println!();
+        if let Some(at) = offer_overrun_stop(&overrun, config)? {
+            let stopped = watson.stop(at).map_err(w_err)?;
+            println!();
+            print_frame_summary("Stopped ".red().bold(), &stopped);
         }
-        None => println!("{}", "Not tracking anything.".bright_black()),
+    } else if frame.start.with_timezone(&Local).date_naive()
+        < now.with_timezone(&Local).date_naive()
+    {
+        println!();
+        println!(
+            "  {} {}",
+            "Started on a previous day — forgot to stop? Try:".yellow(),
+            midnight_stop_hint(&frame).bright_white(),
+        );
     }
     Ok(())
 }
+
+/// Formats a datetime as local `YYYY-MM-DD HH:MM`, the form accepted by `--at`.
+fn fmt_at(dt: DateTime<Utc>) -> String {
+    dt.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// Shows the time only for today, and the date as well for anything older.
+fn fmt_local_datetime(dt: DateTime<Utc>) -> String {
+    if dt.with_timezone(&Local).date_naive() == Local::now().date_naive() {
+        fmt_time(dt)
+    } else {
+        fmt_at(dt)
+    }
+}
+
+/// Suggests a `watson stop --at` command for a frame that crossed midnight.
+/// Uses the last recorded activity when it falls on the start day, otherwise
+/// leaves the time as a placeholder for the user to fill in.
+fn midnight_stop_hint(frame: &ActiveFrame) -> String {
+    let start_day = frame.start.with_timezone(&Local).date_naive();
+    let at = match frame
+        .last_activity
+        .filter(|at| at.with_timezone(&Local).date_naive() == start_day)
+    {
+        Some(at) => fmt_at(at),
+        None => format!("{} HH:MM", start_day.format("%Y-%m-%d")),
+    };
+    format!("watson stop --at \"{at}\"")
+}
+
+/// Warns about a frame running past `max_frame_hours`. On a terminal, offers to stop
+/// it at the last activity, the threshold, or a custom time; otherwise prints a
+/// `watson stop --at` suggestion. Returns the chosen stop time, if any.
+fn offer_overrun_stop(overrun: &Overrun, config: &Config) -> Result<Option<DateTime<Utc>>> {
+    println!(
+        "{} {} {} {}",
+        "Warning:".yellow().bold(),
+        format!("\"{}\" has been running for", overrun.active.project).yellow(),
+        fmt_duration(overrun.elapsed).magenta().bold(),
+        format!(
+            "— longer than max_frame_hours ({}h).",
+            config.behavior.max_frame_hours
+        )
+        .yellow(),
+    );
+
+    let suggestions = overrun.suggestions();
+    if !std::io::stdin().is_terminal() {
+        let (_, at) = suggestions[0];
+        println!(
+            "  {} {}",
+            "Stop it with:".bright_black(),
+            format!("watson stop --at \"{}\"", fmt_at(at)).bright_white(),
+        );
+        return Ok(None);
+    }
+
+    let mut items: Vec<String> = suggestions
+        .iter()
+        .map(|(kind, at)| {
+            let label = match kind {
+                StopSuggestion::LastActivity => "last activity",
+                StopSuggestion::Threshold => "threshold",
+            };
+            format!("Stop at {}  ({label})", fmt_at(*at))
+        })
+        .collect();
+    items.push("Stop at a custom time…".into());
+    items.push("Keep running".into());
+
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Stop the forgotten frame?")
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    let at = match selection {
+        i if i < suggestions.len() => suggestions[i].1,
+        i if i == suggestions.len() => {
+            let at = prompt_time("Stop at  (HH:MM or YYYY-MM-DD HH:MM)", overrun.threshold)?;
+            check_future(at, config)?;
+            at
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(at))
+}
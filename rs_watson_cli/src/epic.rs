@@ -1,22 +1,31 @@
 use chrono::Duration;
 use owo_colors::OwoColorize;
 use rs_watson::config::EpicConfig;
-use rs_watson::{Frame, Report, resolve_epic};
+use rs_watson::{EpicResolver, Frame, Report};
+use uuid::Uuid;
 
 use crate::format::{fmt_duration, print_project_breakdown};
 
 /// Prints an aggregated report grouped by epic, then project, then tag.
 /// Frames with no matching epic are shown under "Unassigned".
-pub(crate) fn print_epic_report(frames: &[Frame], epics: &[EpicConfig], show_total: bool) {
+/// The project containing the frame with ID `running` is marked as running.
+pub(crate) fn print_epic_report(
+    frames: &[Frame],
+    epics: &[EpicConfig],
+    show_total: bool,
+    running: Option<Uuid>,
+    separator: Option<&str>,
+) {
     // Build buckets in config order so the display matches the user's ordering.
     let mut buckets: Vec<(&str, Vec<&Frame>)> =
         epics.iter().map(|e| (e.name.as_str(), vec![])).collect();
     let mut unassigned: Vec<&Frame> = vec![];
 
+    let resolver = EpicResolver::new(epics);
     for frame in frames {
-        match resolve_epic(frame, epics) {
-            Some(name) => {
-                if let Some(b) = buckets.iter_mut().find(|(n, _)| *n == name) {
+        match resolver.resolve(frame) {
+            Some(epic) => {
+                if let Some(b) = buckets.iter_mut().find(|(n, _)| *n == epic.name) {
                     b.1.push(frame);
                 }
             }
@@ -30,27 +39,27 @@ pub(crate) fn print_epic_report(frames: &[Frame], epics: &[EpicConfig], show_tot
 
     for (name, epic_frames) in buckets.iter().filter(|(_, f)| !f.is_empty()) {
         let owned: Vec<Frame> = epic_frames.iter().copied().cloned().collect();
-        let report = Report::from_frames(&owned);
+        let report = Report::from_frames_with_running(&owned, running);
         println!(
             "{}  {}",
             format!("◆  {name}").cyan().bold(),
             format!("({})", fmt_duration(report.total)).bright_black(),
         );
         println!();
-        print_project_breakdown(&report);
+        print_project_breakdown(&report, separator);
         println!();
     }
 
     if !unassigned.is_empty() {
         let owned: Vec<Frame> = unassigned.iter().copied().cloned().collect();
-        let report = Report::from_frames(&owned);
+        let report = Report::from_frames_with_running(&owned, running);
         println!(
             "{}  {}",
             "◆  Unassigned".bright_black().bold(),
             format!("({})", fmt_duration(report.total)).bright_black(),
         );
         println!();
-        print_project_breakdown(&report);
+        print_project_breakdown(&report, separator);
         println!();
     }
 
@@ -67,6 +76,7 @@ pub(crate) fn print_epic_report(frames: &[Frame], epics: &[EpicConfig], show_tot
 mod tests {
     use super::*;
     use chrono::{TimeZone, Utc};
+    use rs_watson::resolve_epic;
     use uuid::Uuid;
 
     fn frame(project: &str, tags: &[&str]) -> Frame {
@@ -82,8 +92,9 @@ mod tests {
     fn epic(name: &str, project: &str, tags: &[&str]) -> EpicConfig {
         EpicConfig {
             name: name.into(),
-            project: project.into(),
+            project: Some(project.into()),
             tags: tags.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
         }
     }
 
@@ -1,8 +1,13 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
-use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc, Weekday};
 use owo_colors::OwoColorize;
-use rs_watson::{Frame, Report};
+use rs_watson::git::Commit;
+use rs_watson::report::{Dimension, PivotNode, ProjectNode};
+use rs_watson::{Frame, Report, Stats};
+use uuid::Uuid;
+
+use crate::commands::short_id;
 
 pub(crate) fn fmt_tags(tags: &[String]) -> String {
     if tags.is_empty() {
@@ -43,8 +48,19 @@ fn group_by_day(frames: &[Frame]) -> BTreeMap<NaiveDate, Vec<&Frame>> {
     by_day
 }
 
+/// Marker appended to output lines that include the currently running frame.
+pub(crate) fn running_marker() -> String {
+    format!("  {}", "● running".green())
+}
+
 /// Prints frames as individual entries grouped by day (used by `log`).
-pub(crate) fn print_frames_grouped(frames: &[Frame]) {
+/// The frame whose ID equals `running` is marked instead of showing its short ID,
+/// and each frame is followed by its entry in `commits` (`log --commits`).
+pub(crate) fn print_frames_grouped(
+    frames: &[Frame],
+    running: Option<Uuid>,
+    commits: &HashMap<Uuid, Vec<Commit>>,
+) {
     for (date, day_frames) in group_by_day(frames) {
         let total = day_frames
             .iter()
@@ -57,28 +73,64 @@ pub(crate) fn print_frames_grouped(frames: &[Frame]) {
         );
 
         for frame in &day_frames {
-            let sid: String = frame
-                .id
-                .to_string()
-                .replace('-', "")
-                .chars()
-                .take(8)
-                .collect();
+            let suffix = if running == Some(frame.id) {
+                running_marker()
+            } else {
+                let sid: String = frame
+                    .id
+                    .to_string()
+                    .replace('-', "")
+                    .chars()
+                    .take(8)
+                    .collect();
+                format!("  {}", sid.bright_black())
+            };
             println!(
-                "  {}  {}  {}   {:<12}  {}{}  {}",
+                "  {}  {}  {}   {:<12}  {}{}{}",
                 fmt_time(frame.start).bright_white(),
                 "→".white(),
                 fmt_time(frame.end).bright_white(),
                 fmt_duration(frame.end - frame.start).magenta().bold(),
                 frame.project.yellow().bold(),
                 fmt_tags(&frame.tags),
-                sid.bright_black(),
+                suffix,
             );
+            for commit in commits.get(&frame.id).into_iter().flatten() {
+                println!(
+                    "      {}  {}  {}",
+                    commit.id.cyan(),
+                    fmt_time(commit.time).bright_black(),
+                    commit.summary,
+                );
+            }
         }
         println!();
     }
 }
 
+/// Prints search matches one per line, best first, each with its date
+/// since the list is not grouped by day (used by `search`).
+pub(crate) fn print_search_results(frames: &[Frame]) {
+    for frame in frames {
+        println!(
+            "{}  {}  {}  {}   {:<12}  {}{}  {}",
+            frame
+                .start
+                .with_timezone(&Local)
+                .format("%Y-%m-%d")
+                .to_string()
+                .white(),
+            fmt_time(frame.start).bright_white(),
+            "→".white(),
+            fmt_time(frame.end).bright_white(),
+            fmt_duration(frame.end - frame.start).magenta().bold(),
+            frame.project.yellow().bold(),
+            fmt_tags(&frame.tags),
+            short_id(frame).bright_black(),
+        );
+    }
+}
+
 /// Prints the standard 3-line summary for a completed frame:
 /// `<header> <project> [tags]`, times, and duration.
 pub(crate) fn print_frame_summary(header: impl std::fmt::Display, frame: &Frame) {
@@ -100,14 +152,26 @@ pub(crate) fn print_frame_summary(header: impl std::fmt::Display, frame: &Frame)
     );
 }
 
-/// Prints an aggregated project/tag breakdown from a report.
+/// Prints an aggregated project/tag breakdown from a report, as a tree of nested
+/// projects when a `separator` is configured.
 /// Shared by `print_report_grouped` and the epic report.
-pub(crate) fn print_project_breakdown(report: &Report) {
+pub(crate) fn print_project_breakdown(report: &Report, separator: Option<&str>) {
+    if let Some(separator) = separator {
+        for node in &report.tree(separator) {
+            print_project_node(node, 0);
+        }
+        return;
+    }
     for project in &report.projects {
         println!(
-            "  {}  {}",
+            "  {}  {}{}",
             format!("{:<20}", project.name).yellow().bold(),
             fmt_duration(project.total).magenta().bold(),
+            if project.running {
+                running_marker()
+            } else {
+                String::new()
+            },
         );
         for tag in &project.tags {
             println!(
@@ -119,9 +183,42 @@ pub(crate) fn print_project_breakdown(report: &Report) {
     }
 }
 
+/// Prints `node` indented by `depth`, then its tags and children. Durations stay
+/// aligned with the flat breakdown's column.
+fn print_project_node(node: &ProjectNode, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let width = 20usize.saturating_sub(indent.len()).max(1);
+    println!(
+        "  {indent}{}  {}{}",
+        format!("{:<width$}", node.name).yellow().bold(),
+        fmt_duration(node.total).magenta().bold(),
+        if node.running {
+            running_marker()
+        } else {
+            String::new()
+        },
+    );
+    for tag in &node.tags {
+        println!(
+            "    {indent}{}  {}",
+            format!("{:<w$}", tag.name, w = width.saturating_sub(2).max(1)).bright_black(),
+            fmt_duration(tag.total).bright_black(),
+        );
+    }
+    for child in &node.children {
+        print_project_node(child, depth + 1);
+    }
+}
+
 /// Prints frames as an aggregated report grouped by day (used by `today` and `report`).
 /// Shows grand total when `show_total` is true and there is more than one day.
-pub(crate) fn print_report_grouped(frames: &[Frame], show_total: bool) {
+/// The project containing the frame with ID `running` is marked as running.
+pub(crate) fn print_report_grouped(
+    frames: &[Frame],
+    show_total: bool,
+    running: Option<Uuid>,
+    separator: Option<&str>,
+) {
     let by_day = group_by_day(frames);
 
     let grand_total = frames
@@ -130,7 +227,7 @@ pub(crate) fn print_report_grouped(frames: &[Frame], show_total: bool) {
 
     for (date, day_frames) in &by_day {
         let owned: Vec<Frame> = day_frames.iter().copied().cloned().collect();
-        let report = Report::from_frames(&owned);
+        let report = Report::from_frames_with_running(&owned, running);
 
         println!(
             "{}  {}",
@@ -139,7 +236,7 @@ pub(crate) fn print_report_grouped(frames: &[Frame], show_total: bool) {
         );
         println!();
 
-        print_project_breakdown(&report);
+        print_project_breakdown(&report, separator);
         println!();
     }
 
@@ -152,6 +249,142 @@ pub(crate) fn print_report_grouped(frames: &[Frame], show_total: bool) {
     }
 }
 
+/// Prints the `stats` summary, then each project's hours per week of the trend.
+pub(crate) fn print_stats(stats: &Stats) {
+    let row = |label: &str, value: String| println!("{}  {value}", format!("{label:<16}").bold());
+    row(
+        "Tracked",
+        format!(
+            "{} in {} over {}",
+            fmt_duration(stats.total).magenta().bold(),
+            plural(stats.frame_count as i64, "frame"),
+            plural(stats.days_tracked as i64, "day"),
+        ),
+    );
+    row(
+        "Daily average",
+        fmt_duration(stats.daily_average).magenta().to_string(),
+    );
+    row(
+        "Frame average",
+        fmt_duration(stats.frame_average).magenta().to_string(),
+    );
+    if let Some(streak) = stats.longest_streak {
+        row(
+            "Longest streak",
+            format!(
+                "{} {}",
+                plural(streak.days(), "day"),
+                format!(
+                    "({} – {})",
+                    streak.first.format("%d %b %Y"),
+                    streak.last.format("%d %b %Y")
+                )
+                .bright_black(),
+            ),
+        );
+    }
+    if let Some((day, total)) = stats.busiest_weekday() {
+        row(
+            "Busiest weekday",
+            format!(
+                "{} {}",
+                weekday_name(day),
+                format!("({})", fmt_duration(total)).bright_black()
+            ),
+        );
+    }
+    row("Switches/day", format!("{:.1}", stats.switches_per_day));
+
+    if stats.trends.is_empty() {
+        return;
+    }
+    println!();
+    let weeks: String = stats
+        .trend_weeks
+        .iter()
+        .map(|w| format!("{:>8}", w.format("%d %b")))
+        .collect();
+    println!(
+        "{}{}",
+        format!("{:<20}", "Weekly trend").bold(),
+        weeks.bright_black()
+    );
+    for trend in &stats.trends {
+        let hours: String = trend
+            .weeks
+            .iter()
+            .map(|d| format!("{:>7.1}h", d.num_seconds() as f64 / 3600.0))
+            .collect();
+        println!(
+            "  {}{}",
+            format!("{:<18}", trend.project).yellow().bold(),
+            hours.magenta(),
+        );
+    }
+}
+
+/// `1 day`, `2 days`.
+fn plural(n: i64, noun: &str) -> String {
+    if n == 1 {
+        format!("{n} {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+pub(crate) fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// Prints a `report --group-by` pivot as an indented tree, followed by the total.
+pub(crate) fn print_pivot(frames: &[Frame], nodes: &[PivotNode]) {
+    for node in nodes {
+        print_pivot_node(node, 0);
+    }
+    let total = frames
+        .iter()
+        .fold(Duration::zero(), |acc, f| acc + (f.end - f.start));
+    println!();
+    println!(
+        "{}  {}",
+        "Total".bold().white(),
+        fmt_duration(total).magenta().bold(),
+    );
+}
+
+fn print_pivot_node(node: &PivotNode, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let width = 28usize.saturating_sub(indent.len()).max(1);
+    let label = format!("{:<width$}", node.label);
+    let label = match node.dimension {
+        Dimension::Epic => label.cyan().bold().to_string(),
+        Dimension::Project => label.yellow().bold().to_string(),
+        Dimension::Tag => label.cyan().to_string(),
+        Dimension::Day | Dimension::Week | Dimension::Weekday => label.white().bold().to_string(),
+    };
+    println!(
+        "{indent}{label}  {}{}",
+        fmt_duration(node.total).magenta().bold(),
+        if node.running {
+            running_marker()
+        } else {
+            String::new()
+        },
+    );
+    for child in &node.children {
+        print_pivot_node(child, depth + 1);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
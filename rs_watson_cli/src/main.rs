@@ -1,3 +1,4 @@
+mod chart;
 mod commands;
 mod epic;
 mod format;
@@ -12,7 +13,7 @@ use rs_watson::Watson;
 
 use rs_watson::config::{Config, StorageProvider};
 
-use crate::commands::{Commands, cmd_init, dispatch};
+use crate::commands::{Commands, cmd_config_unloaded, cmd_init, dispatch};
 
 #[cfg(feature = "storage-json")]
 use rs_watson_storage::json::JsonStorage;
@@ -45,17 +46,16 @@ fn run() -> Result<()> {
         return Ok(());
     }
 
-    let config = Config::load()?;
-
-    let data_dir = if let Ok(dir) = std::env::var("RS_WATSON_DATA_DIR") {
-        std::path::PathBuf::from(dir)
-    } else if let Some(dir) = &config.storage.data_dir {
-        std::path::PathBuf::from(dir)
-    } else {
-        dirs::data_dir()
-            .context("Could not determine data directory")?
-            .join("rs_watson")
+    let config = match Config::load() {
+        Ok(config) => config,
+        // `watson config` must still work to repair a broken file.
+        Err(e) => match cli.command {
+            Commands::Config { action } => return cmd_config_unloaded(action, e),
+            _ => return Err(e.into()),
+        },
     };
+
+    let data_dir = config.data_dir()?;
     std::fs::create_dir_all(&data_dir)
         .with_context(|| format!("Could not create data directory: {}", data_dir.display()))?;
 
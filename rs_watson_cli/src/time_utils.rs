@@ -15,13 +15,7 @@ pub(crate) fn parse_date(input: &str, week_start: WeekStart) -> Result<NaiveDate
     match input.trim().to_lowercase().as_str() {
         "today" => Ok(today),
         "yesterday" => Ok(today - Duration::days(1)),
-        "week" => {
-            let days_back = match week_start {
-                WeekStart::Monday => today.weekday().num_days_from_monday() as i64,
-                WeekStart::Sunday => today.weekday().num_days_from_sunday() as i64,
-            };
-            Ok(today - Duration::days(days_back))
-        }
+        "week" => Ok(week_start.start_of_week(today)),
         "month" => Ok(today.with_day(1).expect("day 1 always valid")),
         s => NaiveDate::parse_from_str(s, "%Y-%m-%d").with_context(|| {
             format!("Invalid date \"{s}\", expected YYYY-MM-DD or: today, yesterday, week, month")
@@ -129,6 +123,18 @@ pub(crate) fn prompt_time(prompt: &str, default: DateTime<Utc>) -> Result<DateTi
     }
 }
 
+/// Like [`parse_at`], but a bare `HH:MM[:SS]` keeps the local date of `reference`
+/// instead of today — used when entering times that belong to an existing frame.
+pub(crate) fn parse_at_on(input: &str, reference: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let s = input.trim();
+    let time =
+        NaiveTime::parse_from_str(s, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"));
+    match time {
+        Ok(t) => local_naive_to_utc(reference.with_timezone(&Local).date_naive().and_time(t)),
+        Err(_) => parse_at(s),
+    }
+}
+
 pub(crate) fn check_future(dt: DateTime<Utc>, config: &Config) -> Result<()> {
     if !config.behavior.allow_future_times && dt > Utc::now() {
         anyhow::bail!(
@@ -146,6 +152,7 @@ mod tests {
 
     fn cfg(allow_future: bool) -> Config {
         Config {
+            version: Config::CURRENT_VERSION,
             storage: StorageConfig {
                 provider: Default::default(),
                 data_dir: None,
@@ -153,9 +160,21 @@ mod tests {
             behavior: BehaviorConfig {
                 allow_future_times: allow_future,
                 week_start: WeekStart::Monday,
+                max_frame_hours: 0,
+                ignore_case: false,
+                strict_projects: false,
+                strict_tags: false,
+                allowed_projects: vec![],
+                allowed_tags: vec![],
+                project_separator: None,
             },
             log: LogConfig::default(),
+            working_hours: Default::default(),
+            aliases: Default::default(),
+            git: Default::default(),
+            hooks: Default::default(),
             epics: vec![],
+            context: None,
         }
     }
 
@@ -265,6 +284,30 @@ mod tests {
         assert!(parse_at("08:30:00").is_ok());
     }
 
+    #[test]
+    fn parse_at_on_keeps_reference_date_for_bare_time() {
+        let reference = parse_at("2026-03-02 08:00").unwrap();
+        let dt = parse_at_on("13:15", reference).unwrap();
+        assert_eq!(
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2026-03-02 13:15"
+        );
+    }
+
+    #[test]
+    fn parse_at_on_accepts_full_datetime() {
+        let reference = parse_at("2026-03-02 08:00").unwrap();
+        let dt = parse_at_on("2026-03-05 10:00", reference).unwrap();
+        assert_eq!(
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2026-03-05 10:00"
+        );
+    }
+
     #[test]
     fn check_future_past_time_always_ok() {
         let past = Utc::now() - Duration::hours(1);
@@ -62,6 +62,7 @@ fn statusline_when_tracking_outputs_project_and_elapsed_time() {
 }
 
 #[test]
+#[allow(clippy::trim_split_whitespace)]
 fn statusline_includes_completed_frames_in_total() {
     let dir = TempDir::new().unwrap();
     watson(&dir)
@@ -308,6 +309,47 @@ fn report_includes_active_frame() {
         .stdout(contains("live"));
 }
 
+#[test]
+fn today_marks_active_frame_as_running() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir).args(["start", "live"]).assert().success();
+    watson(&dir)
+        .args(["today"])
+        .assert()
+        .success()
+        .stdout(contains("running"));
+}
+
+#[test]
+fn today_no_running_excludes_active_frame() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir).args(["start", "live"]).assert().success();
+    watson(&dir)
+        .args(["today", "--no-running"])
+        .assert()
+        .success()
+        .stdout(contains("No frames recorded today"));
+}
+
+#[test]
+fn report_no_running_excludes_active_frame() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "done", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["start", "live", "--at", "09:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["report", "--no-running"])
+        .assert()
+        .success()
+        .stdout(contains("done"))
+        .stdout(contains("live").not());
+}
+
 #[test]
 fn log_includes_active_frame() {
     let dir = TempDir::new().unwrap();
@@ -369,6 +411,169 @@ fn report_with_date_range_filters_frames() {
         .stdout(contains("backend"));
 }
 
+// --- exclusions / queries ---
+
+fn add_filter_frames(dir: &TempDir) {
+    for (project, tags, from, to) in [
+        ("acme", "api", "08:00", "09:00"),
+        ("acme", "db,meeting", "09:00", "10:00"),
+        ("acme", "ui", "10:00", "11:00"),
+        ("globex", "api", "11:00", "12:00"),
+    ] {
+        let mut cmd = watson(dir);
+        cmd.args(["add", project, "--from", from, "--to", to]);
+        for tag in tags.split(',') {
+            cmd.args(["-t", tag]);
+        }
+        cmd.assert().success();
+    }
+}
+
+#[test]
+fn log_query_combines_and_or_not() {
+    let dir = TempDir::new().unwrap();
+    add_filter_frames(&dir);
+    watson(&dir)
+        .args([
+            "log",
+            "--query",
+            "project:acme and (tag:api or tag:db) and not tag:meeting",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("[api]"))
+        .stdout(contains("meeting").not())
+        .stdout(contains("[ui]").not())
+        .stdout(contains("globex").not());
+}
+
+#[test]
+fn report_any_tag_multiple_projects_and_exclusions() {
+    let dir = TempDir::new().unwrap();
+    add_filter_frames(&dir);
+    watson(&dir)
+        .args([
+            "report",
+            "acme",
+            "globex",
+            "--any-tag",
+            "ui",
+            "--any-tag",
+            "db",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("acme"))
+        .stdout(contains("globex").not())
+        .stdout(contains("2h 0m 0s"));
+    watson(&dir)
+        .args([
+            "today",
+            "--exclude-project",
+            "globex",
+            "--exclude-tag",
+            "meeting",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("acme"))
+        .stdout(contains("2h 0m 0s"))
+        .stdout(contains("globex").not());
+}
+
+#[test]
+fn invalid_query_reports_the_column() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["log", "-q", "tag:api and"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid query at column 12"));
+}
+
+// --- stats ---
+
+#[test]
+fn stats_shows_averages_switches_and_trends() {
+    let dir = TempDir::new().unwrap();
+    add_filter_frames(&dir);
+    watson(&dir)
+        .args(["stats", "--weeks", "2"])
+        .assert()
+        .success()
+        .stdout(contains("4h 0m 0s"))
+        .stdout(contains("in 4 frames over 1 day"))
+        .stdout(contains("1.0"))
+        .stdout(contains("Weekly trend"))
+        .stdout(contains("3.0h"));
+    watson(&dir)
+        .args(["stats", "--exclude-project", "acme"])
+        .assert()
+        .success()
+        .stdout(contains("1h 0m 0s"))
+        .stdout(contains("acme").not());
+}
+
+#[test]
+fn stats_rejects_weeks_out_of_range() {
+    let dir = TempDir::new().unwrap();
+    for weeks in ["0", "53", "100000000"] {
+        watson(&dir)
+            .args(["stats", "--weeks", weeks])
+            .assert()
+            .failure()
+            .stderr(contains("1..=52"));
+    }
+}
+
+#[test]
+fn stats_when_empty_says_no_frames() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(contains("No frames recorded."));
+}
+
+// --- search ---
+
+#[test]
+fn search_ranks_matches_and_tolerates_typos() {
+    let dir = TempDir::new().unwrap();
+    add_filter_frames(&dir);
+    let out = watson(&dir).args(["search", "api"]).output().unwrap();
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(out.status.success());
+    let (globex, acme) = (stdout.find("globex").unwrap(), stdout.find("acme").unwrap());
+    assert!(globex < acme, "most recent match first:\n{stdout}");
+    assert!(!stdout.contains("[ui]"));
+
+    watson(&dir)
+        .args(["search", "acme", "meetng"])
+        .assert()
+        .success()
+        .stdout(contains("meeting"))
+        .stdout(contains("[api]").not());
+}
+
+#[test]
+fn search_with_no_matches_says_so() {
+    let dir = TempDir::new().unwrap();
+    add_filter_frames(&dir);
+    watson(&dir)
+        .args(["search", "zzz"])
+        .assert()
+        .success()
+        .stdout(contains("No frames match."));
+    watson(&dir)
+        .args(["search", "api", "--limit", "1"])
+        .assert()
+        .success()
+        .stdout(contains("globex"))
+        .stdout(contains("acme").not());
+}
+
 // --- rename ---
 
 #[test]
@@ -650,6 +855,159 @@ fn export_timestamps_use_z_suffix() {
         .stdout(predicates::str::contains("+00:00").not());
 }
 
+#[test]
+fn export_list_shows_formats_and_options() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["export", "--list"])
+        .assert()
+        .success()
+        .stdout(contains("csv"))
+        .stdout(contains("json"))
+        .stdout(contains("delimiter="));
+}
+
+#[test]
+fn export_json_with_option() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["export", "--format", "json", "--option", "pretty=true"])
+        .assert()
+        .success()
+        .stdout(contains("  {"))
+        .stdout(contains("\"project\": \"backend\""));
+}
+
+#[test]
+fn export_rejects_unknown_formats_and_options() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["export", "--format", "xml"])
+        .assert()
+        .failure()
+        .stderr(contains("available: csv, json"));
+    watson(&dir)
+        .args(["export", "--option", "colour=red"])
+        .assert()
+        .failure()
+        .stderr(contains("colour"));
+}
+
+#[test]
+#[cfg(unix)]
+fn export_uses_external_exporters_on_path() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let bin = TempDir::new().unwrap();
+    let script = bin.path().join("watson-export-count");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\ngrep -o '\"project\"' | wc -l | tr -d ' '\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap()
+    );
+
+    watson(&dir)
+        .args(["add", "backend", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["add", "frontend", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .env("PATH", &path)
+        .args(["export", "--list"])
+        .assert()
+        .success()
+        .stdout(contains("count"));
+    watson(&dir)
+        .env("PATH", &path)
+        .args(["export", "--format", "count"])
+        .assert()
+        .success()
+        .stdout("2\n");
+}
+
+#[test]
+fn export_filters_like_log_and_report() {
+    let dir = TempDir::new().unwrap();
+    for (project, tag, from, to) in [
+        ("backend", "api", "08:00", "09:00"),
+        ("backend", "meeting", "09:00", "10:00"),
+        ("frontend", "ui", "10:00", "11:00"),
+        ("docs", "api", "11:00", "12:00"),
+    ] {
+        watson(&dir)
+            .args(["add", project, "-t", tag, "--from", from, "--to", to])
+            .assert()
+            .success();
+    }
+    watson(&dir)
+        .args(["export", "backend", "--exclude-tag", "meeting"])
+        .assert()
+        .success()
+        .stdout(contains("api"))
+        .stdout(contains("meeting").not());
+    watson(&dir)
+        .args(["export", "-t", "api", "--exclude-project", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("backend"))
+        .stdout(contains("docs").not());
+}
+
+#[test]
+fn export_epic_filter_and_csv_columns() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "\n[[epics]]\nname = \"Platform\"\nprojects = [\"backend\", \"infra\"]\n",
+    );
+    for (project, from, to) in [
+        ("backend", "08:00", "09:30"),
+        ("infra", "09:30", "10:00"),
+        ("frontend", "10:00", "11:00"),
+    ] {
+        watson(&dir)
+            .args(["add", project, "--from", from, "--to", to])
+            .assert()
+            .success();
+    }
+    watson(&dir)
+        .args([
+            "export",
+            "--epic",
+            "Platform",
+            "--option",
+            "columns=epic,project,duration_hours",
+            "--option",
+            "delimiter=;",
+        ])
+        .assert()
+        .success()
+        .stdout("epic;project;duration_hours\nPlatform;backend;1.50\nPlatform;infra;0.50\n");
+    watson(&dir)
+        .args(["export", "--epic", "Nope"])
+        .assert()
+        .failure()
+        .stderr(contains("Epic \"Nope\" not found"));
+}
+
 // --- import ---
 
 #[test]
@@ -741,6 +1099,75 @@ fn epics_lists_configured_epics() {
         .stdout(contains("Refactoring"));
 }
 
+#[test]
+fn epics_add_edit_remove_preserve_comments() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "# keep me\n");
+    watson(&dir)
+        .args(["add", "backend", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .success();
+
+    watson(&dir)
+        .args(["epics", "add", "Backend", "-p", "backend", "-t", "api"])
+        .assert()
+        .success()
+        .stdout(contains("Added epic"));
+    watson(&dir)
+        .args([
+            "epics",
+            "edit",
+            "Backend",
+            "--rename",
+            "API",
+            "--priority",
+            "3",
+        ])
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(content.contains("# keep me"));
+    assert!(
+        content.contains("name = \"API\"\nproject = \"backend\"\ntags = [\"api\"]\npriority = 3")
+    );
+
+    watson(&dir)
+        .args(["epics"])
+        .assert()
+        .success()
+        .stdout(contains("API"));
+    watson(&dir)
+        .args(["epics", "remove", "API"])
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(content.contains("# keep me"));
+    assert!(!content.contains("[[epics]]"));
+}
+
+#[test]
+fn epics_add_rejects_unknown_project_unless_forced() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["epics", "add", "Backend", "-p", "bakend"])
+        .assert()
+        .failure()
+        .stderr(contains("did you mean \"backend\""));
+    watson(&dir)
+        .args(["epics", "add", "Backend", "-p", "bakend", "--force"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["epics", "add", "Backend", "--glob", "back*"])
+        .assert()
+        .failure()
+        .stderr(contains("already exists"));
+}
+
 #[test]
 fn epics_shows_message_when_none_configured() {
     let data = TempDir::new().unwrap();
@@ -824,14 +1251,1013 @@ fn report_epic_fails_without_configured_epics() {
         .stderr(contains("No epics configured"));
 }
 
-// --- invalid time input ---
+#[test]
+fn report_group_by_nests_epic_and_tag() {
+    let data = TempDir::new().unwrap();
+    let cfg = TempDir::new().unwrap();
+    std::fs::write(cfg.path().join("config.toml"), EPIC_CONFIG).unwrap();
+
+    watson_cfg(&data, &cfg)
+        .args([
+            "add", "backend", "-t", "refactor", "--from", "08:00", "--to", "09:00",
+        ])
+        .assert()
+        .success();
+    watson_cfg(&data, &cfg)
+        .args(["add", "frontend", "--from", "09:00", "--to", "09:30"])
+        .assert()
+        .success();
+
+    let out = watson_cfg(&data, &cfg)
+        .args(["report", "--group-by", "epic,tag"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    let refactoring = out.find("Refactoring").unwrap();
+    let refactor_tag = out.find("refactor ").unwrap();
+    let unassigned = out.find("Unassigned").unwrap();
+    assert!(refactoring < refactor_tag && refactor_tag < unassigned);
+    assert!(out.contains("(no tags)"));
+    assert!(out.contains("Total"));
+}
 
 #[test]
-fn start_rejects_invalid_at_format() {
+fn report_group_by_rejects_unknown_dimension() {
     let dir = TempDir::new().unwrap();
     watson(&dir)
-        .args(["start", "backend", "--at", "invalid"])
+        .args(["report", "--group-by", "project,month"])
+        .assert()
+        .failure()
+        .stderr(contains("unknown dimension"));
+}
+
+#[test]
+fn report_chart_fits_width_and_honours_no_color() {
+    let dir = TempDir::new().unwrap();
+    add_filter_frames(&dir);
+    let out = watson(&dir)
+        .args(["report", "--chart", "acme"])
+        .env("NO_COLOR", "1")
+        .env("COLUMNS", "50")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    assert!(out.status.success());
+    assert!(!stdout.contains('\x1b'), "colored output:\n{stdout}");
+    assert!(stdout.lines().all(|l| l.chars().count() <= 50));
+    assert!(stdout.contains("acme  ████"));
+    assert!(stdout.contains("100.0%"));
+    assert!(!stdout.contains("globex"));
+
+    for kind in ["days", "weekdays", "calendar"] {
+        watson(&dir)
+            .args(["report", &format!("--chart={kind}")])
+            .env("NO_COLOR", "1")
+            .assert()
+            .success()
+            .stdout(contains("█"));
+    }
+}
+
+#[test]
+fn report_chart_rejects_unknown_kind() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["report", "--chart=pie"])
+        .assert()
+        .failure()
+        .stderr(contains("unknown chart"));
+}
+
+// --- idle detection ---
+
+fn hours_ago(h: i64) -> String {
+    (chrono::Local::now() - chrono::Duration::hours(h))
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+#[test]
+fn status_warns_about_frame_past_max_frame_hours() {
+    let data = TempDir::new().unwrap();
+    let cfg = TempDir::new().unwrap();
+    std::fs::write(
+        cfg.path().join("config.toml"),
+        "[behavior]\nmax_frame_hours = 2\n",
+    )
+    .unwrap();
+
+    watson_cfg(&data, &cfg)
+        .args(["start", "backend", "--at", &hours_ago(5)])
+        .assert()
+        .success();
+    watson_cfg(&data, &cfg)
+        .args(["status"])
+        .assert()
+        .success()
+        .stdout(contains("longer than max_frame_hours"))
+        .stdout(contains("watson stop --at"));
+}
+
+#[test]
+fn status_does_not_warn_when_idle_detection_disabled() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["start", "backend", "--at", &hours_ago(5)])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["status"])
+        .assert()
+        .success()
+        .stdout(contains("max_frame_hours").not());
+}
+
+#[test]
+fn start_warns_before_replacing_overrun_frame() {
+    let data = TempDir::new().unwrap();
+    let cfg = TempDir::new().unwrap();
+    std::fs::write(
+        cfg.path().join("config.toml"),
+        "[behavior]\nmax_frame_hours = 2\n",
+    )
+    .unwrap();
+
+    watson_cfg(&data, &cfg)
+        .args(["start", "backend", "--at", &hours_ago(5)])
+        .assert()
+        .success();
+    watson_cfg(&data, &cfg)
+        .args(["start", "frontend"])
+        .assert()
+        .success()
+        .stdout(contains("longer than max_frame_hours"))
+        .stdout(contains("Starting"));
+}
+
+// --- invalid time input ---
+
+#[test]
+fn start_rejects_invalid_at_format() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["start", "backend", "--at", "invalid"])
         .assert()
         .failure()
         .stderr(contains("Invalid time"));
 }
+
+// --- split / merge ---
+
+/// Short IDs of the frames shown by `watson log`, oldest first.
+fn short_ids(dir: &TempDir) -> Vec<String> {
+    let out = watson(dir).args(["log"]).output().unwrap();
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    stdout
+        .lines()
+        .filter(|l| l.contains('→'))
+        .filter_map(|l| l.split_whitespace().last())
+        // Strip the colour codes around the ID: keep the 8-hex-digit run.
+        .filter_map(|sid| {
+            sid.rsplit(|c: char| !c.is_ascii_hexdigit())
+                .find(|s| s.len() == 8)
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+#[test]
+fn split_cuts_frame_in_two_with_new_project() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "09:00", "--to", "12:00"])
+        .assert()
+        .success();
+    let ids = short_ids(&dir);
+    watson(&dir)
+        .args(["split", &ids[0], "--at", "10:30", "-p", "frontend"])
+        .assert()
+        .success()
+        .stdout(contains("Split"));
+    watson(&dir).args(["log"]).assert().success().stdout(
+        contains("10:30:00")
+            .and(contains("backend"))
+            .and(contains("frontend")),
+    );
+    assert_eq!(short_ids(&dir).len(), 2);
+}
+
+#[test]
+fn split_rejects_time_outside_frame() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "09:00", "--to", "12:00"])
+        .assert()
+        .success();
+    let ids = short_ids(&dir);
+    watson(&dir)
+        .args(["split", &ids[0], "--at", "13:00"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn merge_combines_frames_of_same_project() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args([
+            "add", "backend", "--from", "09:00", "--to", "10:00", "-t", "api",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args([
+            "add", "backend", "--from", "10:15", "--to", "11:00", "-t", "db",
+        ])
+        .assert()
+        .success();
+    let ids = short_ids(&dir);
+    watson(&dir)
+        .args(["merge", &ids[0], &ids[1]])
+        .assert()
+        .success()
+        .stdout(contains("Merged").and(contains("api")).and(contains("db")));
+    assert_eq!(short_ids(&dir).len(), 1);
+}
+
+#[test]
+fn merge_rejects_different_projects() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["add", "frontend", "--from", "10:00", "--to", "11:00"])
+        .assert()
+        .success();
+    let ids = short_ids(&dir);
+    watson(&dir)
+        .args(["merge", &ids[0], &ids[1]])
+        .assert()
+        .failure()
+        .stderr(contains("different projects"));
+}
+
+// --- gaps ---
+
+#[test]
+fn gaps_lists_untracked_time_within_working_hours() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(
+        dir.path().join("config.toml"),
+        "[behavior]\nallow_future_times = true\n\n[working_hours]\nstart = \"09:00\"\nend = \"17:00\"\n",
+    )
+    .unwrap();
+    // 2025-01-15 is a Wednesday.
+    watson(&dir)
+        .args([
+            "add",
+            "backend",
+            "--from",
+            "2025-01-15 10:00",
+            "--to",
+            "2025-01-15 16:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["gaps", "--from", "2025-01-15", "--to", "2025-01-15"])
+        .assert()
+        .success()
+        .stdout(
+            contains("09:00:00")
+                .and(contains("10:00:00"))
+                .and(contains("16:00:00"))
+                .and(contains("17:00:00"))
+                .and(contains("2h 0m 0s untracked")),
+        );
+}
+
+#[test]
+fn gaps_skips_non_working_days() {
+    let dir = TempDir::new().unwrap();
+    // 2025-01-18 is a Saturday.
+    watson(&dir)
+        .args(["gaps", "--from", "2025-01-18", "--to", "2025-01-18"])
+        .assert()
+        .success()
+        .stdout(contains("No untracked time"));
+}
+
+// --- rename-tag / retag ---
+
+#[test]
+fn rename_tag_updates_frames() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args([
+            "add", "backend", "-t", "be", "--from", "09:00", "--to", "10:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["rename-tag", "be", "backend"])
+        .assert()
+        .success()
+        .stdout(contains("1 updated"));
+    watson(&dir)
+        .args(["tags"])
+        .assert()
+        .success()
+        .stdout(contains("backend").and(contains("be\n").not()));
+}
+
+#[test]
+fn rename_tag_to_itself_keeps_the_tag() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args([
+            "add", "backend", "-t", "api", "--from", "09:00", "--to", "10:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["rename-tag", "api", "api"])
+        .assert()
+        .success()
+        .stdout(contains("0 updated"));
+    watson(&dir)
+        .args(["tags"])
+        .assert()
+        .success()
+        .stdout(contains("api"));
+}
+
+#[test]
+fn rename_unknown_tag_fails() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["rename-tag", "ghost", "new"])
+        .assert()
+        .failure()
+        .stderr(contains("not found"));
+}
+
+#[test]
+fn retag_dry_run_does_not_save() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["retag", "backend", "--add", "api", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(contains("Would retag").and(contains("api")));
+    watson(&dir)
+        .args(["tags"])
+        .assert()
+        .success()
+        .stdout(contains("No tags recorded yet."));
+}
+
+#[test]
+fn retag_adds_and_removes_tags_on_matching_frames() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args([
+            "add", "backend", "-t", "mtg", "--from", "09:00", "--to", "10:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args([
+            "add", "frontend", "-t", "mtg", "--from", "10:00", "--to", "11:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["retag", "-t", "mtg", "--remove", "mtg", "--add", "meeting"])
+        .assert()
+        .success()
+        .stdout(contains("Retagged"));
+    watson(&dir)
+        .args(["tags"])
+        .assert()
+        .success()
+        .stdout(contains("meeting").and(contains("mtg").not()));
+}
+
+#[test]
+fn retag_without_changes_requested_fails() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["retag", "backend"])
+        .assert()
+        .failure()
+        .stderr(contains("--add"));
+}
+
+// --- aliases / merge-projects / merge-tags ---
+
+fn write_config(dir: &TempDir, extra: &str) {
+    std::fs::write(
+        dir.path().join("config.toml"),
+        format!("[behavior]\nallow_future_times = true\n{extra}"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn add_maps_aliases_to_canonical_names() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "[aliases.projects]\nbackend = [\"be\"]\n[aliases.tags]\nmeeting = [\"mtg\"]\n",
+    );
+    watson(&dir)
+        .args(["add", "be", "-t", "mtg", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success()
+        .stdout(contains("backend").and(contains("meeting")));
+}
+
+#[test]
+fn merge_projects_into_existing_project() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "Backend", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["add", "backend", "--from", "10:00", "--to", "11:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["merge-projects", "Backend", "--into", "backend"])
+        .assert()
+        .success()
+        .stdout(contains("1 updated"));
+    watson(&dir)
+        .args(["projects"])
+        .assert()
+        .success()
+        .stdout(contains("Backend").not());
+}
+
+#[test]
+fn merge_projects_without_arguments_applies_configured_aliases() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "be", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    write_config(&dir, "[aliases.projects]\nbackend = [\"be\", \"BE\"]\n");
+    watson(&dir)
+        .args(["merge-projects"])
+        .assert()
+        .success()
+        .stdout(contains("backend"));
+    watson(&dir)
+        .args(["projects"])
+        .assert()
+        .success()
+        .stdout(contains("backend").and(contains("be\n").not()));
+}
+
+#[test]
+fn merge_tags_replaces_sources() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "a", "-t", "mtg", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["merge-tags", "mtg", "--into", "meeting"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["tags"])
+        .assert()
+        .success()
+        .stdout(contains("meeting").and(contains("mtg").not()));
+}
+
+#[test]
+fn log_filter_ignores_case_when_configured() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "ignore_case = true\n");
+    watson(&dir)
+        .args(["add", "Backend", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["log", "backend"])
+        .assert()
+        .success()
+        .stdout(contains("Backend"));
+}
+
+#[test]
+fn archived_project_is_hidden_from_projects() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "legacy", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["project", "legacy", "--archive"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["projects"])
+        .assert()
+        .success()
+        .stdout(contains("legacy").not());
+    watson(&dir)
+        .args(["projects", "--all"])
+        .assert()
+        .success()
+        .stdout(contains("legacy").and(contains("archived")));
+    watson(&dir)
+        .args(["log"])
+        .assert()
+        .success()
+        .stdout(contains("legacy"));
+}
+
+#[test]
+fn project_metadata_is_shown() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args([
+            "project",
+            "research",
+            "--description",
+            "Spike work",
+            "--client",
+            "Acme",
+            "--color",
+            "#3366FF",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["project", "research"])
+        .assert()
+        .success()
+        .stdout(
+            contains("Spike work")
+                .and(contains("Acme"))
+                .and(contains("#3366ff")),
+        );
+    watson(&dir)
+        .args(["projects"])
+        .assert()
+        .success()
+        .stdout(contains("research"));
+}
+
+#[test]
+fn project_rejects_invalid_color() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["project", "research", "--color", "blue"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid color"));
+}
+
+#[test]
+fn strict_projects_rejects_typo_with_suggestion() {
+    let dir = TempDir::new().unwrap();
+    watson(&dir)
+        .args(["add", "backend", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    write_config(&dir, "strict_projects = true\n");
+    watson(&dir)
+        .args(["start", "bakcend"])
+        .assert()
+        .failure()
+        .stderr(contains("Unknown project \"bakcend\"").and(contains("did you mean \"backend\"")));
+    watson(&dir).args(["start", "backend"]).assert().success();
+}
+
+#[test]
+fn strict_tags_accepts_allow_listed_tags() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "strict_tags = true\nallowed_tags = [\"meeting\"]\n");
+    watson(&dir)
+        .args([
+            "add", "backend", "-t", "meeting", "--from", "09:00", "--to", "10:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args([
+            "add", "backend", "-t", "meetnig", "--from", "10:00", "--to", "11:00",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("Unknown tag \"meetnig\""));
+}
+
+#[test]
+fn project_separator_filters_subtree_and_rolls_up_report() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "project_separator = \"/\"\n");
+    for (project, from, to) in [
+        ("acme/website", "09:00", "10:00"),
+        ("acme/api", "10:00", "11:30"),
+        ("internal", "11:30", "12:00"),
+    ] {
+        watson(&dir)
+            .args(["add", project, "--from", from, "--to", to])
+            .assert()
+            .success();
+    }
+    watson(&dir)
+        .args(["log", "acme"])
+        .assert()
+        .success()
+        .stdout(
+            contains("acme/website")
+                .and(contains("acme/api"))
+                .and(contains("internal").not()),
+        );
+    watson(&dir)
+        .args(["report", "--from", "today"])
+        .assert()
+        .success()
+        .stdout(
+            contains("acme")
+                .and(contains("2h 30m"))
+                .and(contains("website")),
+        );
+}
+
+#[test]
+fn rename_moves_project_subtree() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "project_separator = \"/\"\n");
+    watson(&dir)
+        .args(["add", "acme/website", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["rename", "acme", "globex"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["projects"])
+        .assert()
+        .success()
+        .stdout(contains("globex/website").and(contains("acme").not()));
+}
+
+#[test]
+fn epics_explain_shows_matching_rule() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "[[epics]]\nname = \"Acme\"\nproject_glob = \"acme-*\"\nexclude_tags = [\"wip\"]\n\n\
+         [[epics]]\nname = \"Urgent\"\nprojects = [\"acme-web\", \"docs\"]\npriority = 5\n",
+    );
+    watson(&dir)
+        .args(["add", "acme-web", "--from", "09:00", "--to", "10:00"])
+        .assert()
+        .success();
+    let id = short_ids(&dir).remove(0);
+    watson(&dir)
+        .args(["epics", "--explain", &id])
+        .assert()
+        .success()
+        .stdout(
+            contains("matches glob \"acme-*\"")
+                .and(contains("matches project \"acme-web\""))
+                .and(contains("Epic:"))
+                .and(contains("Urgent")),
+        );
+}
+
+#[test]
+fn invalid_epic_regex_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "[[epics]]\nname = \"Broken\"\nproject_regex = \"(\"\n",
+    );
+    watson(&dir)
+        .args(["epics"])
+        .assert()
+        .failure()
+        .stderr(contains("Invalid project_regex in epic \"Broken\""));
+}
+
+// --- config ---
+
+#[test]
+fn config_set_get_and_unset() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "# my settings\n");
+    watson(&dir)
+        .args(["config", "set", "behavior.week_start", "sunday"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["config", "get", "behavior.week_start"])
+        .assert()
+        .success()
+        .stdout("sunday\n");
+    let content = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(content.contains("# my settings"));
+
+    watson(&dir)
+        .args(["config", "unset", "behavior.week_start"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["config", "get", "behavior.week_start"])
+        .assert()
+        .success()
+        .stdout("monday\n");
+    watson(&dir)
+        .args(["config", "set", "behavior.week_strat", "sunday"])
+        .assert()
+        .failure()
+        .stderr(contains("Unknown config key"));
+}
+
+#[test]
+fn config_show_resolves_data_dir_override() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "[storage]\ndata_dir = \"/somewhere/else\"\n");
+    watson(&dir)
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(contains(dir.path().display().to_string()).and(contains("/somewhere/else").not()));
+}
+
+#[test]
+fn config_validate_warns_about_unknown_keys_and_epic_projects() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "week_strat = \"sunday\"\n\n[[epics]]\nname = \"Web\"\nproject = \"website\"\n",
+    );
+    watson(&dir)
+        .args(["config", "validate"])
+        .assert()
+        .success()
+        .stdout(
+            contains("Unknown key \"behavior.week_strat\"")
+                .and(contains("Epic \"Web\": Unknown project \"website\"")),
+        );
+}
+
+#[test]
+fn config_validate_and_set_work_on_a_broken_file() {
+    let dir = TempDir::new().unwrap();
+    write_config(&dir, "max_frame_hours = \"lots\"\n");
+    watson(&dir).args(["status"]).assert().failure();
+    watson(&dir)
+        .args(["config", "validate"])
+        .assert()
+        .failure()
+        .stdout(contains("max_frame_hours"));
+    watson(&dir)
+        .args(["config", "set", "behavior.max_frame_hours", "8"])
+        .assert()
+        .success();
+    watson(&dir).args(["status"]).assert().success();
+}
+
+#[test]
+fn old_config_is_migrated_with_a_backup_on_the_next_edit() {
+    let dir = TempDir::new().unwrap();
+    let old = "epics = [{ name = \"Web\", project = \"website\" }]\n\n[behavior]\nallow_future_times = true\n";
+    std::fs::write(dir.path().join("config.toml"), old).unwrap();
+    watson(&dir)
+        .args(["epics"])
+        .assert()
+        .success()
+        .stdout(contains("Web"));
+    let content = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert_eq!(content, old, "reading the config leaves it alone");
+    assert!(!dir.path().join("config.toml.bak").exists());
+
+    watson(&dir)
+        .args(["config", "set", "behavior.max_frame_hours", "8"])
+        .assert()
+        .success();
+    let content = std::fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(content.contains("version = 1"));
+    assert!(content.contains("[[epics]]"));
+    let backup = std::fs::read_to_string(dir.path().join("config.toml.bak")).unwrap();
+    assert_eq!(backup, old);
+}
+
+// --- .watson.toml ---
+
+#[test]
+fn start_without_project_uses_the_nearest_watson_toml() {
+    let dir = TempDir::new().unwrap();
+    let repo = TempDir::new().unwrap();
+    let nested = repo.path().join("src/deep");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(
+        repo.path().join(".watson.toml"),
+        "tags = [\"client\"]\nepic = \"Acme\"\n\n[[epics]]\nname = \"Acme\"\nproject = \"acme-web\"\ntags = [\"billable\"]\n",
+    )
+    .unwrap();
+
+    watson(&dir)
+        .current_dir(&nested)
+        .args(["start", "-t", "api", "--at", "08:00"])
+        .assert()
+        .success()
+        .stdout(contains("acme-web").and(contains("client, billable, api")));
+    watson(&dir)
+        .current_dir(&nested)
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(contains("layered with").and(contains("name = \"Acme\"")));
+}
+
+#[test]
+fn start_without_project_or_watson_toml_fails() {
+    let dir = TempDir::new().unwrap();
+    let empty = TempDir::new().unwrap();
+    watson(&dir)
+        .current_dir(empty.path())
+        .args(["start", "--at", "08:00"])
+        .assert()
+        .failure()
+        .stderr(contains("no .watson.toml found"));
+}
+
+#[test]
+#[cfg(unix)]
+fn watson_toml_cannot_define_hooks() {
+    let dir = TempDir::new().unwrap();
+    let repo = TempDir::new().unwrap();
+    let marker = repo.path().join("PWNED");
+    std::fs::write(
+        repo.path().join(".watson.toml"),
+        format!(
+            "project = \"website\"\n\n[[hooks.run]]\ncommand = \"touch '{}'\"\n",
+            marker.display()
+        ),
+    )
+    .unwrap();
+
+    watson(&dir)
+        .current_dir(repo.path())
+        .args(["start", "--at", "08:00"])
+        .assert()
+        .success()
+        .stdout(contains("website"));
+    assert!(!marker.exists());
+}
+
+// --- git ---
+
+fn git(repo: &std::path::Path, args: &[&str], date: &str) {
+    let status = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+fn git_repo(branch: &str) -> TempDir {
+    let repo = TempDir::new().unwrap();
+    let now = "2026-05-14T08:00:00";
+    git(repo.path(), &["init", "-q", "-b", branch], now);
+    git(repo.path(), &["config", "user.name", "Ada"], now);
+    git(
+        repo.path(),
+        &["config", "user.email", "ada@example.com"],
+        now,
+    );
+    repo
+}
+
+#[test]
+fn start_tags_the_ticket_from_the_git_branch() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "\n[git]\ntag_branch = true\nticket_regex = \"[A-Z]+-[0-9]+\"\n",
+    );
+    let repo = git_repo("feature/ABC-123-login");
+    watson(&dir)
+        .current_dir(repo.path())
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .args(["start", "backend", "-t", "api", "--at", "08:00"])
+        .assert()
+        .success()
+        .stdout(contains("api, ABC-123"));
+}
+
+#[test]
+fn log_commits_lists_commits_made_during_each_frame() {
+    let dir = TempDir::new().unwrap();
+    let repo = git_repo("main");
+    for (date, message) in [
+        ("2026-05-14T09:30:00", "Fix login redirect"),
+        ("2026-05-14T11:15:00", "Outside any frame"),
+    ] {
+        git(
+            repo.path(),
+            &["commit", "-q", "--allow-empty", "-m", message],
+            date,
+        );
+    }
+    watson(&dir)
+        .args([
+            "add",
+            "backend",
+            "--from",
+            "2026-05-14 09:00",
+            "--to",
+            "2026-05-14 10:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .current_dir(repo.path())
+        .env("GIT_CONFIG_GLOBAL", "/dev/null")
+        .args(["log", "--commits"])
+        .assert()
+        .success()
+        .stdout(contains("Fix login redirect").and(contains("Outside any frame").not()));
+}
+
+// --- hooks ---
+
+#[test]
+#[cfg(unix)]
+fn hooks_run_on_events_and_report_failures() {
+    let dir = TempDir::new().unwrap();
+    let journal = dir.path().join("journal");
+    write_config(
+        &dir,
+        &format!(
+            "\n[[hooks.run]]\non = [\"started\", \"stopped\"]\ncommand = \"echo $WATSON_EVENT $WATSON_PROJECT >> '{}'\"\n\n[[hooks.run]]\non = [\"stopped\"]\ncommand = \"echo slack down >&2; exit 1\"\n",
+            journal.display()
+        ),
+    );
+    watson(&dir)
+        .args(["start", "backend", "--at", "08:00"])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["stop", "--at", "09:00"])
+        .assert()
+        .success()
+        .stderr(contains("exited with status 1: slack down"));
+    assert_eq!(
+        std::fs::read_to_string(&journal).unwrap(),
+        "started backend\nstopped backend\n"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn aborting_hook_prevents_the_change() {
+    let dir = TempDir::new().unwrap();
+    write_config(
+        &dir,
+        "\n[hooks]\non_failure = \"abort\"\n\n[[hooks.run]]\non = [\"added\"]\ncommand = \"test -n \\\"$WATSON_TAGS\\\"\"\n",
+    );
+    watson(&dir)
+        .args(["add", "backend", "--from", "08:00", "--to", "09:00"])
+        .assert()
+        .failure()
+        .stderr(contains("failed on added"));
+    watson(&dir)
+        .args([
+            "add", "backend", "-t", "api", "--from", "08:00", "--to", "09:00",
+        ])
+        .assert()
+        .success();
+    watson(&dir)
+        .args(["log"])
+        .assert()
+        .success()
+        .stdout(contains("api"));
+}
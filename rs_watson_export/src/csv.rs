@@ -1,41 +1,204 @@
 use std::io::Write;
 
-use rs_watson::Frame;
-use thiserror::Error;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use rs_watson::config::EpicConfig;
+use rs_watson::{EpicResolver, Frame};
 
-use crate::Exporter;
+use crate::{ExportError, ExportOption, ExportOptions, Exporter};
 
-#[derive(Debug, Error)]
-pub enum CsvExportError {
-    #[error("CSV error: {0}")]
-    Csv(#[from] ::csv::Error),
+const DEFAULT_COLUMNS: &str = "id,project,tags,start,end,duration_seconds";
+
+const OPTIONS: &[ExportOption] = &[
+    ExportOption {
+        name: "delimiter",
+        description: "Field separator, a single character",
+        default: ",",
+    },
+    ExportOption {
+        name: "tag_separator",
+        description: "Separator between tags in the tags column",
+        default: "|",
+    },
+    ExportOption {
+        name: "columns",
+        description: "Columns in order, from id, project, tags, epic, start, end, \
+                      start_local, end_local, date, duration_seconds, duration_hours",
+        default: DEFAULT_COLUMNS,
+    },
+    ExportOption {
+        name: "header",
+        description: "Write a header row: true or false",
+        default: "true",
+    },
+];
+
+/// A column the CSV exporter can write; the header is its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Id,
+    Project,
+    Tags,
+    /// Epic name from `resolve_epic`, empty when no epic matches.
+    Epic,
+    /// RFC 3339 in UTC with a `Z` suffix.
+    Start,
+    End,
+    /// `YYYY-MM-DD HH:MM:SS` in local time.
+    StartLocal,
+    EndLocal,
+    /// Local date of the start.
+    Date,
+    DurationSeconds,
+    /// Hours as a decimal with two places, e.g. `1.50`.
+    DurationHours,
+}
+
+impl Column {
+    const ALL: [(&'static str, Column); 11] = [
+        ("id", Column::Id),
+        ("project", Column::Project),
+        ("tags", Column::Tags),
+        ("epic", Column::Epic),
+        ("start", Column::Start),
+        ("end", Column::End),
+        ("start_local", Column::StartLocal),
+        ("end_local", Column::EndLocal),
+        ("date", Column::Date),
+        ("duration_seconds", Column::DurationSeconds),
+        ("duration_hours", Column::DurationHours),
+    ];
+
+    fn parse_list(list: &str) -> Result<Vec<(&str, Column)>, ExportError> {
+        let invalid = |reason: String| ExportError::InvalidOption {
+            option: "columns".into(),
+            value: list.into(),
+            reason,
+        };
+        let columns = list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Self::ALL
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, c)| (name, *c))
+                    .ok_or_else(|| invalid(format!("unknown column \"{name}\"")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if columns.is_empty() {
+            return Err(invalid("no columns given".into()));
+        }
+        Ok(columns)
+    }
+}
+
+fn utc(dt: DateTime<Utc>) -> String {
+    dt.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn local(dt: DateTime<Utc>) -> String {
+    dt.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
 }
 
-pub struct CsvExporter;
+/// Writes one row per frame. The epics are only needed for the `epic` column.
+#[derive(Debug, Clone, Default)]
+pub struct CsvExporter {
+    epics: Vec<EpicConfig>,
+}
+
+impl CsvExporter {
+    /// Resolves the `epic` column against `epics`.
+    pub fn with_epics(mut self, epics: Vec<EpicConfig>) -> Self {
+        self.epics = epics;
+        self
+    }
+}
 
 impl Exporter for CsvExporter {
-    type Error = CsvExportError;
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    fn description(&self) -> &str {
+        "Comma-separated values, one row per frame"
+    }
+
+    fn options(&self) -> &[ExportOption] {
+        OPTIONS
+    }
 
-    fn export<W: Write>(&self, frames: &[Frame], writer: W) -> Result<(), Self::Error> {
-        let mut wtr = ::csv::Writer::from_writer(writer);
+    fn export(
+        &self,
+        frames: &[Frame],
+        options: &ExportOptions,
+        writer: &mut dyn Write,
+    ) -> Result<(), ExportError> {
+        options.check(self.name(), OPTIONS)?;
+        let delimiter = match options.get("delimiter").unwrap_or(",").as_bytes() {
+            [byte] => *byte,
+            _ => {
+                return Err(ExportError::InvalidOption {
+                    option: "delimiter".into(),
+                    value: options.get("delimiter").unwrap_or_default().into(),
+                    reason: "must be a single ASCII character".into(),
+                });
+            }
+        };
+        let tag_separator = options.get("tag_separator").unwrap_or("|");
+        let columns = Column::parse_list(options.get("columns").unwrap_or(DEFAULT_COLUMNS))?;
+        let header = match options.get("header").unwrap_or("true") {
+            "true" => true,
+            "false" => false,
+            other => {
+                return Err(ExportError::InvalidOption {
+                    option: "header".into(),
+                    value: other.into(),
+                    reason: "expected true or false".into(),
+                });
+            }
+        };
+        let resolver = EpicResolver::new(&self.epics);
 
-        wtr.write_record(["id", "project", "tags", "start", "end", "duration_seconds"])?;
+        let mut wtr = ::csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .from_writer(writer);
+
+        if header {
+            wtr.write_record(columns.iter().map(|(name, _)| name))?;
+        }
 
         for frame in frames {
-            wtr.write_record([
-                frame.id.to_string(),
-                frame.project.clone(),
-                frame.tags.join("|"),
-                frame
-                    .start
-                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-                frame.end.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-                (frame.end - frame.start).num_seconds().to_string(),
-            ])?;
+            let duration = frame.end - frame.start;
+            wtr.write_record(columns.iter().map(|(_, column)| {
+                match column {
+                    Column::Id => frame.id.to_string(),
+                    Column::Project => frame.project.clone(),
+                    Column::Tags => frame.tags.join(tag_separator),
+                    Column::Epic => resolver
+                        .resolve(frame)
+                        .map(|e| e.name.clone())
+                        .unwrap_or_default(),
+                    Column::Start => utc(frame.start),
+                    Column::End => utc(frame.end),
+                    Column::StartLocal => local(frame.start),
+                    Column::EndLocal => local(frame.end),
+                    Column::Date => frame.start.with_timezone(&Local).date_naive().to_string(),
+                    Column::DurationSeconds => duration.num_seconds().to_string(),
+                    Column::DurationHours => {
+                        format!("{:.2}", duration.num_seconds() as f64 / 3600.0)
+                    }
+                }
+            }))?;
         }
 
-        wtr.into_inner()
-            .map_err(|e| CsvExportError::Csv(e.into_error().into()))?;
+        wtr.flush()?;
         Ok(())
     }
 }
@@ -60,7 +223,9 @@ mod tests {
     fn csv_export_produces_header_and_rows() {
         let frames = vec![frame("backend", &["api", "auth"], 9, 10)];
         let mut buf = Vec::new();
-        CsvExporter.export(&frames, &mut buf).unwrap();
+        CsvExporter::default()
+            .export(&frames, &ExportOptions::default(), &mut buf)
+            .unwrap();
         let output = String::from_utf8(buf).unwrap();
         assert!(output.starts_with("id,project,tags,start,end,duration_seconds\n"));
         assert!(output.contains("backend"));
@@ -72,9 +237,91 @@ mod tests {
     fn csv_export_empty_tags_produces_empty_field() {
         let frames = vec![frame("backend", &[], 9, 10)];
         let mut buf = Vec::new();
-        CsvExporter.export(&frames, &mut buf).unwrap();
+        CsvExporter::default()
+            .export(&frames, &ExportOptions::default(), &mut buf)
+            .unwrap();
         let output = String::from_utf8(buf).unwrap();
         // tags column should be empty
         assert!(output.contains("backend,,"));
     }
+
+    #[test]
+    fn csv_export_honours_delimiter_and_tag_separator() {
+        let frames = vec![frame("backend", &["api", "auth"], 9, 10)];
+        let options = ExportOptions::parse(["delimiter=;", "tag_separator=+"]).unwrap();
+        let mut buf = Vec::new();
+        CsvExporter::default()
+            .export(&frames, &options, &mut buf)
+            .unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.starts_with("id;project;tags;"));
+        assert!(output.contains(";backend;api+auth;"));
+
+        let bad = ExportOptions::parse(["delimiter=;;"]).unwrap();
+        assert!(matches!(
+            CsvExporter::default().export(&frames, &bad, &mut Vec::new()),
+            Err(ExportError::InvalidOption { .. })
+        ));
+    }
+
+    #[test]
+    fn csv_export_selects_and_orders_columns() {
+        let frames = vec![
+            frame("backend", &["api"], 9, 10),
+            frame("docs", &[], 10, 12),
+        ];
+        let epics = vec![EpicConfig {
+            name: "Platform".into(),
+            project: Some("backend".into()),
+            ..Default::default()
+        }];
+        let options =
+            ExportOptions::parse(["columns=date,epic,project,duration_hours", "header=false"])
+                .unwrap();
+        let mut buf = Vec::new();
+        CsvExporter::default()
+            .with_epics(epics)
+            .export(&frames, &options, &mut buf)
+            .unwrap();
+        let date = frames[0].start.with_timezone(&Local).date_naive();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            format!("{date},Platform,backend,1.00\n{date},,docs,2.00\n")
+        );
+
+        let bad = ExportOptions::parse(["columns=project,client"]).unwrap();
+        let err = CsvExporter::default()
+            .export(&frames, &bad, &mut Vec::new())
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown column \"client\""),
+            "{err}"
+        );
+
+        for list in ["", " , "] {
+            let empty = ExportOptions::parse([format!("columns={list}")]).unwrap();
+            let err = CsvExporter::default()
+                .export(&frames, &empty, &mut Vec::new())
+                .unwrap_err();
+            assert!(err.to_string().contains("no columns given"), "{err}");
+        }
+    }
+
+    #[test]
+    fn csv_export_local_time_columns() {
+        let frames = vec![frame("backend", &[], 9, 10)];
+        let options = ExportOptions::parse(["columns=start_local,end_local"]).unwrap();
+        let mut buf = Vec::new();
+        CsvExporter::default()
+            .export(&frames, &options, &mut buf)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            format!(
+                "start_local,end_local\n{},{}\n",
+                local(frames[0].start),
+                local(frames[0].end)
+            )
+        );
+    }
 }
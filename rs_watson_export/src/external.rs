@@ -0,0 +1,278 @@
+//! Exporters provided by `watson-export-<name>` executables on `PATH`.
+//!
+//! The executable receives `{"options": {...}, "frames": [...]}` on stdin, with
+//! frames as written by the `json` exporter, and writes the export to stdout.
+//! A non-zero exit fails the export with the executable's stderr, and one that
+//! runs longer than its timeout is killed.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use rs_watson::Frame;
+use serde::Serialize;
+
+use crate::json::{FrameJson, frames_json};
+use crate::{ExportError, ExportOptions, Exporter};
+
+const PREFIX: &str = "watson-export-";
+
+/// How long an external exporter may run before it is killed.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Serialize)]
+struct Input<'a> {
+    options: BTreeMap<&'a str, &'a str>,
+    frames: Vec<FrameJson<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalExporter {
+    name: String,
+    path: PathBuf,
+    description: String,
+    timeout: Duration,
+}
+
+impl ExternalExporter {
+    /// An exporter called `name` backed by the executable at `path`.
+    pub fn new(name: impl Into<String>, path: PathBuf) -> Self {
+        let description = format!("External exporter ({})", path.display());
+        Self {
+            name: name.into(),
+            path,
+            description,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Kills the executable after `timeout` instead of [`DEFAULT_TIMEOUT`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn failed(&self, status: String, stderr: Vec<u8>) -> ExportError {
+        ExportError::External {
+            name: self.name.clone(),
+            status,
+            stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        }
+    }
+
+    /// Every `watson-export-<name>` executable on `PATH`. When several
+    /// directories provide the same name, the first one wins.
+    pub fn discover() -> Vec<Self> {
+        std::env::var_os("PATH")
+            .map(|paths| Self::discover_in(&paths))
+            .unwrap_or_default()
+    }
+
+    /// Like [`discover`](Self::discover), searching `paths` instead of `PATH`.
+    pub fn discover_in(paths: &OsStr) -> Vec<Self> {
+        let mut found: Vec<Self> = Vec::new();
+        for dir in std::env::split_paths(paths) {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            let mut here: Vec<Self> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| is_executable(path))
+                .filter_map(|path| {
+                    let stem = if cfg!(windows) {
+                        path.file_stem()
+                    } else {
+                        path.file_name()
+                    };
+                    let name = stem?.to_str()?.strip_prefix(PREFIX)?.to_string();
+                    (!name.is_empty()).then(|| Self::new(name, path.clone()))
+                })
+                .filter(|e| !found.iter().any(|f| f.name == e.name))
+                .collect();
+            here.sort_by(|a, b| a.name.cmp(&b.name));
+            here.dedup_by(|a, b| a.name == b.name);
+            found.extend(here);
+        }
+        found
+    }
+}
+
+/// Reads `pipe` to the end on a thread, so a full pipe cannot block the
+/// exporter and a process left holding it open cannot block us.
+fn read_on_thread(pipe: Option<impl Read + Send + 'static>) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    if let Some(mut pipe) = pipe {
+        std::thread::spawn(move || {
+            let mut out = Vec::new();
+            let _ = pipe.read_to_end(&mut out);
+            let _ = tx.send(out);
+        });
+    }
+    rx
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|ext| ["exe", "bat", "cmd"].contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+impl Exporter for ExternalExporter {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// External exporters do not declare one, so it is their name.
+    fn extension(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn export(
+        &self,
+        frames: &[Frame],
+        options: &ExportOptions,
+        writer: &mut dyn Write,
+    ) -> Result<(), ExportError> {
+        let input = serde_json::to_vec(&Input {
+            options: options.iter().collect(),
+            frames: frames_json(frames),
+        })?;
+        let mut child = Command::new(&self.path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // Feed stdin from a thread so an exporter that streams its output before
+        // reading all input cannot deadlock against us.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let feeder = std::thread::spawn(move || stdin.write_all(&input));
+        let stdout = read_on_thread(child.stdout.take());
+        let stderr = read_on_thread(child.stderr.take());
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                let status = format!("timed out after {}s", self.timeout.as_secs());
+                return Err(self.failed(status, Vec::new()));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        // Once the exporter has exited its pipes close, unless it left a
+        // background process holding them.
+        let wait = deadline
+            .saturating_duration_since(Instant::now())
+            .max(Duration::from_millis(100));
+        if !status.success() {
+            let stderr = stderr.recv_timeout(wait).unwrap_or_default();
+            return Err(self.failed(status.to_string(), stderr));
+        }
+        let Ok(output) = stdout.recv_timeout(wait) else {
+            let status = format!("timed out after {}s", self.timeout.as_secs());
+            return Err(self.failed(status, Vec::new()));
+        };
+        let fed = feeder.join().expect("stdin writer does not panic");
+        // An exporter may exit successfully without reading all of its input.
+        if let Err(e) = fed
+            && e.kind() != std::io::ErrorKind::BrokenPipe
+        {
+            return Err(e.into());
+        }
+        writer.write_all(&output)?;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn discovers_executables_by_prefix_first_path_wins() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        script(first.path(), "watson-export-toggl", "true");
+        script(second.path(), "watson-export-toggl", "true");
+        script(second.path(), "watson-export-ods", "true");
+        script(second.path(), "unrelated", "true");
+        std::fs::write(second.path().join("watson-export-notexec"), "").unwrap();
+
+        let paths = std::env::join_paths([first.path(), second.path()]).unwrap();
+        let found = ExternalExporter::discover_in(&paths);
+        let names: Vec<&str> = found.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["toggl", "ods"]);
+        assert!(found[0].path().starts_with(first.path()));
+    }
+
+    #[test]
+    fn export_pipes_json_through_the_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = script(dir.path(), "watson-export-echo", "cat");
+        let exporter = ExternalExporter::new("echo", path);
+        let options = ExportOptions::parse(["client=acme"]).unwrap();
+        let mut buf = Vec::new();
+        exporter.export(&[], &options, &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r#"{"options":{"client":"acme"},"frames":[]}"#
+        );
+    }
+
+    #[test]
+    fn failing_executable_reports_stderr() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = script(dir.path(), "watson-export-bad", "echo broken >&2; exit 2");
+        let err = ExternalExporter::new("bad", path)
+            .export(&[], &ExportOptions::default(), &mut Vec::new())
+            .unwrap_err();
+        assert!(err.to_string().ends_with(": broken"), "{err}");
+    }
+
+    #[test]
+    fn hung_executable_is_killed_after_the_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = script(dir.path(), "watson-export-slow", "exec sleep 30");
+        let started = Instant::now();
+        let err = ExternalExporter::new("slow", path)
+            .with_timeout(Duration::from_millis(200))
+            .export(&[], &ExportOptions::default(), &mut Vec::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::Write;
+
+use chrono::{DateTime, Utc};
+use rs_watson::Frame;
+use serde::Serialize;
+
+use crate::{ExportError, ExportOption, ExportOptions, Exporter};
+
+const OPTIONS: &[ExportOption] = &[ExportOption {
+    name: "pretty",
+    description: "Indent the output: true or false",
+    default: "false",
+}];
+
+/// A frame as written by [`JsonExporter`] and sent to external exporters.
+#[derive(Serialize)]
+pub(crate) struct FrameJson<'a> {
+    id: String,
+    project: &'a str,
+    tags: &'a [String],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration_seconds: i64,
+}
+
+impl<'a> From<&'a Frame> for FrameJson<'a> {
+    fn from(frame: &'a Frame) -> Self {
+        Self {
+            id: frame.id.to_string(),
+            project: &frame.project,
+            tags: &frame.tags,
+            start: frame.start,
+            end: frame.end,
+            duration_seconds: (frame.end - frame.start).num_seconds(),
+        }
+    }
+}
+
+pub(crate) fn frames_json(frames: &[Frame]) -> Vec<FrameJson<'_>> {
+    frames.iter().map(FrameJson::from).collect()
+}
+
+/// Writes the frames as a JSON array.
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "JSON array of frames, as sent to external exporters"
+    }
+
+    fn options(&self) -> &[ExportOption] {
+        OPTIONS
+    }
+
+    fn export(
+        &self,
+        frames: &[Frame],
+        options: &ExportOptions,
+        mut writer: &mut dyn Write,
+    ) -> Result<(), ExportError> {
+        options.check(self.name(), OPTIONS)?;
+        let pretty = match options.get("pretty").unwrap_or("false") {
+            "true" => true,
+            "false" => false,
+            other => {
+                return Err(ExportError::InvalidOption {
+                    option: "pretty".into(),
+                    value: other.into(),
+                    reason: "expected true or false".into(),
+                });
+            }
+        };
+        let frames = frames_json(frames);
+        if pretty {
+            serde_json::to_writer_pretty(&mut writer, &frames)?;
+        } else {
+            serde_json::to_writer(&mut writer, &frames)?;
+        }
+        writeln!(writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    #[test]
+    fn json_export_writes_an_array_of_frames() {
+        let frames = vec![Frame {
+            id: Uuid::nil(),
+            project: "backend".into(),
+            tags: vec!["api".into()],
+            start: Utc.with_ymd_and_hms(2026, 1, 15, 9, 0, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2026, 1, 15, 10, 0, 0).unwrap(),
+        }];
+        let mut buf = Vec::new();
+        JsonExporter
+            .export(&frames, &ExportOptions::default(), &mut buf)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "[{\"id\":\"00000000-0000-0000-0000-000000000000\",\"project\":\"backend\",\
+             \"tags\":[\"api\"],\"start\":\"2026-01-15T09:00:00Z\",\
+             \"end\":\"2026-01-15T10:00:00Z\",\"duration_seconds\":3600}]\n"
+        );
+    }
+}
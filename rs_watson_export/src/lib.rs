@@ -1,11 +1,250 @@
 pub mod csv;
+pub mod external;
+pub mod json;
 
+use std::collections::BTreeMap;
 use std::io::Write;
 
 use rs_watson::Frame;
+use thiserror::Error;
 
+use crate::csv::CsvExporter;
+use crate::external::ExternalExporter;
+use crate::json::JsonExporter;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Unknown export format \"{0}\"")]
+    UnknownFormat(String),
+    #[error("Unknown option \"{option}\" for the {format} exporter")]
+    UnknownOption { format: String, option: String },
+    #[error("Invalid value \"{value}\" for option \"{option}\": {reason}")]
+    InvalidOption {
+        option: String,
+        value: String,
+        reason: String,
+    },
+    #[error("Expected an option as key=value, got \"{0}\"")]
+    MalformedOption(String),
+    #[error("CSV error: {0}")]
+    Csv(#[from] ::csv::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Exporter {name} failed ({status}){}", stderr_suffix(.stderr))]
+    External {
+        name: String,
+        status: String,
+        stderr: String,
+    },
+}
+
+fn stderr_suffix(stderr: &str) -> String {
+    match stderr.trim() {
+        "" => String::new(),
+        s => format!(": {s}"),
+    }
+}
+
+/// An option an exporter understands, for listing and validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOption {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: &'static str,
+}
+
+/// `key=value` settings passed to an exporter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportOptions(BTreeMap<String, String>);
+
+impl ExportOptions {
+    /// Parses `key=value` pairs; a later key overrides an earlier one.
+    pub fn parse<I, T>(pairs: I) -> Result<Self, ExportError>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let mut map = BTreeMap::new();
+        for pair in pairs {
+            let pair = pair.as_ref();
+            let (key, value) = pair
+                .split_once('=')
+                .filter(|(k, _)| !k.trim().is_empty())
+                .ok_or_else(|| ExportError::MalformedOption(pair.to_string()))?;
+            map.insert(key.trim().to_string(), value.to_string());
+        }
+        Ok(Self(map))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.0.insert(key.into(), value.into());
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Fails on the first option not among `known`.
+    pub fn check(&self, format: &str, known: &[ExportOption]) -> Result<(), ExportError> {
+        match self.0.keys().find(|k| !known.iter().any(|o| o.name == *k)) {
+            Some(option) => Err(ExportError::UnknownOption {
+                format: format.to_string(),
+                option: option.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An export format. Object-safe, so formats can be collected in a [`Registry`]
+/// and chosen by name at runtime.
 pub trait Exporter {
-    type Error: std::error::Error;
+    /// Name used to select the format, e.g. `csv`.
+    fn name(&self) -> &str;
+
+    /// File extension without the dot.
+    fn extension(&self) -> &str;
+
+    /// One line for format listings.
+    fn description(&self) -> &str;
+
+    /// The options this exporter reads. External exporters declare none and
+    /// receive whatever is given.
+    fn options(&self) -> &[ExportOption] {
+        &[]
+    }
+
+    fn export(
+        &self,
+        frames: &[Frame],
+        options: &ExportOptions,
+        writer: &mut dyn Write,
+    ) -> Result<(), ExportError>;
+}
+
+/// The available export formats, built-ins first.
+pub struct Registry {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self {
+            exporters: Vec::new(),
+        }
+    }
+
+    /// The formats built into this crate: `csv` and `json`.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(CsvExporter::default()));
+        registry.register(Box::new(JsonExporter));
+        registry
+    }
+
+    /// The built-in formats plus every `watson-export-<name>` executable on `PATH`.
+    pub fn discover() -> Self {
+        let mut registry = Self::builtin();
+        for exporter in ExternalExporter::discover() {
+            registry.register(Box::new(exporter));
+        }
+        registry
+    }
+
+    /// Adds `exporter`, unless a format with the same name is already registered.
+    pub fn register(&mut self, exporter: Box<dyn Exporter>) {
+        if self.get(exporter.name()).is_none() {
+            self.exporters.push(exporter);
+        }
+    }
+
+    /// Adds `exporter`, taking the place of a format with the same name. Used to
+    /// swap in a configured built-in, e.g. a CSV exporter that knows the epics.
+    pub fn replace(&mut self, exporter: Box<dyn Exporter>) {
+        match self
+            .exporters
+            .iter()
+            .position(|e| e.name() == exporter.name())
+        {
+            Some(i) => self.exporters[i] = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters
+            .iter()
+            .find(|e| e.name() == name)
+            .map(|e| e.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Exporter> {
+        self.exporters.iter().map(|e| e.as_ref())
+    }
+
+    /// Exports `frames` in the format called `name`.
+    pub fn export(
+        &self,
+        name: &str,
+        frames: &[Frame],
+        options: &ExportOptions,
+        writer: &mut dyn Write,
+    ) -> Result<(), ExportError> {
+        self.get(name)
+            .ok_or_else(|| ExportError::UnknownFormat(name.to_string()))?
+            .export(frames, options, writer)
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn options_parse_and_check() {
+        let options = ExportOptions::parse(["delimiter=;", "tag_separator= / "]).unwrap();
+        assert_eq!(options.get("delimiter"), Some(";"));
+        assert_eq!(options.get("tag_separator"), Some(" / "));
+        assert!(
+            options
+                .check("csv", CsvExporter::default().options())
+                .is_ok()
+        );
+        assert!(matches!(
+            options.check("json", JsonExporter.options()),
+            Err(ExportError::UnknownOption { .. })
+        ));
+        assert!(matches!(
+            ExportOptions::parse(["nope"]),
+            Err(ExportError::MalformedOption(_))
+        ));
+    }
 
-    fn export<W: Write>(&self, frames: &[Frame], writer: W) -> Result<(), Self::Error>;
+    #[test]
+    fn registry_finds_formats_by_name_and_keeps_the_first() {
+        let mut registry = Registry::builtin();
+        registry.register(Box::new(CsvExporter::default()));
+        let names: Vec<&str> = registry.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["csv", "json"]);
+        assert_eq!(registry.get("json").unwrap().extension(), "json");
+        registry.replace(Box::new(JsonExporter));
+        assert_eq!(registry.iter().count(), 2);
+        assert!(matches!(
+            registry.export("xml", &[], &ExportOptions::default(), &mut Vec::new()),
+            Err(ExportError::UnknownFormat(_))
+        ));
+    }
 }
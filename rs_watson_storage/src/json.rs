@@ -1,9 +1,11 @@
 use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 
 use thiserror::Error;
 
-use crate::{ActiveFrameRecord, FrameRecord, Storage};
+use crate::{ActiveFrameRecord, FrameRecord, ProjectRecord, Storage};
 
 #[derive(Debug, Error)]
 pub enum JsonStorageError {
@@ -16,6 +18,7 @@ pub enum JsonStorageError {
 pub struct JsonStorage {
     frames_path: PathBuf,
     state_path: PathBuf,
+    projects_path: PathBuf,
 }
 
 impl JsonStorage {
@@ -24,6 +27,7 @@ impl JsonStorage {
         Self {
             frames_path: dir.join("frames.json"),
             state_path: dir.join("state.json"),
+            projects_path: dir.join("projects.json"),
         }
     }
 }
@@ -77,6 +81,33 @@ impl Storage for JsonStorage {
         }
         Ok(())
     }
+
+    fn load_projects(&self) -> Result<Vec<ProjectRecord>, Self::Error> {
+        if !self.projects_path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(&self.projects_path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    fn save_projects(&self, projects: &[ProjectRecord]) -> Result<(), Self::Error> {
+        let data = serde_json::to_string_pretty(projects)?;
+        write_atomic(&self.projects_path, &data)?;
+        Ok(())
+    }
+
+    /// Hashes the modification time and size of the three files.
+    fn data_version(&self) -> Result<Option<u64>, Self::Error> {
+        let mut hasher = DefaultHasher::new();
+        for path in [&self.frames_path, &self.state_path, &self.projects_path] {
+            match fs::metadata(path) {
+                Ok(meta) => Some((meta.modified()?, meta.len())).hash(&mut hasher),
+                Err(e) if e.kind() == ErrorKind::NotFound => None::<()>.hash(&mut hasher),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Some(hasher.finish()))
+    }
 }
 
 #[cfg(test)]
@@ -104,9 +135,23 @@ mod tests {
             project: "frontend".into(),
             tags: vec!["ui".into()],
             start: t(9),
+            last_activity: None,
         }
     }
 
+    #[test]
+    fn data_version_follows_the_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let s = JsonStorage::new(dir.path());
+        let empty = s.data_version().unwrap();
+        assert_eq!(s.data_version().unwrap(), empty);
+        s.save_active(Some(&sample_active())).unwrap();
+        let started = s.data_version().unwrap();
+        assert_ne!(started, empty);
+        s.save_active(None).unwrap();
+        assert_eq!(s.data_version().unwrap(), empty);
+    }
+
     #[test]
     fn frames_roundtrip() {
         let dir = tempfile::tempdir().unwrap();
@@ -153,6 +198,19 @@ mod tests {
         assert_eq!(loaded.start, t(9));
     }
 
+    #[test]
+    fn active_without_last_activity_field_still_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("state.json"),
+            r#"{"project":"backend","tags":[],"start":"2026-01-15T09:00:00Z"}"#,
+        )
+        .unwrap();
+        let loaded = JsonStorage::new(dir.path()).load_active().unwrap().unwrap();
+        assert_eq!(loaded.project, "backend");
+        assert!(loaded.last_activity.is_none());
+    }
+
     #[test]
     fn load_active_missing_file_returns_none() {
         let dir = tempfile::tempdir().unwrap();
@@ -190,4 +248,30 @@ mod tests {
         s.save_frames(&[sample_frame()]).unwrap();
         assert!(!dir.path().join("frames.json.tmp").exists());
     }
+
+    #[test]
+    fn projects_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let s = JsonStorage::new(dir.path());
+        let project = ProjectRecord {
+            name: "backend".into(),
+            description: Some("API server".into()),
+            color: Some("#3366ff".into()),
+            client: Some("acme".into()),
+            archived: true,
+        };
+        s.save_projects(std::slice::from_ref(&project)).unwrap();
+        assert_eq!(s.load_projects().unwrap(), vec![project]);
+    }
+
+    #[test]
+    fn load_projects_missing_file_returns_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(
+            JsonStorage::new(dir.path())
+                .load_projects()
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -1,9 +1,12 @@
 #[cfg(feature = "json")]
 pub mod json;
 
+pub mod search;
 #[cfg(feature = "sqlite")]
 pub mod sqlite;
 
+pub use search::SearchHit;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -22,6 +25,25 @@ pub struct ActiveFrameRecord {
     pub project: String,
     pub tags: Vec<String>,
     pub start: DateTime<Utc>,
+    /// Last time the user was seen interacting while this frame was running.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// Metadata for a project. Projects also exist implicitly through their frames;
+/// a record is only stored once something about the project has been set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectRecord {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Display color as `#rrggbb`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
 }
 
 pub trait Storage {
@@ -31,4 +53,28 @@ pub trait Storage {
     fn save_frames(&self, frames: &[FrameRecord]) -> Result<(), Self::Error>;
     fn load_active(&self) -> Result<Option<ActiveFrameRecord>, Self::Error>;
     fn save_active(&self, frame: Option<&ActiveFrameRecord>) -> Result<(), Self::Error>;
+
+    /// Stored project metadata. The default has none, for backends that do not
+    /// keep it; projects then exist only through their frames.
+    fn load_projects(&self) -> Result<Vec<ProjectRecord>, Self::Error> {
+        Ok(Vec::new())
+    }
+
+    /// Replaces all stored project metadata. The default discards it.
+    fn save_projects(&self, _projects: &[ProjectRecord]) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    /// Frames whose project or tags match the words of `text`, best first.
+    /// The default ranks every stored frame in memory with [`search::search_frames`].
+    fn search(&self, text: &str) -> Result<Vec<SearchHit>, Self::Error> {
+        Ok(search::search_frames(self.load_frames()?, text))
+    }
+
+    /// A value that changes when another process writes the data, so
+    /// long-running clients can tell when to reload. It may also change after
+    /// this storage's own writes. The default is `None`: changes go unnoticed.
+    fn data_version(&self) -> Result<Option<u64>, Self::Error> {
+        Ok(None)
+    }
 }
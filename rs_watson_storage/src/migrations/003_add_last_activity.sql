@@ -0,0 +1 @@
+ALTER TABLE active_frame ADD COLUMN last_activity TEXT;
@@ -0,0 +1,7 @@
+CREATE TABLE projects (
+    name        TEXT PRIMARY KEY,
+    description TEXT,
+    color       TEXT,
+    client      TEXT,
+    archived    INTEGER NOT NULL DEFAULT 0
+);
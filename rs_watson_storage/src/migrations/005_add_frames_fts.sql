@@ -0,0 +1,13 @@
+-- Full-text index over project and tags, rebuilt by save_frames.
+CREATE VIRTUAL TABLE frames_fts USING fts5(
+    frame_id UNINDEXED,
+    project,
+    tags,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO frames_fts (frame_id, project, tags)
+SELECT f.id,
+       f.project,
+       COALESCE((SELECT group_concat(t.tag, ' ') FROM frame_tags t WHERE t.frame_id = f.id), '')
+FROM frames f;
@@ -0,0 +1,199 @@
+//! In-memory fuzzy search over frame records, used by backends without a
+//! search index and by `SqliteStorage` when the index finds nothing.
+
+use crate::FrameRecord;
+
+/// A frame found by [`Storage::search`](crate::Storage::search).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub frame: FrameRecord,
+    /// Higher is better. Only comparable within one search.
+    pub score: f64,
+}
+
+/// Lowercase words of `text`; anything but letters and digits separates them,
+/// so `acme/website` and `code-review` are two words each.
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How well `term` matches `word`: 1.0 for equal words down to 0.2 for a loose
+/// subsequence; `None` for no match. Both are lowercase.
+fn word_score(term: &str, word: &str) -> Option<f64> {
+    if word == term {
+        return Some(1.0);
+    }
+    if word.starts_with(term) {
+        return Some(0.8);
+    }
+    if word.contains(term) {
+        return Some(0.6);
+    }
+    let (t_len, w_len) = (term.chars().count(), word.chars().count());
+    if t_len >= 4 && t_len.abs_diff(w_len) <= 1 && edit_distance(term, word) <= 1 {
+        return Some(0.5);
+    }
+    if t_len >= 3 && is_subsequence(term, word) {
+        return Some(0.2 + 0.2 * t_len as f64 / w_len as f64);
+    }
+    None
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut rest = haystack.chars();
+    needle.chars().all(|c| rest.any(|h| h == c))
+}
+
+/// Levenshtein distance, except that swapping two adjacent letters is one
+/// edit (optimal string alignment), so `webiste` is one typo from `website`.
+/// Also used for strict mode's name suggestions.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// The best score of `term` against any of `words`.
+fn best_score(term: &str, words: &[String]) -> Option<f64> {
+    words
+        .iter()
+        .filter_map(|w| word_score(term, w))
+        .fold(None, |best: Option<f64>, s| {
+            Some(best.map_or(s, |b| b.max(s)))
+        })
+}
+
+/// Scores `frame` against the search `terms`. Every term must match the project
+/// or a tag; project matches count double. `None` when a term matches nothing.
+pub fn score(frame: &FrameRecord, terms: &[String]) -> Option<f64> {
+    let project = words(&frame.project);
+    let tags: Vec<String> = frame.tags.iter().flat_map(|t| words(t)).collect();
+    terms.iter().try_fold(0.0, |total, term| {
+        let project = best_score(term, &project).map(|s| 2.0 * s);
+        let tag = best_score(term, &tags);
+        let term_score = match (project, tag) {
+            (None, None) => return None,
+            (p, t) => p.unwrap_or(0.0).max(t.unwrap_or(0.0)),
+        };
+        Some(total + term_score)
+    })
+}
+
+/// Scores any `text` against the search `terms` with the same matching as
+/// [`score`]. Every term must match a word of `text`.
+pub fn text_score(text: &str, terms: &[String]) -> Option<f64> {
+    let words = words(text);
+    terms
+        .iter()
+        .try_fold(0.0, |total, term| Some(total + best_score(term, &words)?))
+}
+
+/// Ranks `frames` against `text`, best first; ties go to the most recent
+/// frame. Empty when `text` has no words.
+pub fn search_frames(frames: Vec<FrameRecord>, text: &str) -> Vec<SearchHit> {
+    let terms = words(text);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut hits: Vec<SearchHit> = frames
+        .into_iter()
+        .filter_map(|frame| score(&frame, &terms).map(|score| SearchHit { frame, score }))
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.frame.start.cmp(&a.frame.start))
+    });
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn frame(project: &str, tags: &[&str], h: u32) -> FrameRecord {
+        FrameRecord {
+            id: Uuid::new_v4(),
+            project: project.into(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            start: Utc.with_ymd_and_hms(2026, 1, 15, h, 0, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2026, 1, 15, h + 1, 0, 0).unwrap(),
+        }
+    }
+
+    fn projects(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.frame.project.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_exact_before_prefix_before_fuzzy() {
+        let frames = vec![
+            frame("websites", &[], 8),
+            frame("acme/website", &[], 9),
+            frame("webite", &[], 10),
+            frame("docs", &["website"], 11),
+            frame("other", &[], 12),
+        ];
+        let hits = search_frames(frames, "website");
+        assert_eq!(
+            projects(&hits),
+            vec!["acme/website", "websites", "docs", "webite"]
+        );
+    }
+
+    #[test]
+    fn every_term_must_match_and_recent_frames_win_ties() {
+        let frames = vec![
+            frame("backend", &["api"], 8),
+            frame("backend", &["db"], 9),
+            frame("backend", &["api", "auth"], 10),
+        ];
+        let hits = search_frames(frames, "Backend API");
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|h| h.frame.tags.contains(&"api".into())));
+        assert_eq!(hits[0].frame.start.format("%H").to_string(), "10");
+        assert!(search_frames(vec![frame("backend", &[], 8)], " / ").is_empty());
+    }
+
+    #[test]
+    fn text_score_needs_every_term() {
+        let terms = words("go rep");
+        assert!(text_score("Go to Report", &terms).is_some());
+        assert!(text_score("Go to Log", &terms).is_none());
+        let exact = text_score("Start backend", &words("backend")).unwrap();
+        let typo = text_score("Start backend", &words("backnd")).unwrap();
+        assert!(exact > typo);
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("meeting", "meting"), 1);
+        assert_eq!(edit_distance("review", "reveiw"), 1);
+        assert_eq!(edit_distance("review", "rveeiw"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+    }
+}
@@ -8,7 +8,8 @@ use rusqlite_migration::{M, Migrations};
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::{ActiveFrameRecord, FrameRecord, Storage};
+use crate::search::{self, SearchHit};
+use crate::{ActiveFrameRecord, FrameRecord, ProjectRecord, Storage};
 
 #[derive(Debug, Error)]
 pub enum SqliteStorageError {
@@ -33,6 +34,9 @@ impl SqliteStorage {
         let migrations = Migrations::new(vec![
             M::up(include_str!("migrations/001_initial.sql")),
             M::up(include_str!("migrations/002_add_start_index.sql")),
+            M::up(include_str!("migrations/003_add_last_activity.sql")),
+            M::up(include_str!("migrations/004_add_projects.sql")),
+            M::up(include_str!("migrations/005_add_frames_fts.sql")),
         ]);
         migrations.to_latest(&mut conn)?;
 
@@ -48,6 +52,18 @@ fn parse_dt(s: &str) -> Result<DateTime<Utc>, SqliteStorageError> {
         .map_err(|e| SqliteStorageError::Parse(format!("invalid datetime \"{s}\": {e}")))
 }
 
+/// An FTS5 query requiring every word of `text` as a prefix, e.g. `"web"* "api"*`.
+fn fts_query(text: &str) -> Option<String> {
+    let terms = search::words(text);
+    (!terms.is_empty()).then(|| {
+        terms
+            .iter()
+            .map(|t| format!("\"{}\"*", t.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ")
+    })
+}
+
 fn parse_uuid(s: &str) -> Result<Uuid, SqliteStorageError> {
     Uuid::parse_str(s).map_err(|e| SqliteStorageError::Parse(format!("invalid UUID \"{s}\": {e}")))
 }
@@ -97,6 +113,7 @@ impl Storage for SqliteStorage {
         let tx = conn.transaction()?;
 
         tx.execute("DELETE FROM frames", [])?; // CASCADE removes frame_tags
+        tx.execute("DELETE FROM frames_fts", [])?;
 
         for frame in frames {
             tx.execute(
@@ -114,6 +131,10 @@ impl Storage for SqliteStorage {
                     params![frame.id.to_string(), i as i64, tag],
                 )?;
             }
+            tx.execute(
+                "INSERT INTO frames_fts (frame_id, project, tags) VALUES (?1, ?2, ?3)",
+                params![frame.id.to_string(), frame.project, frame.tags.join(" ")],
+            )?;
         }
 
         tx.commit()?;
@@ -124,15 +145,21 @@ impl Storage for SqliteStorage {
         let conn = self.conn.lock().unwrap();
 
         let result = conn.query_row(
-            "SELECT project, start FROM active_frame WHERE lock = 1",
+            "SELECT project, start, last_activity FROM active_frame WHERE lock = 1",
             [],
-            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
         );
 
         match result {
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(SqliteStorageError::Rusqlite(e)),
-            Ok((project, start)) => {
+            Ok((project, start, last_activity)) => {
                 let mut tag_stmt =
                     conn.prepare("SELECT tag FROM active_frame_tags ORDER BY position")?;
                 let tags: Vec<String> = tag_stmt
@@ -143,6 +170,7 @@ impl Storage for SqliteStorage {
                     project,
                     tags,
                     start: parse_dt(&start)?,
+                    last_activity: last_activity.as_deref().map(parse_dt).transpose()?,
                 }))
             }
         }
@@ -157,8 +185,13 @@ impl Storage for SqliteStorage {
 
         if let Some(f) = frame {
             tx.execute(
-                "INSERT INTO active_frame (lock, project, start) VALUES (1, ?1, ?2)",
-                params![f.project, f.start.to_rfc3339()],
+                "INSERT INTO active_frame (lock, project, start, last_activity) \
+                 VALUES (1, ?1, ?2, ?3)",
+                params![
+                    f.project,
+                    f.start.to_rfc3339(),
+                    f.last_activity.map(|dt| dt.to_rfc3339()),
+                ],
             )?;
             for (i, tag) in f.tags.iter().enumerate() {
                 tx.execute(
@@ -171,6 +204,101 @@ impl Storage for SqliteStorage {
         tx.commit()?;
         Ok(())
     }
+
+    fn load_projects(&self) -> Result<Vec<ProjectRecord>, Self::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT name, description, color, client, archived FROM projects ORDER BY name",
+        )?;
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(ProjectRecord {
+                    name: row.get(0)?,
+                    description: row.get(1)?,
+                    color: row.get(2)?,
+                    client: row.get(3)?,
+                    archived: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(projects)
+    }
+
+    fn save_projects(&self, projects: &[ProjectRecord]) -> Result<(), Self::Error> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM projects", [])?;
+        for p in projects {
+            tx.execute(
+                "INSERT INTO projects (name, description, color, client, archived) \
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![p.name, p.description, p.color, p.client, p.archived],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Looks the words up as prefixes in the `frames_fts` index, ranked by BM25
+    /// with project matches weighted double. When the index finds nothing, falls
+    /// back to the in-memory fuzzy search so typos and inner substrings still match.
+    fn search(&self, text: &str) -> Result<Vec<SearchHit>, Self::Error> {
+        let Some(query) = fts_query(text) else {
+            return Ok(Vec::new());
+        };
+        let rows: Vec<(String, String, String, String, f64)> = {
+            let conn = self.conn.lock().unwrap();
+            let mut stmt = conn.prepare(
+                "SELECT f.id, f.project, f.start, f.end, -bm25(frames_fts, 0.0, 2.0, 1.0) \
+                 FROM frames_fts JOIN frames f ON f.id = frames_fts.frame_id \
+                 WHERE frames_fts MATCH ?1 \
+                 ORDER BY bm25(frames_fts, 0.0, 2.0, 1.0), f.start DESC",
+            )?;
+            stmt.query_map([&query], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })?
+            .collect::<Result<_, _>>()?
+        };
+        if rows.is_empty() {
+            return Ok(search::search_frames(self.load_frames()?, text));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut tag_stmt =
+            conn.prepare("SELECT tag FROM frame_tags WHERE frame_id = ?1 ORDER BY position")?;
+        rows.into_iter()
+            .map(|(id, project, start, end, score)| {
+                let tags = tag_stmt
+                    .query_map([&id], |row| row.get(0))?
+                    .collect::<Result<_, _>>()?;
+                Ok(SearchHit {
+                    frame: FrameRecord {
+                        id: parse_uuid(&id)?,
+                        project,
+                        tags,
+                        start: parse_dt(&start)?,
+                        end: parse_dt(&end)?,
+                    },
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    /// SQLite's `data_version`, which changes when another connection commits.
+    fn data_version(&self) -> Result<Option<u64>, Self::Error> {
+        let conn = self.conn.lock().unwrap();
+        let version: i64 = conn.pragma_query_value(None, "data_version", |row| row.get(0))?;
+        Ok(Some(version as u64))
+    }
 }
 
 #[cfg(test)]
@@ -201,6 +329,7 @@ mod tests {
             project: "frontend".into(),
             tags: vec!["ui".into()],
             start: t(9),
+            last_activity: None,
         }
     }
 
@@ -263,6 +392,15 @@ mod tests {
         assert_eq!(loaded.start, t(9));
     }
 
+    #[test]
+    fn active_last_activity_roundtrip() {
+        let s = storage();
+        let mut active = sample_active();
+        active.last_activity = Some(t(11));
+        s.save_active(Some(&active)).unwrap();
+        assert_eq!(s.load_active().unwrap().unwrap().last_activity, Some(t(11)));
+    }
+
     #[test]
     fn load_active_empty_returns_none() {
         assert!(storage().load_active().unwrap().is_none());
@@ -284,4 +422,85 @@ mod tests {
         s.save_frames(&[sample_frame()]).unwrap();
         assert_eq!(s.load_frames().unwrap().len(), 1);
     }
+
+    #[test]
+    fn projects_roundtrip() {
+        let s = storage();
+        let projects = vec![
+            ProjectRecord {
+                name: "backend".into(),
+                description: Some("API server".into()),
+                color: Some("#3366ff".into()),
+                client: Some("acme".into()),
+                archived: true,
+            },
+            ProjectRecord {
+                name: "frontend".into(),
+                ..Default::default()
+            },
+        ];
+        s.save_projects(&projects).unwrap();
+        assert_eq!(s.load_projects().unwrap(), projects);
+    }
+
+    #[test]
+    fn save_projects_replaces_previous() {
+        let s = storage();
+        s.save_projects(&[ProjectRecord {
+            name: "old".into(),
+            ..Default::default()
+        }])
+        .unwrap();
+        s.save_projects(&[]).unwrap();
+        assert!(s.load_projects().unwrap().is_empty());
+    }
+
+    #[test]
+    fn data_version_sees_other_connections() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watson.db");
+        let ui = SqliteStorage::new(&path).unwrap();
+        let cli = SqliteStorage::new(&path).unwrap();
+        let before = ui.data_version().unwrap();
+        ui.save_frames(&[sample_frame()]).unwrap();
+        assert_eq!(ui.data_version().unwrap(), before);
+        cli.save_active(Some(&sample_active())).unwrap();
+        assert_ne!(ui.data_version().unwrap(), before);
+    }
+
+    #[test]
+    fn search_uses_the_index_and_follows_saves() {
+        let s = storage();
+        let mut website = sample_frame();
+        website.project = "acme/website".into();
+        website.tags = vec!["code-review".into()];
+        let mut docs = sample_frame();
+        docs.project = "docs".into();
+        docs.tags = vec!["website".into()];
+        s.save_frames(&[docs.clone(), website.clone()]).unwrap();
+
+        let hits = s.search("web").unwrap();
+        let ids: Vec<Uuid> = hits.iter().map(|h| h.frame.id).collect();
+        assert_eq!(ids, vec![website.id, docs.id]);
+        assert_eq!(hits[0].frame.tags, vec!["code-review"]);
+        assert_eq!(s.search("review acme").unwrap().len(), 1);
+
+        s.save_frames(&[docs.clone()]).unwrap();
+        assert_eq!(s.search("web").unwrap().len(), 1);
+        assert!(s.search("\"*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn search_falls_back_to_fuzzy_matching() {
+        let s = storage();
+        let mut frame = sample_frame();
+        frame.project = "website".into();
+        s.save_frames(&[frame.clone()]).unwrap();
+        let hits = s.search("webiste").unwrap();
+        assert_eq!(hits[0].frame.id, frame.id);
+        let hits = s.search("websit").unwrap();
+        assert_eq!(hits[0].frame.id, frame.id);
+        let hits = s.search("ebsit").unwrap();
+        assert_eq!(hits[0].frame.id, frame.id);
+    }
 }
@@ -1,11 +1,21 @@
-use chrono::{Local, Utc};
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use eframe::egui;
 use rs_watson::config::{Config, StorageProvider};
-use rs_watson::{ActiveFrame, Frame, Watson};
+use rs_watson::report::Dimension;
+use rs_watson::{ActiveFrame, ConfigFile, Frame, FrameFilter, Query, QueryError, Watson};
+use rs_watson_export::csv::CsvExporter;
+use rs_watson_export::{ExportOptions, Registry};
 use rs_watson_storage::sqlite::SqliteStorage;
 use uuid::Uuid;
 
-use crate::format::{collect_projects, fmt_duration, parse_local_date, parse_local_dt, parse_tags};
-use crate::types::{EditState, Tab};
+use crate::colors::CHART_PALETTE;
+use crate::format::{fmt_duration, parse_local_date, parse_local_dt, parse_tags};
+use crate::types::{CalendarDrag, EditState, EpicForm, PaletteState, SplitState, Tab};
+
+/// Hooks run on the UI thread, so none may freeze the window for longer than this.
+const UI_HOOK_TIMEOUT_SECS: u64 = 2;
 
 pub(crate) struct WatsonApp {
     pub(crate) watson: Watson<SqliteStorage>,
@@ -14,7 +24,10 @@ pub(crate) struct WatsonApp {
     // Cached state — refreshed after every mutation
     pub(crate) status: Option<ActiveFrame>,
     pub(crate) frames: Vec<Frame>,
+    /// Non-archived project names, for autocompletion.
     pub(crate) projects: Vec<String>,
+    /// Registry colors by project name.
+    pub(crate) project_colors: HashMap<String, egui::Color32>,
 
     // Toolbar
     pub(crate) input_project: String,
@@ -22,13 +35,40 @@ pub(crate) struct WatsonApp {
     pub(crate) message: Option<String>,
     pub(crate) message_is_error: bool,
 
+    // Idle detection
+    pub(crate) idle_custom_stop: String,
+    pub(crate) last_activity_recorded: Option<std::time::Instant>,
+    /// The storage's data version at the last check, to spot writes by the CLI.
+    pub(crate) data_version: Option<u64>,
+    pub(crate) last_change_check: Option<std::time::Instant>,
+
     pub(crate) active_tab: Tab,
+    pub(crate) palette: Option<PaletteState>,
 
     // Log tab
     pub(crate) log_from: String,
     pub(crate) log_to: String,
+    pub(crate) log_query: String,
+    pub(crate) log_search: String,
+    /// Frames matching `log_search`, with the text they were searched for.
+    /// Cleared by `refresh` so edits show up in the results.
+    pub(crate) log_search_hits: Option<(String, HashSet<Uuid>)>,
     pub(crate) delete_confirm_id: Option<Uuid>,
+    /// The frame picked with the arrow keys in the Log tab.
+    pub(crate) log_cursor: Option<Uuid>,
     pub(crate) edit_state: Option<EditState>,
+    pub(crate) split_state: Option<SplitState>,
+    pub(crate) merge_selection: Vec<Uuid>,
+    /// Built-in formats plus `watson-export-*` executables found at startup.
+    pub(crate) exporters: Registry,
+    pub(crate) export_format: String,
+    /// Empty means `watson-export.<extension>` in the working directory.
+    pub(crate) export_path: String,
+
+    // Calendar tab
+    /// First day of the week on screen.
+    pub(crate) calendar_week: NaiveDate,
+    pub(crate) calendar_drag: Option<CalendarDrag>,
 
     // Add tab
     pub(crate) add_project: String,
@@ -41,22 +81,31 @@ pub(crate) struct WatsonApp {
     // Report tab
     pub(crate) report_from: String,
     pub(crate) report_to: String,
+    pub(crate) report_query: String,
     pub(crate) report_use_epics: bool,
+    pub(crate) report_include_running: bool,
+    /// Nested grouping, outermost first; empty for the default project report.
+    pub(crate) report_group_by: Vec<Dimension>,
+    /// Weeks covered by the stats panel's project trends.
+    pub(crate) report_stats_weeks: usize,
+
+    // Charts tab
+    pub(crate) charts_from: String,
+    pub(crate) charts_to: String,
+    pub(crate) charts_query: String,
+
+    // Epics tab
+    pub(crate) epic_form: EpicForm,
+    pub(crate) epic_delete_confirm: bool,
+    pub(crate) epic_message: Option<String>,
+    pub(crate) epic_message_is_error: bool,
 }
 
 impl WatsonApp {
     pub(crate) fn new() -> anyhow::Result<Self> {
         let config = Config::load()?;
 
-        let data_dir = if let Ok(dir) = std::env::var("RS_WATSON_DATA_DIR") {
-            std::path::PathBuf::from(dir)
-        } else if let Some(dir) = &config.storage.data_dir {
-            std::path::PathBuf::from(dir)
-        } else {
-            dirs::data_dir()
-                .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?
-                .join("rs_watson")
-        };
+        let data_dir = config.data_dir()?;
         std::fs::create_dir_all(&data_dir)?;
 
         let storage = match config.storage.provider {
@@ -64,10 +113,23 @@ impl WatsonApp {
                 .map_err(|e| anyhow::anyhow!("SQLite: {e}"))?,
         };
 
-        let watson = Watson::new(storage);
+        let watson = Watson::new(storage)
+            .with_aliases(config.aliases.clone())
+            .with_strictness(config.behavior.strictness())
+            .with_hooks(config.hooks.clone().with_max_timeout(UI_HOOK_TIMEOUT_SECS));
+        let data_version = watson.data_version().ok().flatten();
         let status = watson.status().ok().flatten();
         let frames = watson.log().unwrap_or_default();
-        let projects = collect_projects(&frames);
+        let (projects, project_colors) = load_projects(&watson);
+        let mut exporters = Registry::discover();
+        exporters.replace(Box::new(
+            CsvExporter::default().with_epics(config.epics.clone()),
+        ));
+
+        let calendar_week = config
+            .behavior
+            .week_start
+            .start_of_week(Local::now().date_naive());
 
         Ok(Self {
             watson,
@@ -75,15 +137,32 @@ impl WatsonApp {
             status,
             frames,
             projects,
+            project_colors,
             input_project: String::new(),
             input_tags: String::new(),
             message: None,
             message_is_error: false,
+            idle_custom_stop: String::new(),
+            last_activity_recorded: None,
+            data_version,
+            last_change_check: None,
             active_tab: Tab::Log,
+            palette: None,
             log_from: String::new(),
             log_to: String::new(),
+            log_query: String::new(),
+            log_search: String::new(),
+            log_search_hits: None,
             delete_confirm_id: None,
+            log_cursor: None,
             edit_state: None,
+            split_state: None,
+            merge_selection: Vec::new(),
+            exporters,
+            export_format: "csv".into(),
+            export_path: String::new(),
+            calendar_week,
+            calendar_drag: None,
             add_project: String::new(),
             add_tags: String::new(),
             add_from: String::new(),
@@ -92,14 +171,50 @@ impl WatsonApp {
             add_message_is_error: false,
             report_from: String::new(),
             report_to: String::new(),
+            report_query: String::new(),
             report_use_epics: false,
+            report_include_running: true,
+            report_group_by: Vec::new(),
+            report_stats_weeks: 4,
+            charts_from: String::new(),
+            charts_to: String::new(),
+            charts_query: String::new(),
+            epic_form: EpicForm::default(),
+            epic_delete_confirm: false,
+            epic_message: None,
+            epic_message_is_error: false,
         })
     }
 
     pub(crate) fn refresh(&mut self) {
+        let warnings = self.watson.take_hook_warnings();
+        if !warnings.is_empty() {
+            let lines: Vec<String> = warnings.iter().map(ToString::to_string).collect();
+            self.set_msg(false, lines.join("\n"));
+        }
         self.status = self.watson.status().ok().flatten();
         self.frames = self.watson.log().unwrap_or_default();
-        self.projects = collect_projects(&self.frames);
+        (self.projects, self.project_colors) = load_projects(&self.watson);
+        self.log_search_hits = None;
+        let frames = &self.frames;
+        self.merge_selection
+            .retain(|id| frames.iter().any(|f| f.id == *id));
+    }
+
+    /// The registry color of `project`, if one is set.
+    pub(crate) fn project_color(&self, project: &str) -> Option<egui::Color32> {
+        self.project_colors.get(project).copied()
+    }
+
+    /// The registry color of `project`, else a palette color picked from its
+    /// name so it stays the same across charts and restarts.
+    pub(crate) fn chart_color(&self, project: &str) -> egui::Color32 {
+        self.project_color(project).unwrap_or_else(|| {
+            let hash = project.bytes().fold(0usize, |h, b| {
+                h.wrapping_mul(31).wrapping_add(usize::from(b))
+            });
+            CHART_PALETTE[hash % CHART_PALETTE.len()]
+        })
     }
 
     pub(crate) fn set_msg(&mut self, ok: bool, msg: impl Into<String>) {
@@ -112,7 +227,64 @@ impl WatsonApp {
         self.add_message_is_error = !ok;
     }
 
-    pub(crate) fn filtered_frames<'a>(frames: &'a [Frame], from: &str, to: &str) -> Vec<&'a Frame> {
+    pub(crate) fn set_epic_msg(&mut self, ok: bool, msg: impl Into<String>) {
+        self.epic_message = Some(msg.into());
+        self.epic_message_is_error = !ok;
+    }
+
+    /// The filter for a tab's query field, with the config's alias, case and
+    /// separator rules. An empty field matches everything.
+    pub(crate) fn query_filter(&self, query: &str) -> Result<FrameFilter, QueryError> {
+        let query = match query.trim() {
+            "" => None,
+            q => Some(Query::parse(q)?.canonicalize(&self.config.aliases)),
+        };
+        Ok(FrameFilter {
+            query,
+            ignore_case: self.config.behavior.ignore_case,
+            separator: self.config.behavior.project_separator.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Runs the log tab's search unless its text is unchanged since the last
+    /// run. An empty search clears the hits.
+    pub(crate) fn update_log_search(&mut self) {
+        let text = self.log_search.trim().to_string();
+        if text.is_empty() {
+            self.log_search_hits = None;
+            return;
+        }
+        if self
+            .log_search_hits
+            .as_ref()
+            .is_some_and(|(t, _)| *t == text)
+        {
+            return;
+        }
+        let ids = match self.watson.search(&text) {
+            Ok(frames) => frames.iter().map(|f| f.id).collect(),
+            Err(e) => {
+                self.set_msg(false, format!("Search failed: {e}"));
+                HashSet::new()
+            }
+        };
+        self.log_search_hits = Some((text, ids));
+    }
+
+    /// Whether `frame` is among the log search hits; true with no search.
+    pub(crate) fn in_log_search(&self, frame: &Frame) -> bool {
+        self.log_search_hits
+            .as_ref()
+            .is_none_or(|(_, ids)| ids.contains(&frame.id))
+    }
+
+    pub(crate) fn filtered_frames<'a>(
+        frames: &'a [Frame],
+        from: &str,
+        to: &str,
+        filter: &FrameFilter,
+    ) -> Vec<&'a Frame> {
         let from_date = parse_local_date(from);
         let to_date = parse_local_date(to);
         frames
@@ -121,6 +293,7 @@ impl WatsonApp {
                 let d = f.start.with_timezone(&Local).date_naive();
                 from_date.is_none_or(|fd| d >= fd) && to_date.is_none_or(|td| d <= td)
             })
+            .filter(|f| filter.matches(f))
             .collect()
     }
 
@@ -133,7 +306,15 @@ impl WatsonApp {
             return;
         }
         let tags = parse_tags(&self.input_tags);
-        match self.watson.start_or_replace(&project, tags, Utc::now()) {
+        if self.start_tracking(&project, tags) {
+            self.input_project.clear();
+            self.input_tags.clear();
+        }
+    }
+
+    /// Starts `project`, stopping any running frame first. Returns whether it started.
+    pub(crate) fn start_tracking(&mut self, project: &str, tags: Vec<String>) -> bool {
+        match self.watson.start_or_replace(project, tags, Utc::now()) {
             Ok(r) => {
                 let msg = if r.replaced.is_some() {
                     format!(
@@ -144,16 +325,59 @@ impl WatsonApp {
                     format!("Started tracking \"{}\".", r.active.project)
                 };
                 self.set_msg(true, msg);
-                self.input_project.clear();
-                self.input_tags.clear();
                 self.refresh();
+                true
+            }
+            Err(e) => {
+                self.set_msg(false, e.to_string());
+                false
             }
-            Err(e) => self.set_msg(false, e.to_string()),
+        }
+    }
+
+    /// Reloads when another process, such as the CLI, changed the data.
+    /// Checks at most once a second.
+    pub(crate) fn refresh_on_external_change(&mut self) {
+        if self
+            .last_change_check
+            .is_some_and(|t| t.elapsed() < std::time::Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_change_check = Some(std::time::Instant::now());
+        let Ok(version) = self.watson.data_version() else {
+            return;
+        };
+        if version != self.data_version {
+            self.data_version = version;
+            self.refresh();
+        }
+    }
+
+    /// Records user activity on the active frame, at most once a minute.
+    /// Nothing is recorded without `max_frame_hours`.
+    pub(crate) fn record_activity(&mut self) {
+        let max = self.config.behavior.max_frame_duration();
+        if self.status.is_none()
+            || max.is_none()
+            || self
+                .last_activity_recorded
+                .is_some_and(|t| t.elapsed() < std::time::Duration::from_secs(60))
+        {
+            return;
+        }
+        self.last_activity_recorded = Some(std::time::Instant::now());
+        if self.watson.record_activity(Utc::now(), max).is_ok() {
+            self.status = self.watson.status().ok().flatten();
         }
     }
 
     pub(crate) fn do_stop(&mut self) {
-        match self.watson.stop(Utc::now()) {
+        self.do_stop_at(Utc::now());
+    }
+
+    pub(crate) fn do_stop_at(&mut self, at: DateTime<Utc>) {
+        match self.watson.stop(at) {
             Ok(f) => {
                 self.set_msg(
                     true,
@@ -248,6 +472,143 @@ impl WatsonApp {
         }
     }
 
+    pub(crate) fn do_split_save(&mut self) {
+        let Some(state) = &mut self.split_state else {
+            return;
+        };
+        let project = state.project.trim().to_string();
+        if project.is_empty() {
+            state.error = Some("Project name is required.".into());
+            return;
+        }
+        let Some(at) = parse_local_dt(&state.at) else {
+            state.error = Some("Invalid split time.".into());
+            return;
+        };
+        let tags = parse_tags(&state.tags);
+        let id = state.id;
+
+        match self.watson.split(id, at, project, tags) {
+            Ok(_) => {
+                self.split_state = None;
+                self.set_msg(true, "Frame split.");
+                self.refresh();
+            }
+            Err(e) => {
+                if let Some(s) = &mut self.split_state {
+                    s.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn do_merge(&mut self) {
+        match self.watson.merge(&self.merge_selection) {
+            Ok(_) => {
+                let n = self.merge_selection.len();
+                self.merge_selection.clear();
+                self.set_msg(true, format!("Merged {n} frames."));
+                self.refresh();
+            }
+            Err(e) => self.set_msg(false, e.to_string()),
+        }
+    }
+
+    /// Exports the frames shown in the log to `export_path` in `export_format`.
+    pub(crate) fn do_export(&mut self) {
+        self.update_log_search();
+        let Some(exporter) = self.exporters.get(&self.export_format) else {
+            self.set_msg(
+                false,
+                format!("Unknown export format \"{}\"", self.export_format),
+            );
+            return;
+        };
+        let path = match self.export_path.trim() {
+            "" => format!("watson-export.{}", exporter.extension()),
+            p => p.to_string(),
+        };
+        let filter = match self.query_filter(&self.log_query) {
+            Ok(filter) => filter,
+            Err(e) => {
+                self.set_msg(false, e.to_string());
+                return;
+            }
+        };
+        let frames: Vec<Frame> =
+            Self::filtered_frames(&self.frames, &self.log_from, &self.log_to, &filter)
+                .into_iter()
+                .filter(|f| self.in_log_search(f))
+                .cloned()
+                .collect();
+        if frames.is_empty() {
+            self.set_msg(false, "No frames to export.");
+            return;
+        }
+        let mut buf = Vec::new();
+        let result = exporter
+            .export(&frames, &ExportOptions::default(), &mut buf)
+            .map_err(|e| e.to_string())
+            .and_then(|()| std::fs::write(&path, &buf).map_err(|e| format!("{path}: {e}")));
+        match result {
+            Ok(()) => self.set_msg(true, format!("Exported {} frames to {path}.", frames.len())),
+            Err(e) => self.set_msg(false, format!("Export failed: {e}")),
+        }
+    }
+
+    /// Writes the epic form to config.toml, adding or updating the epic.
+    /// Projects that are neither used nor registered need `force`.
+    pub(crate) fn do_epic_save(&mut self) {
+        let epic = self.epic_form.to_epic();
+        if !self.epic_form.force {
+            match self.watson.unknown_epic_projects(&epic) {
+                Ok(unknown) if !unknown.is_empty() => {
+                    let names: Vec<String> = unknown.iter().map(|u| u.to_string()).collect();
+                    self.set_epic_msg(false, names.join("; "));
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    self.set_epic_msg(false, e.to_string());
+                    return;
+                }
+            }
+        }
+        let result = ConfigFile::open().and_then(|mut file| {
+            match &self.epic_form.original {
+                Some(name) => file.update_epic(name, &epic)?,
+                None => file.add_epic(&epic)?,
+            }
+            file.save()?;
+            file.config()
+        });
+        match result {
+            Ok(config) => {
+                self.config = config;
+                self.set_epic_msg(true, format!("Saved epic \"{}\".", epic.name));
+                self.epic_form = EpicForm::from_epic(&epic);
+            }
+            Err(e) => self.set_epic_msg(false, e.to_string()),
+        }
+    }
+
+    pub(crate) fn do_epic_remove(&mut self, name: &str) {
+        let result = ConfigFile::open().and_then(|mut file| {
+            file.remove_epic(name)?;
+            file.save()?;
+            file.config()
+        });
+        match result {
+            Ok(config) => {
+                self.config = config;
+                self.set_epic_msg(true, format!("Removed epic \"{name}\"."));
+                self.epic_form = EpicForm::default();
+                self.epic_delete_confirm = false;
+            }
+            Err(e) => self.set_epic_msg(false, e.to_string()),
+        }
+    }
+
     pub(crate) fn do_remove(&mut self, id: Uuid) {
         match self.watson.remove(id) {
             Ok(_) => {
@@ -259,3 +620,20 @@ impl WatsonApp {
         }
     }
 }
+
+fn load_projects(watson: &Watson<SqliteStorage>) -> (Vec<String>, HashMap<String, egui::Color32>) {
+    let list = watson.project_list().unwrap_or_default();
+    let colors = list
+        .iter()
+        .filter_map(|p| {
+            let (r, g, b) = p.rgb()?;
+            Some((p.name.clone(), egui::Color32::from_rgb(r, g, b)))
+        })
+        .collect();
+    let names = list
+        .into_iter()
+        .filter(|p| !p.archived)
+        .map(|p| p.name)
+        .collect();
+    (names, colors)
+}
@@ -5,3 +5,13 @@ pub(crate) const CLR_RED: egui::Color32 = egui::Color32::from_rgb(220, 80, 80);
 pub(crate) const CLR_PURPLE: egui::Color32 = egui::Color32::from_rgb(180, 100, 220);
 pub(crate) const CLR_CYAN: egui::Color32 = egui::Color32::from_rgb(100, 180, 220);
 pub(crate) const CLR_YELLOW: egui::Color32 = egui::Color32::from_rgb(220, 180, 80);
+
+/// Chart and calendar colors for projects without a registry color.
+pub(crate) const CHART_PALETTE: [egui::Color32; 6] = [
+    CLR_YELLOW,
+    CLR_CYAN,
+    CLR_PURPLE,
+    CLR_GREEN,
+    egui::Color32::from_rgb(97, 175, 239),
+    CLR_RED,
+];
@@ -1,5 +1,4 @@
 use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
-use rs_watson::Frame;
 
 pub(crate) fn fmt_duration(d: Duration) -> String {
     let total = d.num_seconds().max(0);
@@ -60,13 +59,6 @@ pub(crate) fn parse_local_date(s: &str) -> Option<NaiveDate> {
     }
 }
 
-pub(crate) fn collect_projects(frames: &[Frame]) -> Vec<String> {
-    let mut names: Vec<String> = frames.iter().map(|f| f.project.clone()).collect();
-    names.sort();
-    names.dedup();
-    names
-}
-
 pub(crate) fn parse_tags(s: &str) -> Vec<String> {
     s.split(',')
         .map(|t| t.trim().to_string())
@@ -3,7 +3,11 @@
 mod app;
 mod colors;
 mod format;
+mod shortcuts;
 mod tab_add;
+mod tab_calendar;
+mod tab_charts;
+mod tab_epics;
 mod tab_log;
 mod tab_report;
 mod types;
@@ -11,11 +15,13 @@ mod widgets;
 
 use chrono::Utc;
 use eframe::egui;
+use rs_watson::idle::{Overrun, StopSuggestion};
 
 use app::WatsonApp;
-use colors::{CLR_GREEN, CLR_RED};
-use format::{fmt_duration, fmt_tags};
-use types::Tab;
+use colors::{CLR_GREEN, CLR_RED, CLR_YELLOW};
+use format::{fmt_duration, fmt_local_dt, fmt_tags, parse_local_dt};
+use shortcuts::{CANCEL, OPEN_PALETTE, START, STOP};
+use types::{PaletteState, Tab};
 use widgets::{feedback_label, project_autocomplete};
 
 fn main() -> eframe::Result {
@@ -36,6 +42,63 @@ fn main() -> eframe::Result {
     )
 }
 
+impl WatsonApp {
+    /// Warns about a frame running past `max_frame_hours` and offers stop times.
+    fn show_overrun_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(max) = self.config.behavior.max_frame_duration() else {
+            return;
+        };
+        let Some(overrun) = self
+            .status
+            .as_ref()
+            .and_then(|active| Overrun::detect(active, max, Utc::now()))
+        else {
+            return;
+        };
+
+        let mut stop_at = None;
+        ui.add_space(4.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "⚠ Running for {} — longer than {}h. Forgot to stop?",
+                    fmt_duration(overrun.elapsed),
+                    self.config.behavior.max_frame_hours
+                ))
+                .color(CLR_YELLOW),
+            );
+            for (kind, at) in overrun.suggestions() {
+                let label = match kind {
+                    StopSuggestion::LastActivity => "last activity",
+                    StopSuggestion::Threshold => "threshold",
+                };
+                if ui
+                    .small_button(format!("Stop at {} ({label})", fmt_local_dt(at)))
+                    .clicked()
+                {
+                    stop_at = Some(at);
+                }
+            }
+            ui.add(
+                egui::TextEdit::singleline(&mut self.idle_custom_stop)
+                    .hint_text("YYYY-MM-DD HH:MM")
+                    .desired_width(130.0),
+            );
+            if ui.small_button("Stop at").clicked() {
+                match parse_local_dt(&self.idle_custom_stop) {
+                    Some(at) => stop_at = Some(at),
+                    None => self.set_msg(false, "Invalid stop time. Use YYYY-MM-DD HH:MM."),
+                }
+            }
+        });
+
+        if let Some(at) = stop_at {
+            self.idle_custom_stop.clear();
+            self.do_stop_at(at);
+        }
+    }
+}
+
 struct ErrorApp(String);
 
 impl eframe::App for ErrorApp {
@@ -53,10 +116,22 @@ impl eframe::App for WatsonApp {
         ui.ctx()
             .request_repaint_after(std::time::Duration::from_secs(1));
 
+        self.refresh_on_external_change();
+        if ui.input(|i| !i.events.is_empty()) {
+            self.record_activity();
+        }
+
+        let ctx = ui.ctx().clone();
+        self.handle_shortcuts(&ctx);
+        if self.palette.is_some() {
+            self.show_palette(&ctx);
+        }
         if self.edit_state.is_some() {
-            let ctx = ui.ctx().clone();
             self.show_edit_modal(&ctx);
         }
+        if self.split_state.is_some() {
+            self.show_split_modal(&ctx);
+        }
 
         egui::Panel::top("status_bar").show_inside(ui, |ui| {
             ui.add_space(5.0);
@@ -78,6 +153,7 @@ impl eframe::App for WatsonApp {
                     ui.label(egui::RichText::new("○ Not tracking").color(egui::Color32::GRAY));
                 }
             });
+            self.show_overrun_banner(ui);
             ui.add_space(4.0);
         });
 
@@ -103,6 +179,7 @@ impl eframe::App for WatsonApp {
 
                 let start_clicked = ui
                     .button(egui::RichText::new("▶  Start").color(CLR_GREEN))
+                    .on_hover_text(ctx.format_shortcut(&START))
                     .clicked();
                 let enter = proj_resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
                 if start_clicked || enter {
@@ -113,12 +190,14 @@ impl eframe::App for WatsonApp {
                 ui.add_enabled_ui(tracking, |ui| {
                     if ui
                         .button(egui::RichText::new("■  Stop").color(CLR_RED))
+                        .on_hover_text(ctx.format_shortcut(&STOP))
                         .clicked()
                     {
                         self.do_stop();
                     }
                     if ui
                         .button(egui::RichText::new("✕  Cancel").color(egui::Color32::GRAY))
+                        .on_hover_text(ctx.format_shortcut(&CANCEL))
                         .clicked()
                     {
                         self.do_cancel();
@@ -136,15 +215,30 @@ impl eframe::App for WatsonApp {
         egui::Panel::top("tabs").show_inside(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.active_tab, Tab::Log, "Log");
+                ui.selectable_value(&mut self.active_tab, Tab::Calendar, "Calendar");
                 ui.selectable_value(&mut self.active_tab, Tab::Add, "Add");
                 ui.selectable_value(&mut self.active_tab, Tab::Report, "Report");
+                ui.selectable_value(&mut self.active_tab, Tab::Charts, "Charts");
+                ui.selectable_value(&mut self.active_tab, Tab::Epics, "Epics");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .small_button("Commands")
+                        .on_hover_text(ctx.format_shortcut(&OPEN_PALETTE))
+                        .clicked()
+                    {
+                        self.palette = Some(PaletteState::default());
+                    }
+                });
             });
         });
 
         egui::CentralPanel::default().show_inside(ui, |ui| match self.active_tab {
             Tab::Log => self.show_log(ui),
+            Tab::Calendar => self.show_calendar(ui),
             Tab::Add => self.show_add(ui),
             Tab::Report => self.show_report(ui),
+            Tab::Charts => self.show_charts(ui),
+            Tab::Epics => self.show_epics(ui),
         });
     }
 }
@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+use rs_watson_storage::search::{text_score, words};
+use uuid::Uuid;
+
+use crate::app::WatsonApp;
+use crate::colors::CLR_CYAN;
+use crate::types::{PaletteAction, PaletteState, Tab};
+
+pub(crate) const OPEN_PALETTE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::K);
+pub(crate) const START: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Enter);
+pub(crate) const STOP: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::S);
+pub(crate) const CANCEL: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::X);
+const SAVE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Enter);
+
+/// Tabs in toolbar order, switched with Ctrl+1 … Ctrl+6.
+const TABS: [(Tab, &str, Key); 6] = [
+    (Tab::Log, "Log", Key::Num1),
+    (Tab::Calendar, "Calendar", Key::Num2),
+    (Tab::Add, "Add", Key::Num3),
+    (Tab::Report, "Report", Key::Num4),
+    (Tab::Charts, "Charts", Key::Num5),
+    (Tab::Epics, "Epics", Key::Num6),
+];
+
+/// How many recent project and tag combinations the palette offers.
+const RECENT_COMBOS: usize = 10;
+const PALETTE_ROWS: usize = 12;
+
+fn tab_shortcut(key: Key) -> KeyboardShortcut {
+    KeyboardShortcut::new(Modifiers::COMMAND, key)
+}
+
+/// A key pressed without Ctrl/Cmd; Shift and Alt are ignored.
+fn pressed(ctx: &egui::Context, key: Key) -> bool {
+    ctx.input_mut(|i| i.consume_key(Modifiers::NONE, key))
+}
+
+/// A navigation key pressed in the Log tab, applied with the tab's other mutations.
+pub(crate) enum LogKey {
+    /// The cursor moved to this frame.
+    Move(Uuid),
+    Edit(Uuid),
+    Split(Uuid),
+    /// Asks for confirmation first.
+    Delete(Uuid),
+    ConfirmDelete(Uuid),
+    CancelDelete,
+    /// The cursor was dismissed.
+    Leave,
+}
+
+/// An entry of the command palette.
+struct PaletteEntry {
+    label: String,
+    action: PaletteAction,
+    shortcut: Option<KeyboardShortcut>,
+}
+
+impl WatsonApp {
+    /// Handles the global shortcuts. Runs before any widget so that the keys
+    /// are consumed even while a text field has focus.
+    pub(crate) fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.palette.is_some() {
+            return;
+        }
+        if self.edit_state.is_some() || self.split_state.is_some() {
+            if ctx.input_mut(|i| i.consume_shortcut(&SAVE)) {
+                if self.edit_state.is_some() {
+                    self.do_edit_save();
+                } else {
+                    self.do_split_save();
+                }
+            } else if pressed(ctx, Key::Escape) {
+                self.edit_state = None;
+                self.split_state = None;
+            }
+            return;
+        }
+
+        if ctx.input_mut(|i| i.consume_shortcut(&OPEN_PALETTE)) {
+            self.palette = Some(PaletteState::default());
+        } else if ctx.input_mut(|i| i.consume_shortcut(&STOP)) {
+            if self.status.is_some() {
+                self.do_stop();
+            }
+        } else if ctx.input_mut(|i| i.consume_shortcut(&CANCEL)) {
+            if self.status.is_some() {
+                self.do_cancel();
+            }
+        } else if ctx.input_mut(|i| i.consume_shortcut(&START)) {
+            self.do_start();
+        }
+        for (tab, _, key) in TABS {
+            if ctx.input_mut(|i| i.consume_shortcut(&tab_shortcut(key))) {
+                self.active_tab = tab;
+            }
+        }
+    }
+
+    /// Reads the Log tab's navigation keys. `order` lists the shown frames
+    /// top to bottom. Ignored while a text field, modal or the palette has
+    /// the keyboard.
+    pub(crate) fn log_key(&self, ctx: &egui::Context, order: &[Uuid]) -> Option<LogKey> {
+        if self.palette.is_some()
+            || self.edit_state.is_some()
+            || self.split_state.is_some()
+            || ctx.text_edit_focused()
+        {
+            return None;
+        }
+        if let Some(id) = self.delete_confirm_id {
+            if pressed(ctx, Key::Y) || pressed(ctx, Key::Enter) {
+                return Some(LogKey::ConfirmDelete(id));
+            }
+            if pressed(ctx, Key::N) || pressed(ctx, Key::Escape) {
+                return Some(LogKey::CancelDelete);
+            }
+            return None;
+        }
+
+        let position = self
+            .log_cursor
+            .and_then(|id| order.iter().position(|o| *o == id));
+        let step = if pressed(ctx, Key::ArrowDown) || pressed(ctx, Key::J) {
+            1
+        } else if pressed(ctx, Key::ArrowUp) || pressed(ctx, Key::K) {
+            -1
+        } else {
+            0
+        };
+        if step != 0 {
+            let next = match position {
+                Some(p) => p
+                    .saturating_add_signed(step)
+                    .min(order.len().saturating_sub(1)),
+                None => 0,
+            };
+            return order.get(next).copied().map(LogKey::Move);
+        }
+
+        let id = order[position?];
+        if pressed(ctx, Key::Enter) || pressed(ctx, Key::E) {
+            Some(LogKey::Edit(id))
+        } else if pressed(ctx, Key::S) {
+            Some(LogKey::Split(id))
+        } else if pressed(ctx, Key::Delete) || pressed(ctx, Key::Backspace) {
+            Some(LogKey::Delete(id))
+        } else if pressed(ctx, Key::Escape) {
+            Some(LogKey::Leave)
+        } else {
+            None
+        }
+    }
+
+    /// Palette entries in their unfiltered order: commands, then recent
+    /// project and tag combinations, then every other project.
+    fn palette_entries(&self) -> Vec<PaletteEntry> {
+        let mut entries = Vec::new();
+        if let Some(active) = &self.status {
+            entries.push(PaletteEntry {
+                label: format!("Stop {}", active.project),
+                action: PaletteAction::Stop,
+                shortcut: Some(STOP),
+            });
+            entries.push(PaletteEntry {
+                label: format!("Cancel {}", active.project),
+                action: PaletteAction::Cancel,
+                shortcut: Some(CANCEL),
+            });
+        }
+        for (tab, name, key) in TABS {
+            entries.push(PaletteEntry {
+                label: format!("Go to {name}"),
+                action: PaletteAction::Open(tab),
+                shortcut: Some(tab_shortcut(key)),
+            });
+        }
+
+        let mut seen: HashSet<(&str, &[String])> = HashSet::new();
+        let recent = self
+            .frames
+            .iter()
+            .rev()
+            .filter(|f| seen.insert((f.project.as_str(), f.tags.as_slice())))
+            .take(RECENT_COMBOS);
+        let mut offered: HashSet<&str> = HashSet::new();
+        for frame in recent {
+            if frame.tags.is_empty() {
+                offered.insert(&frame.project);
+            }
+            entries.push(start_entry(&frame.project, &frame.tags));
+        }
+        for project in &self.projects {
+            if !offered.contains(project.as_str()) {
+                entries.push(start_entry(project, &[]));
+            }
+        }
+        entries
+    }
+
+    pub(crate) fn show_palette(&mut self, ctx: &egui::Context) {
+        let Some(query) = self.palette.as_ref().map(|p| p.query.clone()) else {
+            return;
+        };
+        // Consumed before the text field sees them.
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+                i.consume_key(Modifiers::NONE, Key::Escape) || i.consume_shortcut(&OPEN_PALETTE),
+            )
+        });
+        if escape {
+            self.palette = None;
+            return;
+        }
+
+        let terms = words(&query);
+        let mut matches: Vec<(f64, PaletteEntry)> = self
+            .palette_entries()
+            .into_iter()
+            .filter_map(|entry| {
+                if terms.is_empty() {
+                    return Some((0.0, entry));
+                }
+                text_score(&entry.label, &terms).map(|score| (score, entry))
+            })
+            .collect();
+        // Stable, so equal scores keep the unfiltered order.
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+        matches.truncate(PALETTE_ROWS);
+
+        let state = self.palette.as_mut().expect("palette is open");
+        if down {
+            state.selected = (state.selected + 1).min(matches.len().saturating_sub(1));
+        }
+        if up {
+            state.selected = state.selected.saturating_sub(1);
+        }
+        let mut chosen = enter
+            .then(|| matches.get(state.selected))
+            .flatten()
+            .map(|(_, e)| e.action.clone());
+
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+            .fixed_size([420.0, 0.0])
+            .show(ctx, |ui| {
+                let before = state.query.clone();
+                let field = ui.add(
+                    egui::TextEdit::singleline(&mut state.query)
+                        .hint_text("Start a project, stop, go to a tab…")
+                        .desired_width(f32::INFINITY),
+                );
+                field.request_focus();
+                if state.query != before {
+                    state.selected = 0;
+                }
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label(egui::RichText::new("No matches.").color(egui::Color32::GRAY));
+                }
+                for (i, (_, entry)) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let row = ui.selectable_label(i == state.selected, &entry.label);
+                        if row.clicked() {
+                            chosen = Some(entry.action.clone());
+                        }
+                        if let Some(shortcut) = &entry.shortcut {
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.label(
+                                        egui::RichText::new(ctx.format_shortcut(shortcut))
+                                            .color(CLR_CYAN)
+                                            .small(),
+                                    );
+                                },
+                            );
+                        }
+                    });
+                }
+            });
+
+        if let Some(action) = chosen {
+            self.palette = None;
+            match action {
+                PaletteAction::Start { project, tags } => {
+                    self.start_tracking(&project, tags);
+                }
+                PaletteAction::Stop => self.do_stop(),
+                PaletteAction::Cancel => self.do_cancel(),
+                PaletteAction::Open(tab) => self.active_tab = tab,
+            }
+        }
+    }
+}
+
+fn start_entry(project: &str, tags: &[String]) -> PaletteEntry {
+    let shown: String = tags.iter().map(|t| format!(" +{t}")).collect();
+    PaletteEntry {
+        label: format!("Start {project}{shown}"),
+        action: PaletteAction::Start {
+            project: project.to_string(),
+            tags: tags.to_vec(),
+        },
+        shortcut: None,
+    }
+}
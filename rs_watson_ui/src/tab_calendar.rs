@@ -0,0 +1,407 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use eframe::egui;
+use rs_watson::Frame;
+use uuid::Uuid;
+
+use crate::app::WatsonApp;
+use crate::colors::{CLR_GREEN, CLR_RED};
+use crate::format::fmt_time;
+use crate::types::{CalendarDrag, DragMode, EditState};
+
+const HOUR_HEIGHT: f32 = 36.0;
+/// Width of the hour labels left of the grid.
+const GUTTER: f32 = 44.0;
+const HEADER_HEIGHT: f32 = 22.0;
+/// Pointer distance from a block's top or bottom that resizes instead of moving.
+const EDGE: f32 = 6.0;
+/// Drags move and resize frames in steps of this many minutes.
+const SNAP_MINUTES: i64 = 5;
+
+/// What happened to a calendar block during this repaint.
+enum BlockAction {
+    StartDrag(CalendarDrag),
+    Drag(egui::Vec2),
+    Drop,
+    Edit,
+}
+
+impl WatsonApp {
+    pub(crate) fn show_calendar(&mut self, ui: &mut egui::Ui) {
+        let week_start = self.config.behavior.week_start;
+        ui.horizontal(|ui| {
+            if ui.button("◀").on_hover_text("Previous week").clicked() {
+                self.calendar_week -= Duration::weeks(1);
+            }
+            if ui.button("This week").clicked() {
+                self.calendar_week = week_start.start_of_week(Local::now().date_naive());
+            }
+            if ui.button("▶").on_hover_text("Next week").clicked() {
+                self.calendar_week += Duration::weeks(1);
+            }
+            ui.separator();
+            ui.label(
+                egui::RichText::new(self.calendar_week.format("Week of %d %B %Y").to_string())
+                    .strong(),
+            );
+            ui.label(
+                egui::RichText::new(
+                    "Drag a frame to move it, drag its top or bottom edge to resize, click to edit.",
+                )
+                .color(egui::Color32::GRAY)
+                .small(),
+            );
+        });
+        ui.separator();
+
+        let days: Vec<NaiveDate> = self.calendar_week.iter_days().take(7).collect();
+        let col_width = ((ui.available_width() - GUTTER) / 7.0).max(40.0);
+        let (header, _) = ui.allocate_exact_size(
+            egui::vec2(GUTTER + col_width * 7.0, HEADER_HEIGHT),
+            egui::Sense::hover(),
+        );
+        let today = Local::now().date_naive();
+        for (i, day) in days.iter().enumerate() {
+            let x = header.left() + GUTTER + col_width * (i as f32 + 0.5);
+            let color = if *day == today {
+                CLR_GREEN
+            } else {
+                egui::Color32::GRAY
+            };
+            ui.painter().text(
+                egui::pos2(x, header.center().y),
+                egui::Align2::CENTER_CENTER,
+                day.format("%a %d").to_string(),
+                egui::FontId::proportional(13.0),
+                color,
+            );
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt("calendar")
+            .show(ui, |ui| self.week_grid(ui, &days, col_width));
+    }
+
+    /// Hour lines, the week's frames as blocks, and the running frame.
+    fn week_grid(&mut self, ui: &mut egui::Ui, days: &[NaiveDate], col_width: f32) {
+        let (grid, _) = ui.allocate_exact_size(
+            egui::vec2(GUTTER + col_width * 7.0, 24.0 * HOUR_HEIGHT),
+            egui::Sense::hover(),
+        );
+        let painter = ui.painter_at(grid);
+        let line = egui::Stroke::new(1.0, egui::Color32::from_gray(60));
+        for hour in 0..=24 {
+            let y = grid.top() + hour as f32 * HOUR_HEIGHT;
+            painter.line_segment(
+                [
+                    egui::pos2(grid.left() + GUTTER, y),
+                    egui::pos2(grid.right(), y),
+                ],
+                line,
+            );
+            if hour < 24 {
+                painter.text(
+                    egui::pos2(grid.left() + GUTTER - 6.0, y + 2.0),
+                    egui::Align2::RIGHT_TOP,
+                    format!("{hour:02}:00"),
+                    egui::FontId::proportional(11.0),
+                    egui::Color32::GRAY,
+                );
+            }
+        }
+        for col in 0..=7 {
+            let x = grid.left() + GUTTER + col as f32 * col_width;
+            painter.line_segment(
+                [egui::pos2(x, grid.top()), egui::pos2(x, grid.bottom())],
+                line,
+            );
+        }
+
+        let geometry = Geometry {
+            grid,
+            col_width,
+            first_day: days[0],
+        };
+        let now = Utc::now();
+        let week_end = local_midnight(days[0] + Duration::days(7));
+        let week_begin = local_midnight(days[0]);
+
+        let mut action: Option<(Uuid, BlockAction)> = None;
+        for frame in &self.frames {
+            let (start, end) = match &self.calendar_drag {
+                Some(drag) if drag.id == frame.id => (drag.start, drag.end),
+                _ => (frame.start, frame.end),
+            };
+            if end <= week_begin || start >= week_end {
+                continue;
+            }
+            let color = self.chart_color(&frame.project);
+            for (index, (rect, has_start, has_end)) in
+                geometry.segments(start, end).into_iter().enumerate()
+            {
+                let id = ui.id().with(("calendar-frame", frame.id, index));
+                let response = ui.interact(rect, id, egui::Sense::click_and_drag());
+                paint_block(&painter, rect, color, frame, start, end, false);
+                if let Some(a) = block_action(
+                    ui,
+                    &response,
+                    frame,
+                    has_start,
+                    has_end,
+                    &self.calendar_drag,
+                ) {
+                    action = Some((frame.id, a));
+                }
+            }
+        }
+
+        if let Some(active) = &self.status {
+            let running = active.provisional(now);
+            let color = self.chart_color(&running.project);
+            for (rect, _, _) in geometry.segments(running.start, running.end) {
+                paint_block(
+                    &painter,
+                    rect,
+                    color,
+                    &running,
+                    running.start,
+                    running.end,
+                    true,
+                );
+            }
+        }
+
+        if let Some(col) = days
+            .iter()
+            .position(|d| *d == now.with_timezone(&Local).date_naive())
+        {
+            let y = geometry.y(now);
+            let x = grid.left() + GUTTER + col as f32 * col_width;
+            painter.line_segment(
+                [egui::pos2(x, y), egui::pos2(x + col_width, y)],
+                egui::Stroke::new(2.0, CLR_RED),
+            );
+        }
+
+        match action {
+            Some((_, BlockAction::StartDrag(drag))) => self.calendar_drag = Some(drag),
+            Some((id, BlockAction::Drag(delta))) => self.drag_preview(id, delta, col_width),
+            Some((id, BlockAction::Drop)) => self.drop_frame(id),
+            Some((id, BlockAction::Edit)) => {
+                if let Some(frame) = self.frames.iter().find(|f| f.id == id) {
+                    self.edit_state = Some(EditState::from_frame(frame));
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Updates the dragged frame's preview times from the pointer's offset.
+    fn drag_preview(&mut self, id: Uuid, delta: egui::Vec2, col_width: f32) {
+        let Some(frame) = self.frames.iter().find(|f| f.id == id) else {
+            return;
+        };
+        let Some(drag) = &mut self.calendar_drag else {
+            return;
+        };
+        let minutes = ((delta.y / HOUR_HEIGHT * 60.0) as i64 / SNAP_MINUTES) * SNAP_MINUTES;
+        let step = Duration::minutes(SNAP_MINUTES);
+        match drag.mode {
+            DragMode::Move => {
+                let days = (delta.x / col_width).round() as i64;
+                let shift = Duration::days(days) + Duration::minutes(minutes);
+                drag.start = frame.start + shift;
+                drag.end = frame.end + shift;
+            }
+            DragMode::ResizeStart => {
+                drag.start = (frame.start + Duration::minutes(minutes)).min(frame.end - step);
+            }
+            DragMode::ResizeEnd => {
+                drag.end = (frame.end + Duration::minutes(minutes)).max(frame.start + step);
+            }
+        }
+    }
+
+    /// Saves the dragged frame at its preview times. Overlaps and other
+    /// invalid edits are rejected by `Watson::edit` and leave the frame as it was.
+    fn drop_frame(&mut self, id: Uuid) {
+        let Some(drag) = self.calendar_drag.take() else {
+            return;
+        };
+        let Some(frame) = self.frames.iter().find(|f| f.id == id) else {
+            return;
+        };
+        if (drag.start, drag.end) == (frame.start, frame.end) {
+            return;
+        }
+        let (project, tags) = (frame.project.clone(), frame.tags.clone());
+        match self.watson.edit(id, project, tags, drag.start, drag.end) {
+            Ok(f) => {
+                self.set_msg(
+                    true,
+                    format!(
+                        "Moved {} to {} – {}.",
+                        f.project,
+                        fmt_time(f.start),
+                        fmt_time(f.end)
+                    ),
+                );
+                self.refresh();
+            }
+            Err(e) => self.set_msg(false, e.to_string()),
+        }
+    }
+}
+
+/// Where the days and hours of the week sit on screen.
+struct Geometry {
+    grid: egui::Rect,
+    col_width: f32,
+    first_day: NaiveDate,
+}
+
+impl Geometry {
+    fn y(&self, at: DateTime<Utc>) -> f32 {
+        let local = at.with_timezone(&Local);
+        let minutes =
+            (local.naive_local() - local.date_naive().and_time(Default::default())).num_minutes();
+        self.grid.top() + minutes as f32 / 60.0 * HOUR_HEIGHT
+    }
+
+    /// One rectangle per day the interval covers within the week, with flags
+    /// for whether it holds the interval's start and end.
+    fn segments(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<(egui::Rect, bool, bool)> {
+        let mut segments = Vec::new();
+        for col in 0..7 {
+            let day = self.first_day + Duration::days(col);
+            let (day_start, day_end) =
+                (local_midnight(day), local_midnight(day + Duration::days(1)));
+            if end <= day_start || start >= day_end {
+                continue;
+            }
+            let top = if start > day_start {
+                self.y(start)
+            } else {
+                self.grid.top()
+            };
+            let bottom = if end < day_end {
+                self.y(end)
+            } else {
+                self.grid.bottom()
+            };
+            let x = self.grid.left() + GUTTER + col as f32 * self.col_width;
+            let rect = egui::Rect::from_min_max(
+                egui::pos2(x + 2.0, top),
+                egui::pos2(x + self.col_width - 2.0, bottom.max(top + 4.0)),
+            );
+            segments.push((rect, start >= day_start, end <= day_end));
+        }
+        segments
+    }
+}
+
+/// The start of `day` in local time; the earliest one when DST repeats midnight.
+fn local_midnight(day: NaiveDate) -> DateTime<Utc> {
+    let midnight = day.and_time(Default::default());
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map_or_else(|| midnight.and_utc(), |dt| dt.with_timezone(&Utc))
+}
+
+fn hour_minute(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local).format("%H:%M").to_string()
+}
+
+fn paint_block(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    color: egui::Color32,
+    frame: &Frame,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    running: bool,
+) {
+    painter.rect_filled(rect, 3.0, color.gamma_multiply(0.8));
+    if running {
+        painter.rect_stroke(
+            rect,
+            3.0,
+            egui::Stroke::new(2.0, CLR_GREEN),
+            egui::StrokeKind::Inside,
+        );
+    }
+    let text = painter.with_clip_rect(rect.shrink(2.0));
+    let ink = egui::Color32::from_gray(20);
+    text.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        &frame.project,
+        egui::FontId::proportional(12.0),
+        ink,
+    );
+    if rect.height() > 30.0 {
+        text.text(
+            rect.left_top() + egui::vec2(4.0, 16.0),
+            egui::Align2::LEFT_TOP,
+            format!("{} – {}", hour_minute(start), hour_minute(end)),
+            egui::FontId::proportional(11.0),
+            ink,
+        );
+    }
+}
+
+/// Turns pointer input on a block into an action. Resizing is offered on the
+/// segments holding the frame's start or end.
+fn block_action(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    frame: &Frame,
+    has_start: bool,
+    has_end: bool,
+    drag: &Option<CalendarDrag>,
+) -> Option<BlockAction> {
+    let edge = |pos: egui::Pos2| {
+        if has_start && pos.y - response.rect.top() < EDGE {
+            Some(DragMode::ResizeStart)
+        } else if has_end && response.rect.bottom() - pos.y < EDGE {
+            Some(DragMode::ResizeEnd)
+        } else {
+            None
+        }
+    };
+    if let Some(pos) = response.hover_pos()
+        && edge(pos).is_some()
+    {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeVertical);
+    }
+
+    let pointer = response.interact_pointer_pos();
+    if response.drag_started() {
+        // Measure from where the button went down, not where the drag threshold was crossed.
+        let origin = ui.input(|i| i.pointer.press_origin()).or(pointer)?;
+        return Some(BlockAction::StartDrag(CalendarDrag {
+            id: frame.id,
+            mode: edge(origin).unwrap_or(DragMode::Move),
+            origin,
+            start: frame.start,
+            end: frame.end,
+        }));
+    }
+    let dragging = drag.as_ref().filter(|d| d.id == frame.id);
+    if response.drag_stopped() && dragging.is_some() {
+        return Some(BlockAction::Drop);
+    }
+    if response.dragged()
+        && let (Some(drag), Some(pos)) = (dragging, pointer)
+    {
+        ui.ctx().set_cursor_icon(match drag.mode {
+            DragMode::Move => egui::CursorIcon::Grabbing,
+            _ => egui::CursorIcon::ResizeVertical,
+        });
+        return Some(BlockAction::Drag(pos - drag.origin));
+    }
+    if response.clicked() {
+        return Some(BlockAction::Edit);
+    }
+    None
+}
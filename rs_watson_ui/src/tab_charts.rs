@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use chrono::{Duration, Local, NaiveDate, Utc};
+use eframe::egui;
+use rs_watson::{Frame, Report};
+
+use crate::app::WatsonApp;
+use crate::format::fmt_duration;
+use crate::widgets::{date_filter_bar, empty_frames};
+
+const PIE_SIZE: f32 = 200.0;
+const BAR_HEIGHT: f32 = 200.0;
+
+impl WatsonApp {
+    pub(crate) fn show_charts(&mut self, ui: &mut egui::Ui) {
+        let filter = self.query_filter(&self.charts_query);
+        ui.horizontal(|ui| {
+            date_filter_bar(
+                ui,
+                &mut self.charts_from,
+                &mut self.charts_to,
+                &mut self.charts_query,
+                filter.as_ref().err().map(ToString::to_string),
+            );
+        });
+        ui.separator();
+
+        let running: Vec<Frame> = self
+            .status
+            .iter()
+            .map(|active| active.provisional(Utc::now()))
+            .collect();
+        let filter = filter.unwrap_or_default();
+        let visible: Vec<Frame> =
+            Self::filtered_frames(&self.frames, &self.charts_from, &self.charts_to, &filter)
+                .into_iter()
+                .chain(Self::filtered_frames(
+                    &running,
+                    &self.charts_from,
+                    &self.charts_to,
+                    &filter,
+                ))
+                .cloned()
+                .collect();
+
+        if visible.is_empty() {
+            empty_frames(ui);
+            return;
+        }
+
+        let report = Report::from_frames(&visible);
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.heading("Projects");
+            ui.horizontal_top(|ui| {
+                self.pie_chart(ui, &report);
+                ui.add_space(16.0);
+                self.pie_legend(ui, &report);
+            });
+            ui.add_space(12.0);
+            ui.heading("Per day");
+            self.day_bars(ui, &visible);
+        });
+    }
+
+    fn pie_chart(&self, ui: &mut egui::Ui, report: &Report) {
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(PIE_SIZE, PIE_SIZE), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        let center = rect.center();
+        let radius = PIE_SIZE / 2.0 - 4.0;
+        let total = report.total.num_seconds().max(1) as f32;
+
+        // Angles run clockwise from twelve o'clock.
+        let hovered_angle = response.hover_pos().and_then(|pos| {
+            let v = pos - center;
+            (v.length() <= radius).then(|| (v.y.atan2(v.x) + FRAC_PI_2).rem_euclid(TAU))
+        });
+        let mut angle = 0.0;
+        for project in &report.projects {
+            let sweep = project.total.num_seconds() as f32 / total * TAU;
+            let color = self.chart_color(&project.name);
+            // Convex polygons cannot exceed half a turn, so wide slices are drawn in pieces.
+            let pieces = (sweep / FRAC_PI_2).ceil().max(1.0) as usize;
+            for i in 0..pieces {
+                let from = angle + sweep * i as f32 / pieces as f32;
+                let to = angle + sweep * (i + 1) as f32 / pieces as f32;
+                let steps = ((to - from) / 0.05).ceil().max(1.0) as usize;
+                let mut points = vec![center];
+                points.extend((0..=steps).map(|s| {
+                    let a = from + (to - from) * s as f32 / steps as f32 - FRAC_PI_2;
+                    center + radius * egui::vec2(a.cos(), a.sin())
+                }));
+                painter.add(egui::Shape::convex_polygon(
+                    points,
+                    color,
+                    egui::Stroke::NONE,
+                ));
+            }
+            if hovered_angle.is_some_and(|a| a >= angle && a < angle + sweep) {
+                response.clone().on_hover_text(format!(
+                    "{}: {} ({:.0}%)",
+                    project.name,
+                    fmt_duration(project.total),
+                    sweep / TAU * 100.0
+                ));
+            }
+            angle += sweep;
+        }
+    }
+
+    fn pie_legend(&self, ui: &mut egui::Ui, report: &Report) {
+        let total = report.total.num_seconds().max(1) as f64;
+        egui::Grid::new("chart_legend")
+            .num_columns(4)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                for project in &report.projects {
+                    let (swatch, _) =
+                        ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                    ui.painter()
+                        .rect_filled(swatch, 2.0, self.chart_color(&project.name));
+                    ui.label(&project.name);
+                    ui.label(fmt_duration(project.total));
+                    ui.label(format!(
+                        "{:.0}%",
+                        project.total.num_seconds() as f64 / total * 100.0
+                    ));
+                    ui.end_row();
+                }
+                ui.label("");
+                ui.label(egui::RichText::new("Total").strong());
+                ui.label(egui::RichText::new(fmt_duration(report.total)).strong());
+                ui.end_row();
+            });
+    }
+
+    /// One bar per day of the range, stacked by project in report order.
+    fn day_bars(&self, ui: &mut egui::Ui, frames: &[Frame]) {
+        let mut days: BTreeMap<NaiveDate, BTreeMap<&str, Duration>> = BTreeMap::new();
+        for frame in frames {
+            *days
+                .entry(frame.start.with_timezone(&Local).date_naive())
+                .or_default()
+                .entry(frame.project.as_str())
+                .or_insert_with(Duration::zero) += frame.end - frame.start;
+        }
+        let (Some(&first), Some(&last)) = (days.keys().next(), days.keys().next_back()) else {
+            return;
+        };
+        let order: Vec<String> = Report::from_frames(frames)
+            .projects
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let range: Vec<NaiveDate> = first.iter_days().take_while(|d| *d <= last).collect();
+        let longest = days
+            .values()
+            .map(|p| p.values().fold(Duration::zero(), |a, d| a + *d))
+            .max()
+            .unwrap_or_else(Duration::zero)
+            .num_seconds()
+            .max(1) as f32;
+
+        let width = ui.available_width().max(200.0);
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(width, BAR_HEIGHT + 20.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        let slot = width / range.len() as f32;
+        let bar_width = (slot * 0.7).max(1.0);
+        // Label every n-th day so the dates do not overlap.
+        let label_every = (60.0 / slot).ceil().max(1.0) as usize;
+        let baseline = rect.top() + BAR_HEIGHT;
+
+        for (i, day) in range.iter().enumerate() {
+            let x = rect.left() + slot * i as f32 + (slot - bar_width) / 2.0;
+            let mut y = baseline;
+            let mut tooltip = Vec::new();
+            if let Some(projects) = days.get(day) {
+                for name in &order {
+                    let Some(d) = projects.get(name.as_str()) else {
+                        continue;
+                    };
+                    let h = d.num_seconds() as f32 / longest * BAR_HEIGHT;
+                    let bar = egui::Rect::from_min_max(
+                        egui::pos2(x, y - h),
+                        egui::pos2(x + bar_width, y),
+                    );
+                    painter.rect_filled(bar, 0.0, self.chart_color(name));
+                    tooltip.push(format!("{name}: {}", fmt_duration(*d)));
+                    y -= h;
+                }
+            }
+            if i % label_every == 0 {
+                painter.text(
+                    egui::pos2(x + bar_width / 2.0, baseline + 4.0),
+                    egui::Align2::CENTER_TOP,
+                    day.format("%d %b").to_string(),
+                    egui::FontId::proportional(11.0),
+                    egui::Color32::GRAY,
+                );
+            }
+            let column = egui::Rect::from_min_max(
+                egui::pos2(rect.left() + slot * i as f32, rect.top()),
+                egui::pos2(rect.left() + slot * (i + 1) as f32, baseline),
+            );
+            if response.hover_pos().is_some_and(|p| column.contains(p)) && !tooltip.is_empty() {
+                response.clone().on_hover_text(format!(
+                    "{}\n{}",
+                    day.format("%a %d %b %Y"),
+                    tooltip.join("\n")
+                ));
+            }
+        }
+        painter.line_segment(
+            [
+                egui::pos2(rect.left(), baseline),
+                egui::pos2(rect.right(), baseline),
+            ],
+            egui::Stroke::new(1.0, egui::Color32::from_gray(90)),
+        );
+    }
+}
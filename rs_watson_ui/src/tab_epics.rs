@@ -0,0 +1,142 @@
+use eframe::egui;
+
+use crate::app::WatsonApp;
+use crate::colors::{CLR_CYAN, CLR_RED};
+use crate::types::EpicForm;
+use crate::widgets::feedback_label;
+
+impl WatsonApp {
+    pub(crate) fn show_epics(&mut self, ui: &mut egui::Ui) {
+        ui.add_space(8.0);
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                ui.set_width(180.0);
+                if ui.button("+  New epic").clicked() {
+                    self.epic_form = EpicForm::default();
+                    self.epic_delete_confirm = false;
+                    self.epic_message = None;
+                }
+                ui.add_space(4.0);
+                let mut selected = None;
+                for epic in &self.config.epics {
+                    let current = self.epic_form.original.as_deref() == Some(epic.name.as_str());
+                    let label = egui::RichText::new(&epic.name).color(CLR_CYAN);
+                    if ui.selectable_label(current, label).clicked() {
+                        selected = Some(EpicForm::from_epic(epic));
+                    }
+                }
+                if self.config.epics.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No epics configured.").color(egui::Color32::GRAY),
+                    );
+                }
+                if let Some(form) = selected {
+                    self.epic_form = form;
+                    self.epic_delete_confirm = false;
+                    self.epic_message = None;
+                }
+            });
+            ui.separator();
+            ui.vertical(|ui| self.epic_editor(ui));
+        });
+    }
+
+    fn epic_editor(&mut self, ui: &mut egui::Ui) {
+        let form = &mut self.epic_form;
+        egui::Grid::new("epic_grid")
+            .num_columns(2)
+            .spacing([12.0, 10.0])
+            .show(ui, |ui| {
+                ui.label("Name");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.name)
+                        .hint_text("Client work")
+                        .desired_width(240.0),
+                );
+                ui.end_row();
+
+                ui.label("Projects");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.projects)
+                        .hint_text("backend, frontend  (comma-separated)")
+                        .desired_width(240.0),
+                );
+                ui.end_row();
+
+                ui.label("Project glob");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.glob)
+                        .hint_text("acme-*")
+                        .desired_width(240.0),
+                );
+                ui.end_row();
+
+                ui.label("Project regex");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.regex)
+                        .hint_text("client-[0-9]+")
+                        .desired_width(240.0),
+                );
+                ui.end_row();
+
+                ui.label("Tags");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.tags)
+                        .hint_text("billable  (all must match)")
+                        .desired_width(240.0),
+                );
+                ui.end_row();
+
+                ui.label("Exclude tags");
+                ui.add(
+                    egui::TextEdit::singleline(&mut form.exclude_tags)
+                        .hint_text("wip")
+                        .desired_width(240.0),
+                );
+                ui.end_row();
+
+                ui.label("Priority");
+                ui.add(egui::DragValue::new(&mut form.priority));
+                ui.end_row();
+            });
+
+        ui.add_space(8.0);
+        ui.checkbox(
+            &mut self.epic_form.force,
+            "Allow projects that are not used or registered yet",
+        );
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            let label = if self.epic_form.original.is_some() {
+                "Save Epic"
+            } else {
+                "Add Epic"
+            };
+            if ui.button(egui::RichText::new(label).strong()).clicked() {
+                self.do_epic_save();
+            }
+            let Some(name) = self.epic_form.original.clone() else {
+                return;
+            };
+            if self.epic_delete_confirm {
+                ui.label(egui::RichText::new(format!("Remove \"{name}\"?")).color(CLR_RED));
+                if ui.button("Yes").clicked() {
+                    self.do_epic_remove(&name);
+                }
+                if ui.button("No").clicked() {
+                    self.epic_delete_confirm = false;
+                }
+            } else if ui
+                .button(egui::RichText::new("Remove").color(CLR_RED))
+                .clicked()
+            {
+                self.epic_delete_confirm = true;
+            }
+        });
+
+        if let Some(msg) = &self.epic_message.clone() {
+            ui.add_space(6.0);
+            feedback_label(ui, msg, self.epic_message_is_error, false);
+        }
+    }
+}
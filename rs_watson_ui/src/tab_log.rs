@@ -1,25 +1,113 @@
 use std::collections::BTreeMap;
 
-use chrono::{Duration, Local, NaiveDate};
+use chrono::{Duration, Local, NaiveDate, Utc};
 use eframe::egui;
 use rs_watson::Frame;
+use rs_watson::gaps::{Gap, find_gaps};
 use uuid::Uuid;
 
 use crate::app::WatsonApp;
-use crate::colors::{CLR_CYAN, CLR_PURPLE, CLR_RED};
-use crate::format::{fmt_duration, fmt_time};
-use crate::types::EditState;
+use crate::colors::{CLR_CYAN, CLR_PURPLE, CLR_RED, CLR_YELLOW};
+use crate::format::{fmt_duration, fmt_local_dt, fmt_time};
+use crate::shortcuts::LogKey;
+use crate::types::{EditState, SplitState, Tab};
 use crate::widgets::{date_filter_bar, empty_frames};
 
+/// A line in the log: a recorded frame or an untracked gap between frames.
+enum LogRow<'a> {
+    Frame(&'a Frame),
+    Gap(Gap),
+}
+
+impl LogRow<'_> {
+    fn start(&self) -> chrono::DateTime<Utc> {
+        match self {
+            LogRow::Frame(f) => f.start,
+            LogRow::Gap(g) => g.start,
+        }
+    }
+}
+
 impl WatsonApp {
     pub(crate) fn show_log(&mut self, ui: &mut egui::Ui) {
+        let mut merge = false;
+        let mut clear_selection = false;
+        let filter = self.query_filter(&self.log_query);
         ui.horizontal(|ui| {
-            date_filter_bar(ui, &mut self.log_from, &mut self.log_to);
+            date_filter_bar(
+                ui,
+                &mut self.log_from,
+                &mut self.log_to,
+                &mut self.log_query,
+                filter.as_ref().err().map(ToString::to_string),
+            );
+            if !self.merge_selection.is_empty() {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Clear").clicked() {
+                        clear_selection = true;
+                    }
+                    let n = self.merge_selection.len();
+                    if ui
+                        .add_enabled(n >= 2, egui::Button::new(format!("Merge selected ({n})")))
+                        .clicked()
+                    {
+                        merge = true;
+                    }
+                });
+            }
+        });
+        let mut export = false;
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.log_search)
+                    .hint_text("project or tag, typos are fine")
+                    .desired_width(220.0),
+            );
+            ui.separator();
+            ui.label("Export as");
+            egui::ComboBox::from_id_salt("log-export-format")
+                .selected_text(self.export_format.as_str())
+                .show_ui(ui, |ui| {
+                    for exporter in self.exporters.iter() {
+                        ui.selectable_value(
+                            &mut self.export_format,
+                            exporter.name().to_string(),
+                            exporter.name(),
+                        )
+                        .on_hover_text(exporter.description());
+                    }
+                });
+            let ext = self
+                .exporters
+                .get(&self.export_format)
+                .map_or("", |e| e.extension());
+            ui.add(
+                egui::TextEdit::singleline(&mut self.export_path)
+                    .hint_text(format!("watson-export.{ext}"))
+                    .desired_width(200.0),
+            );
+            export = ui.button("Export").clicked();
         });
         ui.separator();
+        if export {
+            self.do_export();
+        } else if merge {
+            self.do_merge();
+        } else if clear_selection {
+            self.merge_selection.clear();
+        }
 
-        let visible: Vec<&Frame> =
-            Self::filtered_frames(&self.frames, &self.log_from, &self.log_to);
+        self.update_log_search();
+        let visible: Vec<&Frame> = Self::filtered_frames(
+            &self.frames,
+            &self.log_from,
+            &self.log_to,
+            &filter.unwrap_or_default(),
+        )
+        .into_iter()
+        .filter(|f| self.in_log_search(f))
+        .collect();
         if visible.is_empty() {
             empty_frames(ui);
             return;
@@ -27,9 +115,31 @@ impl WatsonApp {
 
         // Collect mutations requested during rendering — avoids borrow conflicts.
         let mut to_edit: Option<Uuid> = None;
+        let mut to_split: Option<Uuid> = None;
+        let mut toggle_selected: Option<Uuid> = None;
         let mut to_delete: Option<Uuid> = None;
         let mut confirmed_delete: Option<Uuid> = None;
         let mut cancel_delete = false;
+        let mut to_fill: Option<Gap> = None;
+
+        let mut order: Vec<&Frame> = visible.clone();
+        order.sort_by_key(|f| std::cmp::Reverse(f.start));
+        let order: Vec<Uuid> = order.iter().map(|f| f.id).collect();
+        let mut cursor = self.log_cursor;
+        let mut scroll_to_cursor = false;
+        match self.log_key(ui.ctx(), &order) {
+            Some(LogKey::Move(id)) => {
+                cursor = Some(id);
+                scroll_to_cursor = true;
+            }
+            Some(LogKey::Edit(id)) => to_edit = Some(id),
+            Some(LogKey::Split(id)) => to_split = Some(id),
+            Some(LogKey::Delete(id)) => to_delete = Some(id),
+            Some(LogKey::ConfirmDelete(id)) => confirmed_delete = Some(id),
+            Some(LogKey::CancelDelete) => cancel_delete = true,
+            Some(LogKey::Leave) => cursor = None,
+            None => {}
+        }
 
         let mut by_day: BTreeMap<NaiveDate, Vec<&Frame>> = BTreeMap::new();
         for f in visible.iter().rev() {
@@ -39,6 +149,29 @@ impl WatsonApp {
                 .push(f);
         }
 
+        // Gaps within working hours on the shown days; the running frame counts as tracked.
+        let now = Utc::now();
+        let local_date = |dt: chrono::DateTime<Utc>| dt.with_timezone(&Local).date_naive();
+        let (first_day, last_day) = (
+            *by_day.keys().next().expect("visible is non-empty"),
+            *by_day.keys().next_back().expect("visible is non-empty"),
+        );
+        let tracked: Vec<Frame> = self
+            .frames
+            .iter()
+            .filter(|f| local_date(f.end) >= first_day && local_date(f.start) <= last_day)
+            .cloned()
+            .chain(self.status.as_ref().map(|a| a.provisional(now)))
+            .collect();
+        let gaps = find_gaps(
+            &tracked,
+            first_day,
+            last_day,
+            &self.config.working_hours,
+            &Local,
+            now,
+        );
+
         egui::ScrollArea::vertical().show(ui, |ui| {
             for (date, day_frames) in by_day.iter().rev() {
                 let day_total = day_frames
@@ -55,7 +188,48 @@ impl WatsonApp {
                 });
                 ui.separator();
 
-                for frame in day_frames {
+                let mut rows: Vec<LogRow> = day_frames.iter().map(|f| LogRow::Frame(f)).collect();
+                rows.extend(
+                    gaps.iter()
+                        .filter(|g| local_date(g.start) == *date)
+                        .map(|g| LogRow::Gap(*g)),
+                );
+                rows.sort_by_key(|r| std::cmp::Reverse(r.start()));
+
+                for row in rows {
+                    let frame = match row {
+                        LogRow::Frame(f) => f,
+                        LogRow::Gap(gap) => {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{}  →  {}",
+                                        fmt_time(gap.start),
+                                        fmt_time(gap.end)
+                                    ))
+                                    .monospace()
+                                    .color(CLR_YELLOW),
+                                );
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} untracked",
+                                        fmt_duration(gap.duration())
+                                    ))
+                                    .italics()
+                                    .color(CLR_YELLOW),
+                                );
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        if ui.small_button("Fill").clicked() {
+                                            to_fill = Some(gap);
+                                        }
+                                    },
+                                );
+                            });
+                            continue;
+                        }
+                    };
                     if self.delete_confirm_id == Some(frame.id) {
                         ui.horizontal(|ui| {
                             ui.label(
@@ -78,7 +252,16 @@ impl WatsonApp {
                             }
                         });
                     } else {
-                        ui.horizontal(|ui| {
+                        let highlight = ui.painter().add(egui::Shape::Noop);
+                        let row = ui.horizontal(|ui| {
+                            let mut selected = self.merge_selection.contains(&frame.id);
+                            if ui
+                                .checkbox(&mut selected, "")
+                                .on_hover_text("Select for merge")
+                                .changed()
+                            {
+                                toggle_selected = Some(frame.id);
+                            }
                             ui.label(
                                 egui::RichText::new(format!(
                                     "{}  →  {}",
@@ -93,7 +276,11 @@ impl WatsonApp {
                                     .monospace()
                                     .color(CLR_PURPLE),
                             );
-                            ui.label(egui::RichText::new(&frame.project).strong());
+                            let mut project = egui::RichText::new(&frame.project).strong();
+                            if let Some(color) = self.project_color(&frame.project) {
+                                project = project.color(color);
+                            }
+                            ui.label(project);
                             if !frame.tags.is_empty() {
                                 ui.label(
                                     egui::RichText::new(format!("[{}]", frame.tags.join(", ")))
@@ -113,9 +300,25 @@ impl WatsonApp {
                                     if ui.small_button("Edit").clicked() {
                                         to_edit = Some(frame.id);
                                     }
+                                    if ui.small_button("Split").clicked() {
+                                        to_split = Some(frame.id);
+                                    }
                                 },
                             );
                         });
+                        if cursor == Some(frame.id) {
+                            ui.painter().set(
+                                highlight,
+                                egui::Shape::rect_filled(
+                                    row.response.rect.expand(2.0),
+                                    3.0,
+                                    ui.visuals().selection.bg_fill.gamma_multiply(0.35),
+                                ),
+                            );
+                            if scroll_to_cursor {
+                                row.response.scroll_to_me(None);
+                            }
+                        }
                     }
                 }
             }
@@ -123,6 +326,7 @@ impl WatsonApp {
         });
 
         // Apply mutations after rendering
+        self.log_cursor = cursor;
         if let Some(id) = confirmed_delete {
             self.do_remove(id);
         } else if cancel_delete {
@@ -139,6 +343,26 @@ impl WatsonApp {
             self.edit_state = Some(EditState::from_frame(&id.1));
             self.delete_confirm_id = None;
         }
+        if let Some(frame) = to_split.and_then(|id| self.frames.iter().find(|f| f.id == id)) {
+            self.split_state = Some(SplitState::from_frame(frame));
+            self.delete_confirm_id = None;
+        }
+        if let Some(gap) = to_fill {
+            let neighbor = gap.neighbor(&tracked);
+            self.add_project = neighbor.map(|f| f.project.clone()).unwrap_or_default();
+            self.add_tags = neighbor.map(|f| f.tags.join(", ")).unwrap_or_default();
+            self.add_from = fmt_local_dt(gap.start);
+            self.add_to = fmt_local_dt(gap.end);
+            self.add_message = None;
+            self.active_tab = Tab::Add;
+        }
+        if let Some(id) = toggle_selected {
+            if let Some(pos) = self.merge_selection.iter().position(|s| *s == id) {
+                self.merge_selection.remove(pos);
+            } else {
+                self.merge_selection.push(id);
+            }
+        }
     }
 
     pub(crate) fn show_edit_modal(&mut self, ctx: &egui::Context) {
@@ -199,4 +423,59 @@ impl WatsonApp {
             self.edit_state = None;
         }
     }
+
+    pub(crate) fn show_split_modal(&mut self, ctx: &egui::Context) {
+        let Some(state) = &mut self.split_state else {
+            return;
+        };
+        let mut save = false;
+        let mut close = false;
+
+        egui::Window::new("Split Frame")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("split_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 8.0])
+                    .show(ui, |ui| {
+                        ui.label("Split at");
+                        ui.add(egui::TextEdit::singleline(&mut state.at).desired_width(220.0));
+                        ui.end_row();
+
+                        ui.label("Then project");
+                        ui.add(egui::TextEdit::singleline(&mut state.project).desired_width(220.0));
+                        ui.end_row();
+
+                        ui.label("Then tags");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut state.tags)
+                                .hint_text("api, auth")
+                                .desired_width(220.0),
+                        );
+                        ui.end_row();
+                    });
+
+                if let Some(err) = &state.error {
+                    ui.colored_label(CLR_RED, err);
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(egui::RichText::new("Split").strong()).clicked() {
+                        save = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if save {
+            self.do_split_save();
+        } else if close {
+            self.split_state = None;
+        }
+    }
 }
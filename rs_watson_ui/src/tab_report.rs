@@ -1,26 +1,59 @@
-use chrono::Duration;
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
 use eframe::egui;
-use rs_watson::{Frame, Report, resolve_epic};
+use rs_watson::report::{Dimension, PivotContext, PivotNode, ProjectNode, pivot};
+use rs_watson::stats::StatsContext;
+use rs_watson::{EpicResolver, Frame, Report, Stats};
+use uuid::Uuid;
 
 use crate::app::WatsonApp;
-use crate::colors::{CLR_CYAN, CLR_PURPLE, CLR_YELLOW};
+use crate::colors::{CLR_CYAN, CLR_GREEN, CLR_PURPLE, CLR_YELLOW};
 use crate::format::fmt_duration;
 use crate::widgets::{date_filter_bar, empty_frames};
 
 impl WatsonApp {
     pub(crate) fn show_report(&mut self, ui: &mut egui::Ui) {
+        let filter = self.query_filter(&self.report_query);
         ui.horizontal(|ui| {
-            date_filter_bar(ui, &mut self.report_from, &mut self.report_to);
-            if !self.config.epics.is_empty() {
+            date_filter_bar(
+                ui,
+                &mut self.report_from,
+                &mut self.report_to,
+                &mut self.report_query,
+                filter.as_ref().err().map(ToString::to_string),
+            );
+            if !self.config.epics.is_empty() && self.report_group_by.is_empty() {
                 ui.separator();
                 ui.checkbox(&mut self.report_use_epics, "By Epic");
             }
+            if self.status.is_some() {
+                ui.separator();
+                ui.checkbox(&mut self.report_include_running, "Include running");
+            }
         });
+        self.group_by_bar(ui);
         ui.separator();
 
+        // The running frame is materialized fresh on every repaint so totals tick live.
+        let running: Vec<Frame> = self
+            .status
+            .iter()
+            .filter(|_| self.report_include_running)
+            .map(|active| active.provisional(Utc::now()))
+            .collect();
+        let running_id = running.first().map(|f| f.id);
+
+        let filter = filter.unwrap_or_default();
         let visible: Vec<Frame> =
-            Self::filtered_frames(&self.frames, &self.report_from, &self.report_to)
+            Self::filtered_frames(&self.frames, &self.report_from, &self.report_to, &filter)
                 .into_iter()
+                .chain(Self::filtered_frames(
+                    &running,
+                    &self.report_from,
+                    &self.report_to,
+                    &filter,
+                ))
                 .cloned()
                 .collect();
 
@@ -30,25 +63,212 @@ impl WatsonApp {
         }
 
         egui::ScrollArea::vertical().show(ui, |ui| {
-            if self.report_use_epics && !self.config.epics.is_empty() {
-                self.render_epic_report(ui, &visible);
+            self.render_stats(ui, &visible);
+            if !self.report_group_by.is_empty() {
+                let ctx = PivotContext {
+                    epics: &self.config.epics,
+                    week_start: self.config.behavior.week_start,
+                    running: running_id,
+                };
+                let nodes = pivot(&visible, &self.report_group_by, &ctx);
+                let id = ui.make_persistent_id("pivot");
+                for node in &nodes {
+                    self.render_pivot_node(ui, node, id);
+                }
+                let total = visible
+                    .iter()
+                    .fold(Duration::zero(), |a, f| a + (f.end - f.start));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Total").strong());
+                    ui.label(
+                        egui::RichText::new(fmt_duration(total))
+                            .color(CLR_PURPLE)
+                            .strong(),
+                    );
+                });
+            } else if self.report_use_epics && !self.config.epics.is_empty() {
+                self.render_epic_report(ui, &visible, running_id);
             } else {
-                let report = Report::from_frames(&visible);
-                render_project_report(ui, &report, true);
+                let report = Report::from_frames_with_running(&visible, running_id);
+                render_project_report(
+                    ui,
+                    &report,
+                    &self.project_colors,
+                    self.config.behavior.project_separator.as_deref(),
+                    true,
+                );
             }
         });
     }
 
-    fn render_epic_report(&self, ui: &mut egui::Ui, frames: &[Frame]) {
+    /// Collapsible statistics for the frames in the report: averages, habits
+    /// and each project's hours per week.
+    fn render_stats(&mut self, ui: &mut egui::Ui, frames: &[Frame]) {
+        let ctx = StatsContext::new(self.config.behavior.week_start, self.report_stats_weeks);
+        let stats = Stats::from_frames(frames, &ctx);
+        let value = |ui: &mut egui::Ui, text: String| {
+            ui.label(egui::RichText::new(text).color(CLR_PURPLE));
+        };
+        egui::CollapsingHeader::new(egui::RichText::new("Statistics").strong())
+            .id_salt("report-stats")
+            .show(ui, |ui| {
+                egui::Grid::new("report-stats-summary")
+                    .num_columns(2)
+                    .spacing([16.0, 4.0])
+                    .show(ui, |ui| {
+                        ui.label("Daily average");
+                        value(ui, fmt_duration(stats.daily_average));
+                        ui.end_row();
+                        ui.label("Frame average");
+                        value(ui, fmt_duration(stats.frame_average));
+                        ui.end_row();
+                        ui.label("Longest streak");
+                        value(
+                            ui,
+                            stats.longest_streak.map_or("—".into(), |s| {
+                                format!(
+                                    "{} {} ({} – {})",
+                                    s.days(),
+                                    if s.days() == 1 { "day" } else { "days" },
+                                    s.first.format("%d %b"),
+                                    s.last.format("%d %b %Y")
+                                )
+                            }),
+                        );
+                        ui.end_row();
+                        ui.label("Busiest weekday");
+                        value(
+                            ui,
+                            stats.busiest_weekday().map_or("—".into(), |(day, total)| {
+                                format!("{day} ({})", fmt_duration(total))
+                            }),
+                        );
+                        ui.end_row();
+                        ui.label("Switches per day");
+                        value(ui, format!("{:.1}", stats.switches_per_day));
+                        ui.end_row();
+                    });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Weekly trend").strong());
+                    ui.add(
+                        egui::DragValue::new(&mut self.report_stats_weeks)
+                            .range(1..=52)
+                            .suffix(" weeks"),
+                    );
+                });
+                egui::Grid::new("report-stats-trend")
+                    .striped(true)
+                    .spacing([16.0, 2.0])
+                    .show(ui, |ui| {
+                        ui.label("");
+                        for week in &stats.trend_weeks {
+                            ui.label(
+                                egui::RichText::new(week.format("%d %b").to_string())
+                                    .color(egui::Color32::GRAY)
+                                    .small(),
+                            );
+                        }
+                        ui.end_row();
+                        for trend in &stats.trends {
+                            let color = self.project_color(&trend.project).unwrap_or(CLR_YELLOW);
+                            ui.label(egui::RichText::new(&trend.project).strong().color(color));
+                            for week in &trend.weeks {
+                                value(ui, format!("{:.1}h", week.num_seconds() as f64 / 3600.0));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        ui.separator();
+    }
+
+    /// The "Group by" chain: current dimensions, a picker for the next one, and reset.
+    fn group_by_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Group by:");
+            if self.report_group_by.is_empty() {
+                ui.label(egui::RichText::new("project (default)").color(egui::Color32::GRAY));
+            }
+            for (i, dim) in self.report_group_by.iter().enumerate() {
+                if i > 0 {
+                    ui.label("›");
+                }
+                ui.label(egui::RichText::new(dim.to_string()).strong());
+            }
+            let available: Vec<Dimension> = Dimension::ALL
+                .into_iter()
+                .filter(|d| !self.report_group_by.contains(d))
+                .filter(|d| *d != Dimension::Epic || !self.config.epics.is_empty())
+                .collect();
+            if !available.is_empty() {
+                egui::ComboBox::from_id_salt("report-group-by")
+                    .selected_text("+ add")
+                    .show_ui(ui, |ui| {
+                        for dim in available {
+                            if ui.selectable_label(false, dim.to_string()).clicked() {
+                                self.report_group_by.push(dim);
+                            }
+                        }
+                    });
+            }
+            if !self.report_group_by.is_empty() && ui.button("Reset").clicked() {
+                self.report_group_by.clear();
+            }
+        });
+    }
+
+    /// One group of the pivot report, collapsible when it has children.
+    fn render_pivot_node(&self, ui: &mut egui::Ui, node: &PivotNode, parent: egui::Id) {
+        let color = match node.dimension {
+            Dimension::Epic => CLR_CYAN,
+            Dimension::Project => self.project_color(&node.label).unwrap_or(CLR_YELLOW),
+            Dimension::Tag => CLR_CYAN,
+            Dimension::Day | Dimension::Week | Dimension::Weekday => egui::Color32::WHITE,
+        };
+        let label = match node.dimension {
+            Dimension::Epic => format!("◆  {}", node.label),
+            Dimension::Tag => format!("[{}]", node.label),
+            _ => node.label.clone(),
+        };
+        let header = |ui: &mut egui::Ui| {
+            ui.label(egui::RichText::new(&label).strong().color(color));
+            ui.label(
+                egui::RichText::new(fmt_duration(node.total))
+                    .color(CLR_PURPLE)
+                    .strong(),
+            );
+            if node.running {
+                running_label(ui);
+            }
+        };
+        ui.add_space(2.0);
+        if node.children.is_empty() {
+            ui.horizontal(header);
+            return;
+        }
+        let id = parent.with(&node.label);
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+            .show_header(ui, header)
+            .body(|ui| {
+                for child in &node.children {
+                    self.render_pivot_node(ui, child, id);
+                }
+            });
+    }
+
+    fn render_epic_report(&self, ui: &mut egui::Ui, frames: &[Frame], running: Option<Uuid>) {
         let epics = &self.config.epics;
         let mut buckets: Vec<(&str, Vec<&Frame>)> =
             epics.iter().map(|e| (e.name.as_str(), vec![])).collect();
         let mut unassigned: Vec<&Frame> = vec![];
 
+        let resolver = EpicResolver::new(epics);
         for frame in frames {
-            match resolve_epic(frame, epics) {
-                Some(name) => {
-                    if let Some(b) = buckets.iter_mut().find(|(n, _)| *n == name) {
+            match resolver.resolve(frame) {
+                Some(epic) => {
+                    if let Some(b) = buckets.iter_mut().find(|(n, _)| *n == epic.name) {
                         b.1.push(frame);
                     }
                 }
@@ -62,7 +282,7 @@ impl WatsonApp {
 
         for (name, epic_frames) in buckets.iter().filter(|(_, f)| !f.is_empty()) {
             let owned: Vec<Frame> = epic_frames.iter().copied().cloned().collect();
-            let report = Report::from_frames(&owned);
+            let report = Report::from_frames_with_running(&owned, running);
             ui.add_space(4.0);
             ui.horizontal(|ui| {
                 ui.label(
@@ -76,13 +296,19 @@ impl WatsonApp {
                         .small(),
                 );
             });
-            render_project_report(ui, &report, false);
+            render_project_report(
+                ui,
+                &report,
+                &self.project_colors,
+                self.config.behavior.project_separator.as_deref(),
+                false,
+            );
             ui.add_space(4.0);
         }
 
         if !unassigned.is_empty() {
             let owned: Vec<Frame> = unassigned.iter().copied().cloned().collect();
-            let report = Report::from_frames(&owned);
+            let report = Report::from_frames_with_running(&owned, running);
             ui.add_space(4.0);
             ui.horizontal(|ui| {
                 ui.label(
@@ -96,7 +322,13 @@ impl WatsonApp {
                         .small(),
                 );
             });
-            render_project_report(ui, &report, false);
+            render_project_report(
+                ui,
+                &report,
+                &self.project_colors,
+                self.config.behavior.project_separator.as_deref(),
+                false,
+            );
         }
 
         ui.separator();
@@ -112,36 +344,48 @@ impl WatsonApp {
     }
 }
 
-pub(crate) fn render_project_report(ui: &mut egui::Ui, report: &Report, show_total: bool) {
+pub(crate) fn render_project_report(
+    ui: &mut egui::Ui,
+    report: &Report,
+    colors: &HashMap<String, egui::Color32>,
+    separator: Option<&str>,
+    show_total: bool,
+) {
+    if let Some(separator) = separator {
+        for node in &report.tree(separator) {
+            render_project_node(ui, node, colors);
+        }
+        render_total(ui, report, show_total);
+        return;
+    }
     for project in &report.projects {
+        let color = colors.get(&project.name).copied().unwrap_or(CLR_YELLOW);
         ui.add_space(2.0);
         if project.tags.is_empty() {
             ui.horizontal(|ui| {
-                ui.label(
-                    egui::RichText::new(&project.name)
-                        .strong()
-                        .color(CLR_YELLOW),
-                );
+                ui.label(egui::RichText::new(&project.name).strong().color(color));
                 ui.label(
                     egui::RichText::new(fmt_duration(project.total))
                         .color(CLR_PURPLE)
                         .strong(),
                 );
+                if project.running {
+                    running_label(ui);
+                }
             });
         } else {
             let id = ui.make_persistent_id(&project.name);
             egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
                 .show_header(ui, |ui| {
-                    ui.label(
-                        egui::RichText::new(&project.name)
-                            .strong()
-                            .color(CLR_YELLOW),
-                    );
+                    ui.label(egui::RichText::new(&project.name).strong().color(color));
                     ui.label(
                         egui::RichText::new(fmt_duration(project.total))
                             .color(CLR_PURPLE)
                             .strong(),
                     );
+                    if project.running {
+                        running_label(ui);
+                    }
                 })
                 .body(|ui| {
                     for tag in &project.tags {
@@ -162,6 +406,10 @@ pub(crate) fn render_project_report(ui: &mut egui::Ui, report: &Report, show_tot
                 });
         }
     }
+    render_total(ui, report, show_total);
+}
+
+fn render_total(ui: &mut egui::Ui, report: &Report, show_total: bool) {
     if show_total && report.projects.len() > 1 {
         ui.separator();
         ui.horizontal(|ui| {
@@ -175,3 +423,57 @@ pub(crate) fn render_project_report(ui: &mut egui::Ui, report: &Report, show_tot
     }
     ui.add_space(4.0);
 }
+
+/// One level of the nested project tree: a collapsible header with the rolled-up
+/// total, then the node's own tags and its children.
+fn render_project_node(
+    ui: &mut egui::Ui,
+    node: &ProjectNode,
+    colors: &HashMap<String, egui::Color32>,
+) {
+    let color = colors.get(&node.path).copied().unwrap_or(CLR_YELLOW);
+    let header = |ui: &mut egui::Ui| {
+        ui.label(egui::RichText::new(&node.name).strong().color(color));
+        ui.label(
+            egui::RichText::new(fmt_duration(node.total))
+                .color(CLR_PURPLE)
+                .strong(),
+        );
+        if node.running {
+            running_label(ui);
+        }
+    };
+    ui.add_space(2.0);
+    if node.tags.is_empty() && node.children.is_empty() {
+        ui.horizontal(header);
+        return;
+    }
+    let id = ui.make_persistent_id(("project-tree", &node.path));
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+        .show_header(ui, header)
+        .body(|ui| {
+            for tag in &node.tags {
+                ui.horizontal(|ui| {
+                    ui.add_space(16.0);
+                    ui.label(
+                        egui::RichText::new(format!("[{}]", tag.name))
+                            .color(CLR_CYAN)
+                            .small(),
+                    );
+                    ui.label(
+                        egui::RichText::new(fmt_duration(tag.total))
+                            .color(CLR_PURPLE)
+                            .small(),
+                    );
+                });
+            }
+            for child in &node.children {
+                render_project_node(ui, child, colors);
+            }
+        });
+}
+
+/// Small green marker for report rows that include the running frame.
+fn running_label(ui: &mut egui::Ui) {
+    ui.label(egui::RichText::new("● running").color(CLR_GREEN).small());
+}
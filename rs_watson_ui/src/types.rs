@@ -1,13 +1,19 @@
+use chrono::{DateTime, Utc};
+use eframe::egui;
 use rs_watson::Frame;
+use rs_watson::config::EpicConfig;
 use uuid::Uuid;
 
-use crate::format::fmt_local_dt;
+use crate::format::{fmt_local_dt, parse_tags};
 
 #[derive(PartialEq, Clone, Copy)]
 pub(crate) enum Tab {
     Log,
+    Calendar,
     Add,
     Report,
+    Charts,
+    Epics,
 }
 
 pub(crate) struct EditState {
@@ -31,3 +37,120 @@ impl EditState {
         }
     }
 }
+
+pub(crate) struct SplitState {
+    pub(crate) id: Uuid,
+    pub(crate) at: String,
+    pub(crate) project: String,
+    pub(crate) tags: String,
+    pub(crate) error: Option<String>,
+}
+
+impl SplitState {
+    /// Pre-fills the split time with the frame's midpoint and the second part
+    /// with the frame's own project and tags.
+    pub(crate) fn from_frame(f: &Frame) -> Self {
+        Self {
+            id: f.id,
+            at: fmt_local_dt(f.start + (f.end - f.start) / 2),
+            project: f.project.clone(),
+            tags: f.tags.join(", "),
+            error: None,
+        }
+    }
+}
+
+/// What a command palette entry does when chosen.
+#[derive(Clone)]
+pub(crate) enum PaletteAction {
+    Start { project: String, tags: Vec<String> },
+    Stop,
+    Cancel,
+    Open(Tab),
+}
+
+/// The open command palette.
+#[derive(Default)]
+pub(crate) struct PaletteState {
+    pub(crate) query: String,
+    /// Index of the highlighted entry among the matches.
+    pub(crate) selected: usize,
+}
+
+/// Which part of a calendar block is being dragged.
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum DragMode {
+    Move,
+    ResizeStart,
+    ResizeEnd,
+}
+
+/// A frame being dragged in the Calendar tab. `start` and `end` are the
+/// preview times, saved with `Watson::edit` when the drag ends.
+pub(crate) struct CalendarDrag {
+    pub(crate) id: Uuid,
+    pub(crate) mode: DragMode,
+    /// Pointer position where the drag began.
+    pub(crate) origin: egui::Pos2,
+    pub(crate) start: DateTime<Utc>,
+    pub(crate) end: DateTime<Utc>,
+}
+
+/// The epic being edited in the Epics tab. List fields are comma-separated.
+#[derive(Default)]
+pub(crate) struct EpicForm {
+    /// Name of the epic in config.toml, or `None` for a new one.
+    pub(crate) original: Option<String>,
+    pub(crate) name: String,
+    pub(crate) projects: String,
+    pub(crate) glob: String,
+    pub(crate) regex: String,
+    pub(crate) tags: String,
+    pub(crate) exclude_tags: String,
+    pub(crate) priority: i32,
+    /// Save even though some projects are unknown.
+    pub(crate) force: bool,
+}
+
+impl EpicForm {
+    pub(crate) fn from_epic(epic: &EpicConfig) -> Self {
+        let projects: Vec<&str> = epic
+            .project
+            .iter()
+            .chain(&epic.projects)
+            .map(String::as_str)
+            .collect();
+        Self {
+            original: Some(epic.name.clone()),
+            name: epic.name.clone(),
+            projects: projects.join(", "),
+            glob: epic.project_glob.clone().unwrap_or_default(),
+            regex: epic.project_regex.clone().unwrap_or_default(),
+            tags: epic.tags.join(", "),
+            exclude_tags: epic.exclude_tags.join(", "),
+            priority: epic.priority,
+            force: false,
+        }
+    }
+
+    /// A single project is written as `project`, several as `projects`.
+    pub(crate) fn to_epic(&self) -> EpicConfig {
+        let mut projects = parse_tags(&self.projects);
+        let optional = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let (project, projects) = if projects.len() == 1 {
+            (projects.pop(), Vec::new())
+        } else {
+            (None, projects)
+        };
+        EpicConfig {
+            name: self.name.trim().to_string(),
+            project,
+            projects,
+            project_glob: optional(&self.glob),
+            project_regex: optional(&self.regex),
+            tags: parse_tags(&self.tags),
+            exclude_tags: parse_tags(&self.exclude_tags),
+            priority: self.priority,
+        }
+    }
+}
@@ -36,8 +36,15 @@ pub(crate) fn project_autocomplete(
     );
 }
 
-/// "From … To … Clear" filter row — call inside a `ui.horizontal` closure.
-pub(crate) fn date_filter_bar(ui: &mut egui::Ui, from: &mut String, to: &mut String) {
+/// "From … To … Filter … Clear" row — call inside a `ui.horizontal` closure.
+/// `query_error` marks the query field red and explains why on hover.
+pub(crate) fn date_filter_bar(
+    ui: &mut egui::Ui,
+    from: &mut String,
+    to: &mut String,
+    query: &mut String,
+    query_error: Option<String>,
+) {
     ui.label("From");
     ui.add(
         egui::TextEdit::singleline(from)
@@ -50,9 +57,21 @@ pub(crate) fn date_filter_bar(ui: &mut egui::Ui, from: &mut String, to: &mut Str
             .hint_text("YYYY-MM-DD")
             .desired_width(100.0),
     );
+    ui.label("Filter");
+    let mut edit = egui::TextEdit::singleline(query)
+        .hint_text("project:acme and not tag:meeting")
+        .desired_width(220.0);
+    if query_error.is_some() {
+        edit = edit.text_color(CLR_RED);
+    }
+    let resp = ui.add(edit);
+    if let Some(e) = query_error {
+        resp.on_hover_text(e);
+    }
     if ui.small_button("Clear").clicked() {
         from.clear();
         to.clear();
+        query.clear();
     }
 }
 